@@ -0,0 +1,245 @@
+//! Date and time
+//!
+//! `DATETIME` is 6 bytes: year (0 = 2000), month (1 = Jan), day, hour (24), minute, second
+//!
+//! It's updated from the VM [Clock] by [VM::run_frame()] and [VM::update_datetime()], if the
+//! value has changed at the [DateTimeInterrupt] unit (or higher) then `IRQ_DATETIME` is triggered
+
+use crate::history::Event;
+use crate::{address, VM};
+use maikor_platform::mem::interrupt_flags;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct DateTime {
+    pub year: u16,
+    /// 1 = Jan
+    pub month: u8,
+    /// 1 = first day of month
+    pub day: u8,
+    /// 24 hour
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Convert seconds since 1970-01-01 00:00:00 UTC
+    pub fn from_unix_secs(secs: u64) -> DateTime {
+        let days = (secs / 86400) as i64;
+        let time = secs % 86400;
+        //see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        DateTime {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time / 3600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8,
+        }
+    }
+
+    /// Bytes as stored in `DATETIME`, year is clamped to 2000..=2255
+    pub fn to_bytes(&self) -> [u8; 6] {
+        [
+            self.year.clamp(2000, 2255).saturating_sub(2000) as u8,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        ]
+    }
+}
+
+/// Source of the date and time for the VM, replace with [VM::set_clock()]
+pub trait Clock {
+    fn now(&mut self) -> DateTime;
+}
+
+/// Default [Clock], current system time in UTC
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> DateTime {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        DateTime::from_unix_secs(secs)
+    }
+}
+
+/// Smallest change that triggers `IRQ_DATETIME`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum DateTimeInterrupt {
+    Second,
+    #[default]
+    Minute,
+}
+
+impl VM {
+    /// Replace the clock used for `DATETIME`, the memory is updated immediately without
+    /// triggering `IRQ_DATETIME`
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+        let bytes = self.clock.now().to_bytes();
+        self.memory[address::DATETIME..address::DATETIME + bytes.len()].copy_from_slice(&bytes);
+    }
+
+    pub fn set_datetime_interrupt(&mut self, unit: DateTimeInterrupt) {
+        self.datetime_interrupt = unit;
+    }
+
+    /// Read the clock and update `DATETIME`, triggering `IRQ_DATETIME` if needed
+    /// Called by [VM::run_frame()], hosts using [VM::step()] directly must call this
+    pub fn update_datetime(&mut self) {
+        let bytes = self.clock.now().to_bytes();
+        self.write_datetime(bytes);
+    }
+
+    /// Write `bytes` to `DATETIME` if they've changed, triggering `IRQ_DATETIME` if needed
+    /// Recorded in history as a single op
+    pub(crate) fn write_datetime(&mut self, bytes: [u8; 6]) {
+        let current = &self.memory[address::DATETIME..address::DATETIME + bytes.len()];
+        if current == bytes {
+            return;
+        }
+        let compare_len = match self.datetime_interrupt {
+            DateTimeInterrupt::Second => 6,
+            DateTimeInterrupt::Minute => 5,
+        };
+        let changed = current[..compare_len] != bytes[..compare_len];
+        self.begin_history_record();
+        self.log_event(Event::DateTime(bytes));
+        self.log_mem(address::DATETIME, bytes.len(), false);
+        self.memory[address::DATETIME..address::DATETIME + bytes.len()].copy_from_slice(&bytes);
+        if changed {
+            self.trigger_interrupt(interrupt_flags::IRQ_DATETIME);
+        }
+        self.end_history_record();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::mem::address::interrupt;
+    use maikor_platform::ops;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct FakeClock(Rc<Cell<u64>>);
+
+    impl Clock for FakeClock {
+        fn now(&mut self) -> DateTime {
+            DateTime::from_unix_secs(self.0.get())
+        }
+    }
+
+    #[test]
+    fn from_unix_secs() {
+        assert_eq!(
+            DateTime::from_unix_secs(0),
+            DateTime {
+                year: 1970,
+                month: 1,
+                day: 1,
+                ..DateTime::default()
+            }
+        );
+        assert_eq!(
+            DateTime::from_unix_secs(951782400),
+            DateTime {
+                year: 2000,
+                month: 2,
+                day: 29,
+                ..DateTime::default()
+            }
+        );
+        let date = DateTime::from_unix_secs(1_700_000_000);
+        assert_eq!(date.to_bytes(), [23, 11, 14, 22, 13, 20]);
+        assert_eq!(DateTime::from_unix_secs(0).to_bytes()[0], 0);
+    }
+
+    #[test]
+    fn interrupt() {
+        let time = Rc::new(Cell::new(1_700_000_000));
+        let mut vm = VM::new_test();
+        vm.set_clock(Box::new(FakeClock(time.clone())));
+        assert_eq!(
+            vm.memory[address::DATETIME..address::DATETIME + 6],
+            [23, 11, 14, 22, 13, 20]
+        );
+        vm.debug_set_mem_range(0, &[ops::JMP_ADDR, 0, 0]);
+        vm.debug_set_mem_range(
+            interrupt::IRQ_DATETIME,
+            &[ops::INC_ADDR_BYTE, 0x50, 0x00, ops::RETI],
+        );
+        vm.debug_set_mem(address::IRQ_CONTROL as u16, interrupt_flags::IRQ_DATETIME);
+
+        time.set(1_700_000_001);
+        vm.run_frame();
+        assert_eq!(vm.memory[0x5000], 0);
+        assert_eq!(vm.memory[address::DATETIME + 5], 21);
+
+        time.set(1_700_000_040);
+        vm.run_frame();
+        assert_eq!(vm.memory[0x5000], 1);
+        assert_eq!(vm.memory[address::DATETIME + 4], 14);
+
+        vm.set_datetime_interrupt(DateTimeInterrupt::Second);
+        vm.run_frame();
+        assert_eq!(vm.memory[0x5000], 1);
+        time.set(1_700_000_041);
+        vm.update_datetime();
+        vm.step();
+        vm.step();
+        assert_eq!(vm.memory[0x5000], 2);
+    }
+
+    #[test]
+    fn history() {
+        let time = Rc::new(Cell::new(1_700_000_000));
+        let mut vm = VM::new_test();
+        vm.set_clock(Box::new(FakeClock(time.clone())));
+        vm.debug_set_mem_range(0, &[ops::JMP_ADDR, 0, 0]);
+        vm.debug_set_mem_range(interrupt::IRQ_DATETIME, &[ops::RETI]);
+        vm.debug_set_mem(address::IRQ_CONTROL as u16, interrupt_flags::IRQ_DATETIME);
+        vm.enable_history(10);
+        vm.set_history_checkpoints(10, 10);
+        vm.step();
+
+        time.set(1_700_000_060);
+        vm.update_datetime();
+        assert_eq!(vm.pc, interrupt::IRQ_DATETIME);
+        vm.step();
+        time.set(1_700_000_120);
+        for _ in 0..20 {
+            vm.step();
+        }
+
+        //replayed from the checkpoint, so the clock is not read again
+        while vm.pc != interrupt::IRQ_DATETIME {
+            assert!(vm.step_back());
+        }
+        assert_eq!(vm.memory[address::DATETIME + 4], 14);
+        assert!(vm.step_back());
+        assert_eq!(vm.pc, 0);
+        assert_eq!(vm.op_executed, 1);
+        assert_eq!(vm.memory[address::DATETIME + 4], 13);
+        assert!(vm.step_back());
+        assert_eq!(vm.op_executed, 0);
+        assert!(!vm.step_back());
+    }
+}
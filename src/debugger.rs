@@ -0,0 +1,543 @@
+use crate::register::{offset, Register};
+use crate::{address, sizes, VM};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+/// Max number of watchpoint hits stored, once reached the oldest are discarded
+const MAX_WATCH_HITS: usize = 1024;
+
+/// Breakpoint condition, checked before the op at the breakpoint address is executed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Condition {
+    /// `register` is a register id (see [maikor_platform::registers::id]), extended
+    /// registers (AX, etc) are compared as words
+    Register { register: u8, value: u16 },
+    /// `flag` is a flag mask (see [maikor_platform::registers::flags]), if `set` is true then
+    /// all bits in the mask must be set, otherwise they must all be clear
+    Flag { flag: u8, set: bool },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    /// All conditions must match for the breakpoint to trigger,
+    /// if empty it always triggers
+    pub conditions: Vec<Condition>,
+}
+
+/// Why [VM::run_until()] returned
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StopReason {
+    /// PC is at the address of a breakpoint and its conditions matched,
+    /// the op at PC has not been executed
+    Breakpoint(u16),
+    /// VM has halted, see [VM::error]
+    Halted,
+    /// Cycle budget was used
+    CycleLimit,
+    /// Last op accessed memory covered by a watchpoint, see [VM::take_watch_hits()]
+    Watchpoint,
+    /// No more ops can be undone, see [VM::run_backwards_until()]
+    HistoryStart,
+}
+
+/// Memory range to watch, see [VM::add_watchpoint()]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Watchpoint {
+    /// First address, inclusive
+    pub start: u16,
+    /// Last address, inclusive
+    pub end: u16,
+    pub on_read: bool,
+    pub on_write: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Memory access by an op that matched a watchpoint
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WatchHit {
+    pub addr: u16,
+    pub access: Access,
+    /// For reads this is the same as `new_value`
+    pub old_value: u8,
+    pub new_value: u8,
+    /// PC of the op that accessed memory
+    pub pc: u16,
+    pub op: u8,
+}
+
+/// Function call made by CALL that hasn't returned, see [VM::call_stack()]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CallFrame {
+    /// Address RET will jump to
+    pub return_addr: u16,
+    /// FP for this call, the caller's FP and `return_addr` are the 4 bytes before it
+    pub frame_pointer: u16,
+    /// Id of the code bank loaded now (not when the call was made, as that isn't stored on
+    /// the stack) in the slot `return_addr` is in, None if it's in main code
+    pub current_code_bank: Option<u8>,
+}
+
+impl Display for CallFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.current_code_bank {
+            Some(bank) => write!(
+                f,
+                "{:04X} [{:02X}] FP: {:04X}",
+                self.return_addr, bank, self.frame_pointer
+            ),
+            None => write!(f, "{:04X} FP: {:04X}", self.return_addr, self.frame_pointer),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    watch_hits: VecDeque<WatchHit>,
+    //total hits recorded, used to detect new hits as watch_hits stops growing once full
+    hit_count: usize,
+    //op currently being executed, memory accesses outside of ops (i.e. by the host)
+    //don't trigger watchpoints
+    pub(crate) current_op: Option<u8>,
+}
+
+impl VM {
+    /// Add breakpoint at `addr`, replacing any existing breakpoint at the same address
+    pub fn add_breakpoint(&mut self, addr: u16, conditions: Vec<Condition>) {
+        self.remove_breakpoint(addr);
+        self.debugger
+            .breakpoints
+            .push(Breakpoint { addr, conditions });
+    }
+
+    /// Remove breakpoint at `addr`
+    /// Returns true if there was a breakpoint
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        let len = self.debugger.breakpoints.len();
+        self.debugger.breakpoints.retain(|bp| bp.addr != addr);
+        len != self.debugger.breakpoints.len()
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.debugger.breakpoints
+    }
+
+    /// Execute ops until a breakpoint is hit, the VM halts or at least `max_cycles` have
+    /// been executed
+    /// Breakpoints are checked before each op, except the first so that execution can be
+    /// resumed from a breakpoint
+    pub fn run_until(&mut self, max_cycles: usize) -> StopReason {
+        let mut cycles = 0;
+        let mut first = true;
+        loop {
+            if self.halted {
+                return StopReason::Halted;
+            }
+            if !first && self.is_at_breakpoint() {
+                return StopReason::Breakpoint(self.pc);
+            }
+            if cycles >= max_cycles {
+                return StopReason::CycleLimit;
+            }
+            first = false;
+            let hits = self.debugger.hit_count;
+            cycles += self.step();
+            if self.debugger.hit_count != hits {
+                return StopReason::Watchpoint;
+            }
+        }
+    }
+
+    /// Returns true if there is a breakpoint at PC and its conditions match
+    pub fn is_at_breakpoint(&self) -> bool {
+        self.debugger
+            .breakpoints
+            .iter()
+            .filter(|bp| bp.addr == self.pc)
+            .any(|bp| bp.conditions.iter().all(|cond| self.check_condition(cond)))
+    }
+
+    /// Watch memory from `start` to `end` (inclusive) for reads and/or writes by ops
+    pub fn add_watchpoint(&mut self, start: u16, end: u16, on_read: bool, on_write: bool) {
+        self.debugger.watchpoints.push(Watchpoint {
+            start,
+            end,
+            on_read,
+            on_write,
+        });
+    }
+
+    /// Remove all watchpoints that cover `addr`
+    /// Returns true if any were removed
+    pub fn remove_watchpoint(&mut self, addr: u16) -> bool {
+        let len = self.debugger.watchpoints.len();
+        self.debugger
+            .watchpoints
+            .retain(|wp| addr < wp.start || addr > wp.end);
+        len != self.debugger.watchpoints.len()
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.debugger.watchpoints.clear();
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.debugger.watchpoints
+    }
+
+    /// Calls that haven't returned yet, innermost first
+    ///
+    /// Found by walking the FP chain on the stack (CALL pushes FP and the return address then
+    /// points FP after them), the walk stops at a FP that is outside the stack or doesn't
+    /// point further down the stack than the previous one
+    pub fn call_stack(&self) -> Vec<CallFrame> {
+        let mut frames = vec![];
+        let mut fp = self.get_fp() as usize;
+        while fp >= address::STACK + 4 && fp <= self.get_sp() as usize {
+            let caller_fp = u16::from_be_bytes([self.memory[fp - 4], self.memory[fp - 3]]);
+            let return_addr = u16::from_be_bytes([self.memory[fp - 2], self.memory[fp - 1]]);
+            frames.push(CallFrame {
+                return_addr,
+                frame_pointer: fp as u16,
+                current_code_bank: self.code_bank_at(return_addr),
+            });
+            if caller_fp as usize >= fp {
+                break;
+            }
+            fp = caller_fp as usize;
+        }
+        frames
+    }
+
+    fn code_bank_at(&self, addr: u16) -> Option<u8> {
+        let addr = addr as usize;
+        if (address::CODE_BANK_1..address::CODE_BANK_1 + sizes::CODE_BANK).contains(&addr) {
+            Some(self.memory[address::CODE_BANK_1_ID])
+        } else if (address::CODE_BANK_2..address::CODE_BANK_2 + sizes::CODE_BANK).contains(&addr) {
+            Some(self.memory[address::CODE_BANK_2_ID])
+        } else {
+            None
+        }
+    }
+
+    /// Returns and clears watchpoint hits, oldest first
+    pub fn take_watch_hits(&mut self) -> Vec<WatchHit> {
+        self.debugger.watch_hits.drain(..).collect()
+    }
+
+    /// Put back hits removed by [VM::take_watch_hits()], before any new hits
+    pub(crate) fn restore_watch_hits(&mut self, hits: Vec<WatchHit>) {
+        let new_hits = std::mem::take(&mut self.debugger.watch_hits);
+        self.debugger.watch_hits = hits.into();
+        self.debugger.watch_hits.extend(new_hits);
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_watching(&self) -> bool {
+        !self.debugger.watchpoints.is_empty()
+    }
+
+    #[inline(always)]
+    pub(crate) fn watch_read(&mut self, addr: u16, value: u8) {
+        if self.is_watching() {
+            self.record_watch(addr, Access::Read, value, value);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn watch_write(&mut self, addr: u16, old_value: u8, new_value: u8) {
+        if self.is_watching() {
+            self.record_watch(addr, Access::Write, old_value, new_value);
+        }
+    }
+
+    /// Record bulk memory accesses (MCPY and MSWP)
+    /// `before` is the content of dst and src before the op
+    pub(crate) fn watch_transfer(
+        &mut self,
+        dst: u16,
+        src: u16,
+        before: (Vec<u8>, Vec<u8>),
+        swap: bool,
+    ) {
+        let (dst_before, src_before) = before;
+        for i in 0..dst_before.len() {
+            let dst_addr = dst.wrapping_add(i as u16);
+            let src_addr = src.wrapping_add(i as u16);
+            self.watch_read(src_addr, src_before[i]);
+            if swap {
+                self.watch_read(dst_addr, dst_before[i]);
+            }
+            self.watch_write(dst_addr, dst_before[i], self.memory[dst_addr as usize]);
+            if swap {
+                self.watch_write(src_addr, src_before[i], self.memory[src_addr as usize]);
+            }
+        }
+    }
+
+    fn record_watch(&mut self, addr: u16, access: Access, old_value: u8, new_value: u8) {
+        let op = match self.debugger.current_op {
+            Some(op) => op,
+            None => return,
+        };
+        let matched = self.debugger.watchpoints.iter().any(|wp| {
+            addr >= wp.start
+                && addr <= wp.end
+                && match access {
+                    Access::Read => wp.on_read,
+                    Access::Write => wp.on_write,
+                }
+        });
+        if matched {
+            self.debugger.hit_count = self.debugger.hit_count.wrapping_add(1);
+            if self.debugger.watch_hits.len() >= MAX_WATCH_HITS {
+                self.debugger.watch_hits.pop_front();
+            }
+            self.debugger.watch_hits.push_back(WatchHit {
+                addr,
+                access,
+                old_value,
+                new_value,
+                pc: self.pc,
+                op,
+            });
+        }
+    }
+
+    fn check_condition(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Register { register, value } => {
+                let reg = Register::from(*register);
+                let actual = if reg.size == 2 {
+                    u16::from_be_bytes([self.registers[reg.addr], self.registers[reg.addr + 1]])
+                } else {
+                    self.registers[reg.addr] as u16
+                };
+                actual == *value
+            }
+            Condition::Flag { flag, set } => {
+                if *set {
+                    self.check_flag(*flag)
+                } else {
+                    self.registers[offset::FLAGS] & flag == 0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::op_params::INDIRECT;
+    use maikor_platform::ops::{
+        ADD_REG_NUM_BYTE, CALL_ADDR, CPY_ADDR_REG_BYTE, CPY_REG_REG_BYTE, EHALT, HALT,
+        INC_ADDR_BYTE, INC_REG_BYTE, JMP_ADDR, MEM_CPY_ADDR_ADDR_BYTE, MSWP_ADDR_ADDR_BYTE, RET,
+    };
+    use maikor_platform::registers::{flags, id};
+
+    fn setup() -> VM {
+        let mut vm = VM::new_test();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            INC_REG_BYTE, id::AL,
+            ADD_REG_NUM_BYTE, id::BL, 2,
+            JMP_ADDR, 0, 0,
+        ]);
+        vm
+    }
+
+    #[test]
+    fn breakpoint() {
+        let mut vm = setup();
+        vm.add_breakpoint(2, vec![]);
+        assert_eq!(vm.run_until(1000), StopReason::Breakpoint(2));
+        assert_eq!(vm.registers[1], 1);
+        assert_eq!(vm.run_until(1000), StopReason::Breakpoint(2));
+        assert_eq!(vm.registers[1], 2);
+        assert_eq!(vm.registers[3], 2);
+
+        assert!(vm.remove_breakpoint(2));
+        assert!(!vm.remove_breakpoint(2));
+        assert_eq!(vm.run_until(10), StopReason::CycleLimit);
+        assert!(vm.cycles_executed >= 10);
+    }
+
+    #[test]
+    fn conditional_breakpoint() {
+        let mut vm = setup();
+        vm.add_breakpoint(
+            5,
+            vec![Condition::Register {
+                register: id::BX,
+                value: 6,
+            }],
+        );
+        assert_eq!(vm.run_until(1000), StopReason::Breakpoint(5));
+        assert_eq!(vm.registers[1], 3);
+
+        vm.add_breakpoint(
+            0,
+            vec![
+                Condition::Register {
+                    register: id::AL,
+                    value: 5,
+                },
+                Condition::Flag {
+                    flag: flags::ZERO,
+                    set: false,
+                },
+            ],
+        );
+        assert_eq!(vm.breakpoints().len(), 2);
+        assert_eq!(vm.run_until(1000), StopReason::Breakpoint(0));
+        assert_eq!(vm.registers[1], 5);
+
+        vm.clear_breakpoints();
+        assert_eq!(vm.run_until(1000), StopReason::CycleLimit);
+    }
+
+    #[test]
+    fn halted() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[INC_REG_BYTE, id::AL, HALT]);
+        assert_eq!(vm.run_until(1000), StopReason::Halted);
+        assert_eq!(vm.run_until(1000), StopReason::Halted);
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem(300, 9);
+        vm.registers[0] = 1;
+        vm.registers[1] = 44;
+        vm.registers[3] = 7;
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            CPY_ADDR_REG_BYTE, 1, 44, id::BL,
+            CPY_REG_REG_BYTE, id::CL, id::AX | INDIRECT,
+            MEM_CPY_ADDR_ADDR_BYTE, 1, 45, 1, 44, 2,
+            MSWP_ADDR_ADDR_BYTE, 1, 44, 1, 45, 1,
+            HALT,
+        ]);
+        vm.add_watchpoint(300, 300, true, true);
+        assert!(vm.take_watch_hits().is_empty());
+
+        assert_eq!(vm.run_until(1000), StopReason::Watchpoint);
+        let hit = |addr, access, old_value, new_value, pc, op| WatchHit {
+            addr,
+            access,
+            old_value,
+            new_value,
+            pc,
+            op,
+        };
+        assert_eq!(
+            vm.take_watch_hits(),
+            vec![hit(300, Access::Write, 9, 7, 0, CPY_ADDR_REG_BYTE)]
+        );
+        assert_eq!(vm.run_until(1000), StopReason::Watchpoint);
+        assert_eq!(
+            vm.take_watch_hits(),
+            vec![hit(300, Access::Read, 7, 7, 4, CPY_REG_REG_BYTE)]
+        );
+        assert_eq!(vm.registers[5], 7);
+        assert_eq!(vm.run_until(1000), StopReason::Watchpoint);
+        assert_eq!(
+            vm.take_watch_hits(),
+            vec![hit(300, Access::Read, 7, 7, 7, MEM_CPY_ADDR_ADDR_BYTE)]
+        );
+        assert_eq!(vm.run_until(1000), StopReason::Watchpoint);
+        assert_eq!(
+            vm.take_watch_hits(),
+            vec![
+                hit(300, Access::Read, 7, 7, 13, MSWP_ADDR_ADDR_BYTE),
+                hit(300, Access::Write, 7, 7, 13, MSWP_ADDR_ADDR_BYTE),
+            ]
+        );
+
+        assert!(vm.remove_watchpoint(300));
+        vm.add_watchpoint(0, 10, false, true);
+        vm.debug_set_mem(1, 1);
+        assert!(vm.take_watch_hits().is_empty());
+        assert_eq!(vm.run_until(1000), StopReason::Halted);
+    }
+
+    #[test]
+    fn watchpoint_hits_full() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[INC_ADDR_BYTE, 1, 44, JMP_ADDR, 0, 0]);
+        vm.add_watchpoint(300, 300, false, true);
+        for _ in 0..MAX_WATCH_HITS + 10 {
+            assert_eq!(vm.run_until(1000), StopReason::Watchpoint);
+        }
+        assert_eq!(vm.take_watch_hits().len(), MAX_WATCH_HITS);
+    }
+
+    #[test]
+    fn call_stack() {
+        let mut vm = VM::new_test();
+        let bank = (address::CODE_BANK_1 as u16).to_be_bytes();
+        vm.debug_set_mem(address::CODE_BANK_1_ID as u16, 3);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            CALL_ADDR, bank[0], bank[1],
+            HALT,
+            0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0,
+            EHALT,
+        ]);
+        vm.debug_set_mem_range(address::CODE_BANK_1 as u16, &[CALL_ADDR, 0, 20, RET]);
+        vm.enable_history(10);
+        vm.step();
+        vm.step();
+        let stack = address::STACK as u16;
+        assert_eq!(
+            vm.call_stack(),
+            vec![
+                CallFrame {
+                    return_addr: address::CODE_BANK_1 as u16 + 3,
+                    frame_pointer: stack + 8,
+                    current_code_bank: Some(3),
+                },
+                CallFrame {
+                    return_addr: 3,
+                    frame_pointer: stack + 4,
+                    current_code_bank: None,
+                }
+            ]
+        );
+        vm.step();
+        let report = vm.error_report().unwrap();
+        assert!(report.ends_with(&format!(
+            "Call stack:\n  {:04X} [03] FP: {:04X}\n  0003 FP: {:04X}",
+            address::CODE_BANK_1 + 3,
+            stack + 8,
+            stack + 4
+        )));
+
+        assert!(vm.step_back());
+        vm.debug_set_mem(20, RET);
+        vm.step();
+        assert_eq!(vm.call_stack().len(), 1);
+        vm.step();
+        assert!(vm.call_stack().is_empty());
+        assert_eq!(vm.pc, 3);
+
+        vm.step_back();
+        assert_eq!(vm.call_stack().len(), 1);
+        assert_eq!(vm.call_stack()[0].return_addr, 3);
+    }
+}
@@ -0,0 +1,1105 @@
+//! Decode Maikor bytecode into [Instruction]s
+//!
+//! This is used by the VM before executing each op and by the [disassembler](crate::disassembler)
+
+use crate::VM;
+use maikor_platform::op_params::*;
+use maikor_platform::ops;
+use maikor_platform::registers::id;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Most operands any op has
+pub const MAX_OPERANDS: usize = 3;
+/// Longest op in bytes (op, 3 words and 3 offset words)
+pub const MAX_LEN: usize = 13;
+
+/// Operand type, used to describe the layout of each op
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Kind {
+    Reg,
+    Addr,
+    Byte,
+    Word,
+}
+
+use Kind::{Addr as A, Byte as B, Reg as R, Word as W};
+
+/// Every op supported by the platform, see [maikor_platform::ops]
+#[rustfmt::skip]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Opcode {
+    Nop = ops::NOP,
+    Halt = ops::HALT,
+    CallAddr = ops::CALL_ADDR,
+    CallReg = ops::CALL_REG,
+    Ret = ops::RET,
+    Reti = ops::RETI,
+    SwapRegRegByte = ops::SWAP_REG_REG_BYTE,
+    SwapRegRegWord = ops::SWAP_REG_REG_WORD,
+    MemCpyAddrAddrByte = ops::MEM_CPY_ADDR_ADDR_BYTE,
+    MemCpyAddrRegByte = ops::MEM_CPY_ADDR_REG_BYTE,
+    MemCpyRegAddrByte = ops::MEM_CPY_REG_ADDR_BYTE,
+    MemCpyRegRegByte = ops::MEM_CPY_REG_REG_BYTE,
+    MemCpyAddrAddrReg = ops::MEM_CPY_ADDR_ADDR_REG,
+    MemCpyAddrRegReg = ops::MEM_CPY_ADDR_REG_REG,
+    MemCpyRegAddrReg = ops::MEM_CPY_REG_ADDR_REG,
+    MemCpyRegRegReg = ops::MEM_CPY_REG_REG_REG,
+    CpyRegRegByte = ops::CPY_REG_REG_BYTE,
+    CpyRegRegWord = ops::CPY_REG_REG_WORD,
+    CpyAddrRegByte = ops::CPY_ADDR_REG_BYTE,
+    CpyAddrRegWord = ops::CPY_ADDR_REG_WORD,
+    CpyRegAddrByte = ops::CPY_REG_ADDR_BYTE,
+    CpyRegAddrWord = ops::CPY_REG_ADDR_WORD,
+    CpyAddrAddrByte = ops::CPY_ADDR_ADDR_BYTE,
+    CpyAddrAddrWord = ops::CPY_ADDR_ADDR_WORD,
+    CpyRegNumByte = ops::CPY_REG_NUM_BYTE,
+    CpyRegNumWord = ops::CPY_REG_NUM_WORD,
+    CpyAddrNumByte = ops::CPY_ADDR_NUM_BYTE,
+    CpyAddrNumWord = ops::CPY_ADDR_NUM_WORD,
+    CmpRegAddrByte = ops::CMP_REG_ADDR_BYTE,
+    CmpRegAddrWord = ops::CMP_REG_ADDR_WORD,
+    CmpsRegAddrByte = ops::CMPS_REG_ADDR_BYTE,
+    CmpsRegAddrWord = ops::CMPS_REG_ADDR_WORD,
+    AddRegRegByte = ops::ADD_REG_REG_BYTE,
+    AddRegRegWord = ops::ADD_REG_REG_WORD,
+    AddRegNumByte = ops::ADD_REG_NUM_BYTE,
+    AddRegNumWord = ops::ADD_REG_NUM_WORD,
+    AddRegAddrByte = ops::ADD_REG_ADDR_BYTE,
+    AddRegAddrWord = ops::ADD_REG_ADDR_WORD,
+    AddAddrRegByte = ops::ADD_ADDR_REG_BYTE,
+    AddAddrRegWord = ops::ADD_ADDR_REG_WORD,
+    AddAddrNumByte = ops::ADD_ADDR_NUM_BYTE,
+    AddAddrNumWord = ops::ADD_ADDR_NUM_WORD,
+    AddAddrAddrByte = ops::ADD_ADDR_ADDR_BYTE,
+    AddAddrAddrWord = ops::ADD_ADDR_ADDR_WORD,
+    IncRegByte = ops::INC_REG_BYTE,
+    IncRegWord = ops::INC_REG_WORD,
+    IncAddrByte = ops::INC_ADDR_BYTE,
+    IncAddrWord = ops::INC_ADDR_WORD,
+    SubRegRegByte = ops::SUB_REG_REG_BYTE,
+    SubRegRegWord = ops::SUB_REG_REG_WORD,
+    SubRegNumByte = ops::SUB_REG_NUM_BYTE,
+    SubRegNumWord = ops::SUB_REG_NUM_WORD,
+    SubRegAddrByte = ops::SUB_REG_ADDR_BYTE,
+    SubRegAddrWord = ops::SUB_REG_ADDR_WORD,
+    SubAddrRegByte = ops::SUB_ADDR_REG_BYTE,
+    SubAddrRegWord = ops::SUB_ADDR_REG_WORD,
+    SubAddrNumByte = ops::SUB_ADDR_NUM_BYTE,
+    SubAddrNumWord = ops::SUB_ADDR_NUM_WORD,
+    SubAddrAddrByte = ops::SUB_ADDR_ADDR_BYTE,
+    SubAddrAddrWord = ops::SUB_ADDR_ADDR_WORD,
+    DecRegByte = ops::DEC_REG_BYTE,
+    DecRegWord = ops::DEC_REG_WORD,
+    DecAddrByte = ops::DEC_ADDR_BYTE,
+    DecAddrWord = ops::DEC_ADDR_WORD,
+    NotRegByte = ops::NOT_REG_BYTE,
+    NotRegWord = ops::NOT_REG_WORD,
+    OrRegRegByte = ops::OR_REG_REG_BYTE,
+    OrRegRegWord = ops::OR_REG_REG_WORD,
+    OrRegNumByte = ops::OR_REG_NUM_BYTE,
+    OrRegNumWord = ops::OR_REG_NUM_WORD,
+    XorRegRegByte = ops::XOR_REG_REG_BYTE,
+    XorRegRegWord = ops::XOR_REG_REG_WORD,
+    XorRegNumByte = ops::XOR_REG_NUM_BYTE,
+    XorRegNumWord = ops::XOR_REG_NUM_WORD,
+    AndRegRegByte = ops::AND_REG_REG_BYTE,
+    AndRegRegWord = ops::AND_REG_REG_WORD,
+    AndRegNumByte = ops::AND_REG_NUM_BYTE,
+    AndRegNumWord = ops::AND_REG_NUM_WORD,
+    JrfByte = ops::JRF_BYTE,
+    JrbByte = ops::JRB_BYTE,
+    AslRegNumByte = ops::ASL_REG_NUM_BYTE,
+    AslRegNumWord = ops::ASL_REG_NUM_WORD,
+    AslRegRegByte = ops::ASL_REG_REG_BYTE,
+    AslRegRegWord = ops::ASL_REG_REG_WORD,
+    AslAddrByte = ops::ASL_ADDR_BYTE,
+    AslAddrWord = ops::ASL_ADDR_WORD,
+    AsrRegNumByte = ops::ASR_REG_NUM_BYTE,
+    AsrRegNumWord = ops::ASR_REG_NUM_WORD,
+    AsrRegRegByte = ops::ASR_REG_REG_BYTE,
+    AsrRegRegWord = ops::ASR_REG_REG_WORD,
+    AsrAddrByte = ops::ASR_ADDR_BYTE,
+    AsrAddrWord = ops::ASR_ADDR_WORD,
+    LsrRegNumByte = ops::LSR_REG_NUM_BYTE,
+    LsrRegNumWord = ops::LSR_REG_NUM_WORD,
+    LsrRegRegByte = ops::LSR_REG_REG_BYTE,
+    LsrRegRegWord = ops::LSR_REG_REG_WORD,
+    LsrAddrByte = ops::LSR_ADDR_BYTE,
+    LsrAddrWord = ops::LSR_ADDR_WORD,
+    RolRegNumByte = ops::ROL_REG_NUM_BYTE,
+    RolRegNumWord = ops::ROL_REG_NUM_WORD,
+    RolRegRegByte = ops::ROL_REG_REG_BYTE,
+    RolRegRegWord = ops::ROL_REG_REG_WORD,
+    RolAddrByte = ops::ROL_ADDR_BYTE,
+    RolAddrWord = ops::ROL_ADDR_WORD,
+    RorRegNumByte = ops::ROR_REG_NUM_BYTE,
+    RorRegNumWord = ops::ROR_REG_NUM_WORD,
+    RorRegRegByte = ops::ROR_REG_REG_BYTE,
+    RorRegRegWord = ops::ROR_REG_REG_WORD,
+    RorAddrByte = ops::ROR_ADDR_BYTE,
+    RorAddrWord = ops::ROR_ADDR_WORD,
+    PushRegByte = ops::PUSH_REG_BYTE,
+    PushNumByte = ops::PUSH_NUM_BYTE,
+    PushRegWord = ops::PUSH_REG_WORD,
+    PushNumWord = ops::PUSH_NUM_WORD,
+    PopRegByte = ops::POP_REG_BYTE,
+    PopRegWord = ops::POP_REG_WORD,
+    MswpRegRegReg = ops::MSWP_REG_REG_REG,
+    MswpRegRegByte = ops::MSWP_REG_REG_BYTE,
+    MswpRegAddrReg = ops::MSWP_REG_ADDR_REG,
+    MswpRegAddrByte = ops::MSWP_REG_ADDR_BYTE,
+    MswpAddrRegReg = ops::MSWP_ADDR_REG_REG,
+    MswpAddrRegByte = ops::MSWP_ADDR_REG_BYTE,
+    MswpAddrAddrByte = ops::MSWP_ADDR_ADDR_BYTE,
+    MswpAddrAddrReg = ops::MSWP_ADDR_ADDR_REG,
+    MulRegRegByte = ops::MUL_REG_REG_BYTE,
+    MulRegRegWord = ops::MUL_REG_REG_WORD,
+    MulRegNumByte = ops::MUL_REG_NUM_BYTE,
+    MulRegNumWord = ops::MUL_REG_NUM_WORD,
+    MulRegAddrByte = ops::MUL_REG_ADDR_BYTE,
+    MulRegAddrWord = ops::MUL_REG_ADDR_WORD,
+    MulAddrRegByte = ops::MUL_ADDR_REG_BYTE,
+    MulAddrRegWord = ops::MUL_ADDR_REG_WORD,
+    MulAddrNumByte = ops::MUL_ADDR_NUM_BYTE,
+    MulAddrNumWord = ops::MUL_ADDR_NUM_WORD,
+    MulAddrAddrByte = ops::MUL_ADDR_ADDR_BYTE,
+    MulAddrAddrWord = ops::MUL_ADDR_ADDR_WORD,
+    MulsRegRegByte = ops::MULS_REG_REG_BYTE,
+    MulsRegRegWord = ops::MULS_REG_REG_WORD,
+    MulsRegNumByte = ops::MULS_REG_NUM_BYTE,
+    MulsRegNumWord = ops::MULS_REG_NUM_WORD,
+    MulsRegAddrByte = ops::MULS_REG_ADDR_BYTE,
+    MulsRegAddrWord = ops::MULS_REG_ADDR_WORD,
+    MulsAddrRegByte = ops::MULS_ADDR_REG_BYTE,
+    MulsAddrRegWord = ops::MULS_ADDR_REG_WORD,
+    MulsAddrNumByte = ops::MULS_ADDR_NUM_BYTE,
+    MulsAddrNumWord = ops::MULS_ADDR_NUM_WORD,
+    MulsAddrAddrByte = ops::MULS_ADDR_ADDR_BYTE,
+    MulsAddrAddrWord = ops::MULS_ADDR_ADDR_WORD,
+    DivRegRegByte = ops::DIV_REG_REG_BYTE,
+    DivRegRegWord = ops::DIV_REG_REG_WORD,
+    DivRegNumByte = ops::DIV_REG_NUM_BYTE,
+    DivRegNumWord = ops::DIV_REG_NUM_WORD,
+    DivRegAddrByte = ops::DIV_REG_ADDR_BYTE,
+    DivRegAddrWord = ops::DIV_REG_ADDR_WORD,
+    DivAddrRegByte = ops::DIV_ADDR_REG_BYTE,
+    DivAddrRegWord = ops::DIV_ADDR_REG_WORD,
+    DivAddrNumByte = ops::DIV_ADDR_NUM_BYTE,
+    DivAddrNumWord = ops::DIV_ADDR_NUM_WORD,
+    DivAddrAddrByte = ops::DIV_ADDR_ADDR_BYTE,
+    DivAddrAddrWord = ops::DIV_ADDR_ADDR_WORD,
+    DivsRegRegByte = ops::DIVS_REG_REG_BYTE,
+    DivsRegRegWord = ops::DIVS_REG_REG_WORD,
+    DivsRegNumByte = ops::DIVS_REG_NUM_BYTE,
+    DivsRegNumWord = ops::DIVS_REG_NUM_WORD,
+    DivsRegAddrByte = ops::DIVS_REG_ADDR_BYTE,
+    DivsRegAddrWord = ops::DIVS_REG_ADDR_WORD,
+    DivsAddrRegByte = ops::DIVS_ADDR_REG_BYTE,
+    DivsAddrRegWord = ops::DIVS_ADDR_REG_WORD,
+    DivsAddrNumByte = ops::DIVS_ADDR_NUM_BYTE,
+    DivsAddrNumWord = ops::DIVS_ADDR_NUM_WORD,
+    DivsAddrAddrByte = ops::DIVS_ADDR_ADDR_BYTE,
+    DivsAddrAddrWord = ops::DIVS_ADDR_ADDR_WORD,
+    JmpAddr = ops::JMP_ADDR,
+    JmpReg = ops::JMP_REG,
+    JeAddr = ops::JE_ADDR,
+    JeReg = ops::JE_REG,
+    JneAddr = ops::JNE_ADDR,
+    JneReg = ops::JNE_REG,
+    JlAddr = ops::JL_ADDR,
+    JlReg = ops::JL_REG,
+    JgAddr = ops::JG_ADDR,
+    JgReg = ops::JG_REG,
+    JleAddr = ops::JLE_ADDR,
+    JleReg = ops::JLE_REG,
+    JgeAddr = ops::JGE_ADDR,
+    JgeReg = ops::JGE_REG,
+    CmpRegNumByte = ops::CMP_REG_NUM_BYTE,
+    CmpRegNumWord = ops::CMP_REG_NUM_WORD,
+    CmpRegRegByte = ops::CMP_REG_REG_BYTE,
+    CmpRegRegWord = ops::CMP_REG_REG_WORD,
+    CmpsRegNumByte = ops::CMPS_REG_NUM_BYTE,
+    CmpsRegNumWord = ops::CMPS_REG_NUM_WORD,
+    CmpsRegRegByte = ops::CMPS_REG_REG_BYTE,
+    CmpsRegRegWord = ops::CMPS_REG_REG_WORD,
+    JbcRegReg = ops::JBC_REG_REG,
+    JbsRegReg = ops::JBS_REG_REG,
+    JbcAddrReg = ops::JBC_ADDR_REG,
+    JbsAddrReg = ops::JBS_ADDR_REG,
+    JbcRegNum = ops::JBC_REG_NUM,
+    JbsRegNum = ops::JBS_REG_NUM,
+    JbcAddrNum = ops::JBC_ADDR_NUM,
+    JbsAddrNum = ops::JBS_ADDR_NUM,
+    RclRegNumByte = ops::RCL_REG_NUM_BYTE,
+    RclRegNumWord = ops::RCL_REG_NUM_WORD,
+    RclRegRegByte = ops::RCL_REG_REG_BYTE,
+    RclRegRegWord = ops::RCL_REG_REG_WORD,
+    RclAddrByte = ops::RCL_ADDR_BYTE,
+    RclAddrWord = ops::RCL_ADDR_WORD,
+    RcrRegNumByte = ops::RCR_REG_NUM_BYTE,
+    RcrRegNumWord = ops::RCR_REG_NUM_WORD,
+    RcrRegRegByte = ops::RCR_REG_REG_BYTE,
+    RcrRegRegWord = ops::RCR_REG_REG_WORD,
+    RcrAddrByte = ops::RCR_ADDR_BYTE,
+    RcrAddrWord = ops::RCR_ADDR_WORD,
+    AddcRegRegByte = ops::ADDC_REG_REG_BYTE,
+    AddcRegRegWord = ops::ADDC_REG_REG_WORD,
+    AddcRegNumByte = ops::ADDC_REG_NUM_BYTE,
+    AddcRegNumWord = ops::ADDC_REG_NUM_WORD,
+    AddcRegAddrByte = ops::ADDC_REG_ADDR_BYTE,
+    AddcRegAddrWord = ops::ADDC_REG_ADDR_WORD,
+    AddcAddrRegByte = ops::ADDC_ADDR_REG_BYTE,
+    AddcAddrRegWord = ops::ADDC_ADDR_REG_WORD,
+    AddcAddrNumByte = ops::ADDC_ADDR_NUM_BYTE,
+    AddcAddrNumWord = ops::ADDC_ADDR_NUM_WORD,
+    AddcAddrAddrByte = ops::ADDC_ADDR_ADDR_BYTE,
+    AddcAddrAddrWord = ops::ADDC_ADDR_ADDR_WORD,
+    SubcRegRegByte = ops::SUBC_REG_REG_BYTE,
+    SubcRegRegWord = ops::SUBC_REG_REG_WORD,
+    SubcRegNumByte = ops::SUBC_REG_NUM_BYTE,
+    SubcRegNumWord = ops::SUBC_REG_NUM_WORD,
+    SubcRegAddrByte = ops::SUBC_REG_ADDR_BYTE,
+    SubcRegAddrWord = ops::SUBC_REG_ADDR_WORD,
+    SubcAddrRegByte = ops::SUBC_ADDR_REG_BYTE,
+    SubcAddrRegWord = ops::SUBC_ADDR_REG_WORD,
+    SubcAddrNumByte = ops::SUBC_ADDR_NUM_BYTE,
+    SubcAddrNumWord = ops::SUBC_ADDR_NUM_WORD,
+    SubcAddrAddrByte = ops::SUBC_ADDR_ADDR_BYTE,
+    SubcAddrAddrWord = ops::SUBC_ADDR_ADDR_WORD,
+    BmulAddrAddr = ops::BMUL_ADDR_ADDR,
+    BmulAddrNum = ops::BMUL_ADDR_NUM,
+    BmulAddrReg = ops::BMUL_ADDR_REG,
+    BmulRegAddr = ops::BMUL_REG_ADDR,
+    BmulRegNum = ops::BMUL_REG_NUM,
+    BmulRegReg = ops::BMUL_REG_REG,
+    Ehalt = ops::EHALT,
+    Sleep = ops::SLEEP,
+}
+
+impl Opcode {
+    #[rustfmt::skip]
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        Some(match byte {
+            ops::NOP => Opcode::Nop,
+            ops::HALT => Opcode::Halt,
+            ops::CALL_ADDR => Opcode::CallAddr,
+            ops::CALL_REG => Opcode::CallReg,
+            ops::RET => Opcode::Ret,
+            ops::RETI => Opcode::Reti,
+            ops::SWAP_REG_REG_BYTE => Opcode::SwapRegRegByte,
+            ops::SWAP_REG_REG_WORD => Opcode::SwapRegRegWord,
+            ops::MEM_CPY_ADDR_ADDR_BYTE => Opcode::MemCpyAddrAddrByte,
+            ops::MEM_CPY_ADDR_REG_BYTE => Opcode::MemCpyAddrRegByte,
+            ops::MEM_CPY_REG_ADDR_BYTE => Opcode::MemCpyRegAddrByte,
+            ops::MEM_CPY_REG_REG_BYTE => Opcode::MemCpyRegRegByte,
+            ops::MEM_CPY_ADDR_ADDR_REG => Opcode::MemCpyAddrAddrReg,
+            ops::MEM_CPY_ADDR_REG_REG => Opcode::MemCpyAddrRegReg,
+            ops::MEM_CPY_REG_ADDR_REG => Opcode::MemCpyRegAddrReg,
+            ops::MEM_CPY_REG_REG_REG => Opcode::MemCpyRegRegReg,
+            ops::CPY_REG_REG_BYTE => Opcode::CpyRegRegByte,
+            ops::CPY_REG_REG_WORD => Opcode::CpyRegRegWord,
+            ops::CPY_ADDR_REG_BYTE => Opcode::CpyAddrRegByte,
+            ops::CPY_ADDR_REG_WORD => Opcode::CpyAddrRegWord,
+            ops::CPY_REG_ADDR_BYTE => Opcode::CpyRegAddrByte,
+            ops::CPY_REG_ADDR_WORD => Opcode::CpyRegAddrWord,
+            ops::CPY_ADDR_ADDR_BYTE => Opcode::CpyAddrAddrByte,
+            ops::CPY_ADDR_ADDR_WORD => Opcode::CpyAddrAddrWord,
+            ops::CPY_REG_NUM_BYTE => Opcode::CpyRegNumByte,
+            ops::CPY_REG_NUM_WORD => Opcode::CpyRegNumWord,
+            ops::CPY_ADDR_NUM_BYTE => Opcode::CpyAddrNumByte,
+            ops::CPY_ADDR_NUM_WORD => Opcode::CpyAddrNumWord,
+            ops::CMP_REG_ADDR_BYTE => Opcode::CmpRegAddrByte,
+            ops::CMP_REG_ADDR_WORD => Opcode::CmpRegAddrWord,
+            ops::CMPS_REG_ADDR_BYTE => Opcode::CmpsRegAddrByte,
+            ops::CMPS_REG_ADDR_WORD => Opcode::CmpsRegAddrWord,
+            ops::ADD_REG_REG_BYTE => Opcode::AddRegRegByte,
+            ops::ADD_REG_REG_WORD => Opcode::AddRegRegWord,
+            ops::ADD_REG_NUM_BYTE => Opcode::AddRegNumByte,
+            ops::ADD_REG_NUM_WORD => Opcode::AddRegNumWord,
+            ops::ADD_REG_ADDR_BYTE => Opcode::AddRegAddrByte,
+            ops::ADD_REG_ADDR_WORD => Opcode::AddRegAddrWord,
+            ops::ADD_ADDR_REG_BYTE => Opcode::AddAddrRegByte,
+            ops::ADD_ADDR_REG_WORD => Opcode::AddAddrRegWord,
+            ops::ADD_ADDR_NUM_BYTE => Opcode::AddAddrNumByte,
+            ops::ADD_ADDR_NUM_WORD => Opcode::AddAddrNumWord,
+            ops::ADD_ADDR_ADDR_BYTE => Opcode::AddAddrAddrByte,
+            ops::ADD_ADDR_ADDR_WORD => Opcode::AddAddrAddrWord,
+            ops::INC_REG_BYTE => Opcode::IncRegByte,
+            ops::INC_REG_WORD => Opcode::IncRegWord,
+            ops::INC_ADDR_BYTE => Opcode::IncAddrByte,
+            ops::INC_ADDR_WORD => Opcode::IncAddrWord,
+            ops::SUB_REG_REG_BYTE => Opcode::SubRegRegByte,
+            ops::SUB_REG_REG_WORD => Opcode::SubRegRegWord,
+            ops::SUB_REG_NUM_BYTE => Opcode::SubRegNumByte,
+            ops::SUB_REG_NUM_WORD => Opcode::SubRegNumWord,
+            ops::SUB_REG_ADDR_BYTE => Opcode::SubRegAddrByte,
+            ops::SUB_REG_ADDR_WORD => Opcode::SubRegAddrWord,
+            ops::SUB_ADDR_REG_BYTE => Opcode::SubAddrRegByte,
+            ops::SUB_ADDR_REG_WORD => Opcode::SubAddrRegWord,
+            ops::SUB_ADDR_NUM_BYTE => Opcode::SubAddrNumByte,
+            ops::SUB_ADDR_NUM_WORD => Opcode::SubAddrNumWord,
+            ops::SUB_ADDR_ADDR_BYTE => Opcode::SubAddrAddrByte,
+            ops::SUB_ADDR_ADDR_WORD => Opcode::SubAddrAddrWord,
+            ops::DEC_REG_BYTE => Opcode::DecRegByte,
+            ops::DEC_REG_WORD => Opcode::DecRegWord,
+            ops::DEC_ADDR_BYTE => Opcode::DecAddrByte,
+            ops::DEC_ADDR_WORD => Opcode::DecAddrWord,
+            ops::NOT_REG_BYTE => Opcode::NotRegByte,
+            ops::NOT_REG_WORD => Opcode::NotRegWord,
+            ops::OR_REG_REG_BYTE => Opcode::OrRegRegByte,
+            ops::OR_REG_REG_WORD => Opcode::OrRegRegWord,
+            ops::OR_REG_NUM_BYTE => Opcode::OrRegNumByte,
+            ops::OR_REG_NUM_WORD => Opcode::OrRegNumWord,
+            ops::XOR_REG_REG_BYTE => Opcode::XorRegRegByte,
+            ops::XOR_REG_REG_WORD => Opcode::XorRegRegWord,
+            ops::XOR_REG_NUM_BYTE => Opcode::XorRegNumByte,
+            ops::XOR_REG_NUM_WORD => Opcode::XorRegNumWord,
+            ops::AND_REG_REG_BYTE => Opcode::AndRegRegByte,
+            ops::AND_REG_REG_WORD => Opcode::AndRegRegWord,
+            ops::AND_REG_NUM_BYTE => Opcode::AndRegNumByte,
+            ops::AND_REG_NUM_WORD => Opcode::AndRegNumWord,
+            ops::JRF_BYTE => Opcode::JrfByte,
+            ops::JRB_BYTE => Opcode::JrbByte,
+            ops::ASL_REG_NUM_BYTE => Opcode::AslRegNumByte,
+            ops::ASL_REG_NUM_WORD => Opcode::AslRegNumWord,
+            ops::ASL_REG_REG_BYTE => Opcode::AslRegRegByte,
+            ops::ASL_REG_REG_WORD => Opcode::AslRegRegWord,
+            ops::ASL_ADDR_BYTE => Opcode::AslAddrByte,
+            ops::ASL_ADDR_WORD => Opcode::AslAddrWord,
+            ops::ASR_REG_NUM_BYTE => Opcode::AsrRegNumByte,
+            ops::ASR_REG_NUM_WORD => Opcode::AsrRegNumWord,
+            ops::ASR_REG_REG_BYTE => Opcode::AsrRegRegByte,
+            ops::ASR_REG_REG_WORD => Opcode::AsrRegRegWord,
+            ops::ASR_ADDR_BYTE => Opcode::AsrAddrByte,
+            ops::ASR_ADDR_WORD => Opcode::AsrAddrWord,
+            ops::LSR_REG_NUM_BYTE => Opcode::LsrRegNumByte,
+            ops::LSR_REG_NUM_WORD => Opcode::LsrRegNumWord,
+            ops::LSR_REG_REG_BYTE => Opcode::LsrRegRegByte,
+            ops::LSR_REG_REG_WORD => Opcode::LsrRegRegWord,
+            ops::LSR_ADDR_BYTE => Opcode::LsrAddrByte,
+            ops::LSR_ADDR_WORD => Opcode::LsrAddrWord,
+            ops::ROL_REG_NUM_BYTE => Opcode::RolRegNumByte,
+            ops::ROL_REG_NUM_WORD => Opcode::RolRegNumWord,
+            ops::ROL_REG_REG_BYTE => Opcode::RolRegRegByte,
+            ops::ROL_REG_REG_WORD => Opcode::RolRegRegWord,
+            ops::ROL_ADDR_BYTE => Opcode::RolAddrByte,
+            ops::ROL_ADDR_WORD => Opcode::RolAddrWord,
+            ops::ROR_REG_NUM_BYTE => Opcode::RorRegNumByte,
+            ops::ROR_REG_NUM_WORD => Opcode::RorRegNumWord,
+            ops::ROR_REG_REG_BYTE => Opcode::RorRegRegByte,
+            ops::ROR_REG_REG_WORD => Opcode::RorRegRegWord,
+            ops::ROR_ADDR_BYTE => Opcode::RorAddrByte,
+            ops::ROR_ADDR_WORD => Opcode::RorAddrWord,
+            ops::PUSH_REG_BYTE => Opcode::PushRegByte,
+            ops::PUSH_NUM_BYTE => Opcode::PushNumByte,
+            ops::PUSH_REG_WORD => Opcode::PushRegWord,
+            ops::PUSH_NUM_WORD => Opcode::PushNumWord,
+            ops::POP_REG_BYTE => Opcode::PopRegByte,
+            ops::POP_REG_WORD => Opcode::PopRegWord,
+            ops::MSWP_REG_REG_REG => Opcode::MswpRegRegReg,
+            ops::MSWP_REG_REG_BYTE => Opcode::MswpRegRegByte,
+            ops::MSWP_REG_ADDR_REG => Opcode::MswpRegAddrReg,
+            ops::MSWP_REG_ADDR_BYTE => Opcode::MswpRegAddrByte,
+            ops::MSWP_ADDR_REG_REG => Opcode::MswpAddrRegReg,
+            ops::MSWP_ADDR_REG_BYTE => Opcode::MswpAddrRegByte,
+            ops::MSWP_ADDR_ADDR_BYTE => Opcode::MswpAddrAddrByte,
+            ops::MSWP_ADDR_ADDR_REG => Opcode::MswpAddrAddrReg,
+            ops::MUL_REG_REG_BYTE => Opcode::MulRegRegByte,
+            ops::MUL_REG_REG_WORD => Opcode::MulRegRegWord,
+            ops::MUL_REG_NUM_BYTE => Opcode::MulRegNumByte,
+            ops::MUL_REG_NUM_WORD => Opcode::MulRegNumWord,
+            ops::MUL_REG_ADDR_BYTE => Opcode::MulRegAddrByte,
+            ops::MUL_REG_ADDR_WORD => Opcode::MulRegAddrWord,
+            ops::MUL_ADDR_REG_BYTE => Opcode::MulAddrRegByte,
+            ops::MUL_ADDR_REG_WORD => Opcode::MulAddrRegWord,
+            ops::MUL_ADDR_NUM_BYTE => Opcode::MulAddrNumByte,
+            ops::MUL_ADDR_NUM_WORD => Opcode::MulAddrNumWord,
+            ops::MUL_ADDR_ADDR_BYTE => Opcode::MulAddrAddrByte,
+            ops::MUL_ADDR_ADDR_WORD => Opcode::MulAddrAddrWord,
+            ops::MULS_REG_REG_BYTE => Opcode::MulsRegRegByte,
+            ops::MULS_REG_REG_WORD => Opcode::MulsRegRegWord,
+            ops::MULS_REG_NUM_BYTE => Opcode::MulsRegNumByte,
+            ops::MULS_REG_NUM_WORD => Opcode::MulsRegNumWord,
+            ops::MULS_REG_ADDR_BYTE => Opcode::MulsRegAddrByte,
+            ops::MULS_REG_ADDR_WORD => Opcode::MulsRegAddrWord,
+            ops::MULS_ADDR_REG_BYTE => Opcode::MulsAddrRegByte,
+            ops::MULS_ADDR_REG_WORD => Opcode::MulsAddrRegWord,
+            ops::MULS_ADDR_NUM_BYTE => Opcode::MulsAddrNumByte,
+            ops::MULS_ADDR_NUM_WORD => Opcode::MulsAddrNumWord,
+            ops::MULS_ADDR_ADDR_BYTE => Opcode::MulsAddrAddrByte,
+            ops::MULS_ADDR_ADDR_WORD => Opcode::MulsAddrAddrWord,
+            ops::DIV_REG_REG_BYTE => Opcode::DivRegRegByte,
+            ops::DIV_REG_REG_WORD => Opcode::DivRegRegWord,
+            ops::DIV_REG_NUM_BYTE => Opcode::DivRegNumByte,
+            ops::DIV_REG_NUM_WORD => Opcode::DivRegNumWord,
+            ops::DIV_REG_ADDR_BYTE => Opcode::DivRegAddrByte,
+            ops::DIV_REG_ADDR_WORD => Opcode::DivRegAddrWord,
+            ops::DIV_ADDR_REG_BYTE => Opcode::DivAddrRegByte,
+            ops::DIV_ADDR_REG_WORD => Opcode::DivAddrRegWord,
+            ops::DIV_ADDR_NUM_BYTE => Opcode::DivAddrNumByte,
+            ops::DIV_ADDR_NUM_WORD => Opcode::DivAddrNumWord,
+            ops::DIV_ADDR_ADDR_BYTE => Opcode::DivAddrAddrByte,
+            ops::DIV_ADDR_ADDR_WORD => Opcode::DivAddrAddrWord,
+            ops::DIVS_REG_REG_BYTE => Opcode::DivsRegRegByte,
+            ops::DIVS_REG_REG_WORD => Opcode::DivsRegRegWord,
+            ops::DIVS_REG_NUM_BYTE => Opcode::DivsRegNumByte,
+            ops::DIVS_REG_NUM_WORD => Opcode::DivsRegNumWord,
+            ops::DIVS_REG_ADDR_BYTE => Opcode::DivsRegAddrByte,
+            ops::DIVS_REG_ADDR_WORD => Opcode::DivsRegAddrWord,
+            ops::DIVS_ADDR_REG_BYTE => Opcode::DivsAddrRegByte,
+            ops::DIVS_ADDR_REG_WORD => Opcode::DivsAddrRegWord,
+            ops::DIVS_ADDR_NUM_BYTE => Opcode::DivsAddrNumByte,
+            ops::DIVS_ADDR_NUM_WORD => Opcode::DivsAddrNumWord,
+            ops::DIVS_ADDR_ADDR_BYTE => Opcode::DivsAddrAddrByte,
+            ops::DIVS_ADDR_ADDR_WORD => Opcode::DivsAddrAddrWord,
+            ops::JMP_ADDR => Opcode::JmpAddr,
+            ops::JMP_REG => Opcode::JmpReg,
+            ops::JE_ADDR => Opcode::JeAddr,
+            ops::JE_REG => Opcode::JeReg,
+            ops::JNE_ADDR => Opcode::JneAddr,
+            ops::JNE_REG => Opcode::JneReg,
+            ops::JL_ADDR => Opcode::JlAddr,
+            ops::JL_REG => Opcode::JlReg,
+            ops::JG_ADDR => Opcode::JgAddr,
+            ops::JG_REG => Opcode::JgReg,
+            ops::JLE_ADDR => Opcode::JleAddr,
+            ops::JLE_REG => Opcode::JleReg,
+            ops::JGE_ADDR => Opcode::JgeAddr,
+            ops::JGE_REG => Opcode::JgeReg,
+            ops::CMP_REG_NUM_BYTE => Opcode::CmpRegNumByte,
+            ops::CMP_REG_NUM_WORD => Opcode::CmpRegNumWord,
+            ops::CMP_REG_REG_BYTE => Opcode::CmpRegRegByte,
+            ops::CMP_REG_REG_WORD => Opcode::CmpRegRegWord,
+            ops::CMPS_REG_NUM_BYTE => Opcode::CmpsRegNumByte,
+            ops::CMPS_REG_NUM_WORD => Opcode::CmpsRegNumWord,
+            ops::CMPS_REG_REG_BYTE => Opcode::CmpsRegRegByte,
+            ops::CMPS_REG_REG_WORD => Opcode::CmpsRegRegWord,
+            ops::JBC_REG_REG => Opcode::JbcRegReg,
+            ops::JBS_REG_REG => Opcode::JbsRegReg,
+            ops::JBC_ADDR_REG => Opcode::JbcAddrReg,
+            ops::JBS_ADDR_REG => Opcode::JbsAddrReg,
+            ops::JBC_REG_NUM => Opcode::JbcRegNum,
+            ops::JBS_REG_NUM => Opcode::JbsRegNum,
+            ops::JBC_ADDR_NUM => Opcode::JbcAddrNum,
+            ops::JBS_ADDR_NUM => Opcode::JbsAddrNum,
+            ops::RCL_REG_NUM_BYTE => Opcode::RclRegNumByte,
+            ops::RCL_REG_NUM_WORD => Opcode::RclRegNumWord,
+            ops::RCL_REG_REG_BYTE => Opcode::RclRegRegByte,
+            ops::RCL_REG_REG_WORD => Opcode::RclRegRegWord,
+            ops::RCL_ADDR_BYTE => Opcode::RclAddrByte,
+            ops::RCL_ADDR_WORD => Opcode::RclAddrWord,
+            ops::RCR_REG_NUM_BYTE => Opcode::RcrRegNumByte,
+            ops::RCR_REG_NUM_WORD => Opcode::RcrRegNumWord,
+            ops::RCR_REG_REG_BYTE => Opcode::RcrRegRegByte,
+            ops::RCR_REG_REG_WORD => Opcode::RcrRegRegWord,
+            ops::RCR_ADDR_BYTE => Opcode::RcrAddrByte,
+            ops::RCR_ADDR_WORD => Opcode::RcrAddrWord,
+            ops::ADDC_REG_REG_BYTE => Opcode::AddcRegRegByte,
+            ops::ADDC_REG_REG_WORD => Opcode::AddcRegRegWord,
+            ops::ADDC_REG_NUM_BYTE => Opcode::AddcRegNumByte,
+            ops::ADDC_REG_NUM_WORD => Opcode::AddcRegNumWord,
+            ops::ADDC_REG_ADDR_BYTE => Opcode::AddcRegAddrByte,
+            ops::ADDC_REG_ADDR_WORD => Opcode::AddcRegAddrWord,
+            ops::ADDC_ADDR_REG_BYTE => Opcode::AddcAddrRegByte,
+            ops::ADDC_ADDR_REG_WORD => Opcode::AddcAddrRegWord,
+            ops::ADDC_ADDR_NUM_BYTE => Opcode::AddcAddrNumByte,
+            ops::ADDC_ADDR_NUM_WORD => Opcode::AddcAddrNumWord,
+            ops::ADDC_ADDR_ADDR_BYTE => Opcode::AddcAddrAddrByte,
+            ops::ADDC_ADDR_ADDR_WORD => Opcode::AddcAddrAddrWord,
+            ops::SUBC_REG_REG_BYTE => Opcode::SubcRegRegByte,
+            ops::SUBC_REG_REG_WORD => Opcode::SubcRegRegWord,
+            ops::SUBC_REG_NUM_BYTE => Opcode::SubcRegNumByte,
+            ops::SUBC_REG_NUM_WORD => Opcode::SubcRegNumWord,
+            ops::SUBC_REG_ADDR_BYTE => Opcode::SubcRegAddrByte,
+            ops::SUBC_REG_ADDR_WORD => Opcode::SubcRegAddrWord,
+            ops::SUBC_ADDR_REG_BYTE => Opcode::SubcAddrRegByte,
+            ops::SUBC_ADDR_REG_WORD => Opcode::SubcAddrRegWord,
+            ops::SUBC_ADDR_NUM_BYTE => Opcode::SubcAddrNumByte,
+            ops::SUBC_ADDR_NUM_WORD => Opcode::SubcAddrNumWord,
+            ops::SUBC_ADDR_ADDR_BYTE => Opcode::SubcAddrAddrByte,
+            ops::SUBC_ADDR_ADDR_WORD => Opcode::SubcAddrAddrWord,
+            ops::BMUL_ADDR_ADDR => Opcode::BmulAddrAddr,
+            ops::BMUL_ADDR_NUM => Opcode::BmulAddrNum,
+            ops::BMUL_ADDR_REG => Opcode::BmulAddrReg,
+            ops::BMUL_REG_ADDR => Opcode::BmulRegAddr,
+            ops::BMUL_REG_NUM => Opcode::BmulRegNum,
+            ops::BMUL_REG_REG => Opcode::BmulRegReg,
+            ops::EHALT => Opcode::Ehalt,
+            ops::SLEEP => Opcode::Sleep,
+            _ => return None,
+        })
+    }
+
+    /// Name and size, i.e. `CPY.B`
+    pub fn mnemonic(&self) -> &'static str {
+        self.layout().0
+    }
+
+    /// Mnemonic and operands for op
+    #[rustfmt::skip]
+    fn layout(&self) -> (&'static str, &'static [Kind]) {
+        match self {
+            Opcode::Nop => ("NOP", &[]),
+            Opcode::Halt => ("HALT", &[]),
+            Opcode::CallAddr => ("CALL", &[A]),
+            Opcode::CallReg => ("CALL", &[R]),
+            Opcode::Ret => ("RET", &[]),
+            Opcode::Reti => ("RETI", &[]),
+            Opcode::SwapRegRegByte => ("SWAP.B", &[R, R]),
+            Opcode::SwapRegRegWord => ("SWAP.W", &[R, R]),
+            Opcode::MemCpyAddrAddrByte => ("MCPY", &[A, A, B]),
+            Opcode::MemCpyAddrRegByte => ("MCPY", &[A, R, B]),
+            Opcode::MemCpyRegAddrByte => ("MCPY", &[R, A, B]),
+            Opcode::MemCpyRegRegByte => ("MCPY", &[R, R, B]),
+            Opcode::MemCpyAddrAddrReg => ("MCPY", &[A, A, R]),
+            Opcode::MemCpyAddrRegReg => ("MCPY", &[A, R, R]),
+            Opcode::MemCpyRegAddrReg => ("MCPY", &[R, A, R]),
+            Opcode::MemCpyRegRegReg => ("MCPY", &[R, R, R]),
+            Opcode::CpyRegRegByte => ("CPY.B", &[R, R]),
+            Opcode::CpyRegRegWord => ("CPY.W", &[R, R]),
+            Opcode::CpyAddrRegByte => ("CPY.B", &[A, R]),
+            Opcode::CpyAddrRegWord => ("CPY.W", &[A, R]),
+            Opcode::CpyRegAddrByte => ("CPY.B", &[R, A]),
+            Opcode::CpyRegAddrWord => ("CPY.W", &[R, A]),
+            Opcode::CpyAddrAddrByte => ("CPY.B", &[A, A]),
+            Opcode::CpyAddrAddrWord => ("CPY.W", &[A, A]),
+            Opcode::CpyRegNumByte => ("CPY.B", &[R, B]),
+            Opcode::CpyRegNumWord => ("CPY.W", &[R, W]),
+            Opcode::CpyAddrNumByte => ("CPY.B", &[A, B]),
+            Opcode::CpyAddrNumWord => ("CPY.W", &[A, W]),
+            Opcode::CmpRegAddrByte => ("CMP.B", &[R, A]),
+            Opcode::CmpRegAddrWord => ("CMP.W", &[R, A]),
+            Opcode::CmpsRegAddrByte => ("CMPS.B", &[R, A]),
+            Opcode::CmpsRegAddrWord => ("CMPS.W", &[R, A]),
+            Opcode::AddRegRegByte => ("ADD.B", &[R, R]),
+            Opcode::AddRegRegWord => ("ADD.W", &[R, R]),
+            Opcode::AddRegNumByte => ("ADD.B", &[R, B]),
+            Opcode::AddRegNumWord => ("ADD.W", &[R, W]),
+            Opcode::AddRegAddrByte => ("ADD.B", &[R, A]),
+            Opcode::AddRegAddrWord => ("ADD.W", &[R, A]),
+            Opcode::AddAddrRegByte => ("ADD.B", &[A, R]),
+            Opcode::AddAddrRegWord => ("ADD.W", &[A, R]),
+            Opcode::AddAddrNumByte => ("ADD.B", &[A, B]),
+            Opcode::AddAddrNumWord => ("ADD.W", &[A, W]),
+            Opcode::AddAddrAddrByte => ("ADD.B", &[A, A]),
+            Opcode::AddAddrAddrWord => ("ADD.W", &[A, A]),
+            Opcode::IncRegByte => ("INC.B", &[R]),
+            Opcode::IncRegWord => ("INC.W", &[R]),
+            Opcode::IncAddrByte => ("INC.B", &[A]),
+            Opcode::IncAddrWord => ("INC.W", &[A]),
+            Opcode::SubRegRegByte => ("SUB.B", &[R, R]),
+            Opcode::SubRegRegWord => ("SUB.W", &[R, R]),
+            Opcode::SubRegNumByte => ("SUB.B", &[R, B]),
+            Opcode::SubRegNumWord => ("SUB.W", &[R, W]),
+            Opcode::SubRegAddrByte => ("SUB.B", &[R, A]),
+            Opcode::SubRegAddrWord => ("SUB.W", &[R, A]),
+            Opcode::SubAddrRegByte => ("SUB.B", &[A, R]),
+            Opcode::SubAddrRegWord => ("SUB.W", &[A, R]),
+            Opcode::SubAddrNumByte => ("SUB.B", &[A, B]),
+            Opcode::SubAddrNumWord => ("SUB.W", &[A, W]),
+            Opcode::SubAddrAddrByte => ("SUB.B", &[A, A]),
+            Opcode::SubAddrAddrWord => ("SUB.W", &[A, A]),
+            Opcode::DecRegByte => ("DEC.B", &[R]),
+            Opcode::DecRegWord => ("DEC.W", &[R]),
+            Opcode::DecAddrByte => ("DEC.B", &[A]),
+            Opcode::DecAddrWord => ("DEC.W", &[A]),
+            Opcode::NotRegByte => ("NOT.B", &[R]),
+            Opcode::NotRegWord => ("NOT.W", &[R]),
+            Opcode::OrRegRegByte => ("OR.B", &[R, R]),
+            Opcode::OrRegRegWord => ("OR.W", &[R, R]),
+            Opcode::OrRegNumByte => ("OR.B", &[R, B]),
+            Opcode::OrRegNumWord => ("OR.W", &[R, W]),
+            Opcode::XorRegRegByte => ("XOR.B", &[R, R]),
+            Opcode::XorRegRegWord => ("XOR.W", &[R, R]),
+            Opcode::XorRegNumByte => ("XOR.B", &[R, B]),
+            Opcode::XorRegNumWord => ("XOR.W", &[R, W]),
+            Opcode::AndRegRegByte => ("AND.B", &[R, R]),
+            Opcode::AndRegRegWord => ("AND.W", &[R, R]),
+            Opcode::AndRegNumByte => ("AND.B", &[R, B]),
+            Opcode::AndRegNumWord => ("AND.W", &[R, W]),
+            Opcode::JrfByte => ("JRF", &[B]),
+            Opcode::JrbByte => ("JRB", &[B]),
+            Opcode::AslRegNumByte => ("ASL.B", &[R, B]),
+            Opcode::AslRegNumWord => ("ASL.W", &[R, W]),
+            Opcode::AslRegRegByte => ("ASL.B", &[R, R]),
+            Opcode::AslRegRegWord => ("ASL.W", &[R, R]),
+            Opcode::AslAddrByte => ("ASL.B", &[A]),
+            Opcode::AslAddrWord => ("ASL.W", &[A]),
+            Opcode::AsrRegNumByte => ("ASR.B", &[R, B]),
+            Opcode::AsrRegNumWord => ("ASR.W", &[R, W]),
+            Opcode::AsrRegRegByte => ("ASR.B", &[R, R]),
+            Opcode::AsrRegRegWord => ("ASR.W", &[R, R]),
+            Opcode::AsrAddrByte => ("ASR.B", &[A]),
+            Opcode::AsrAddrWord => ("ASR.W", &[A]),
+            Opcode::LsrRegNumByte => ("LSR.B", &[R, B]),
+            Opcode::LsrRegNumWord => ("LSR.W", &[R, W]),
+            Opcode::LsrRegRegByte => ("LSR.B", &[R, R]),
+            Opcode::LsrRegRegWord => ("LSR.W", &[R, R]),
+            Opcode::LsrAddrByte => ("LSR.B", &[A]),
+            Opcode::LsrAddrWord => ("LSR.W", &[A]),
+            Opcode::RolRegNumByte => ("ROL.B", &[R, B]),
+            Opcode::RolRegNumWord => ("ROL.W", &[R, W]),
+            Opcode::RolRegRegByte => ("ROL.B", &[R, R]),
+            Opcode::RolRegRegWord => ("ROL.W", &[R, R]),
+            Opcode::RolAddrByte => ("ROL.B", &[A]),
+            Opcode::RolAddrWord => ("ROL.W", &[A]),
+            Opcode::RorRegNumByte => ("ROR.B", &[R, B]),
+            Opcode::RorRegNumWord => ("ROR.W", &[R, W]),
+            Opcode::RorRegRegByte => ("ROR.B", &[R, R]),
+            Opcode::RorRegRegWord => ("ROR.W", &[R, R]),
+            Opcode::RorAddrByte => ("ROR.B", &[A]),
+            Opcode::RorAddrWord => ("ROR.W", &[A]),
+            Opcode::PushRegByte => ("PUSH.B", &[R]),
+            Opcode::PushNumByte => ("PUSH.B", &[B]),
+            Opcode::PushRegWord => ("PUSH.W", &[R]),
+            Opcode::PushNumWord => ("PUSH.W", &[W]),
+            Opcode::PopRegByte => ("POP.B", &[R]),
+            Opcode::PopRegWord => ("POP.W", &[R]),
+            Opcode::MswpRegRegReg => ("MSWP", &[R, R, R]),
+            Opcode::MswpRegRegByte => ("MSWP", &[R, R, B]),
+            Opcode::MswpRegAddrReg => ("MSWP", &[R, A, R]),
+            Opcode::MswpRegAddrByte => ("MSWP", &[R, A, B]),
+            Opcode::MswpAddrRegReg => ("MSWP", &[A, R, R]),
+            Opcode::MswpAddrRegByte => ("MSWP", &[A, R, B]),
+            Opcode::MswpAddrAddrByte => ("MSWP", &[A, A, B]),
+            Opcode::MswpAddrAddrReg => ("MSWP", &[A, A, R]),
+            Opcode::MulRegRegByte => ("MUL.B", &[R, R]),
+            Opcode::MulRegRegWord => ("MUL.W", &[R, R]),
+            Opcode::MulRegNumByte => ("MUL.B", &[R, B]),
+            Opcode::MulRegNumWord => ("MUL.W", &[R, W]),
+            Opcode::MulRegAddrByte => ("MUL.B", &[R, A]),
+            Opcode::MulRegAddrWord => ("MUL.W", &[R, A]),
+            Opcode::MulAddrRegByte => ("MUL.B", &[A, R]),
+            Opcode::MulAddrRegWord => ("MUL.W", &[A, R]),
+            Opcode::MulAddrNumByte => ("MUL.B", &[A, B]),
+            Opcode::MulAddrNumWord => ("MUL.W", &[A, W]),
+            Opcode::MulAddrAddrByte => ("MUL.B", &[A, A]),
+            Opcode::MulAddrAddrWord => ("MUL.W", &[A, A]),
+            Opcode::MulsRegRegByte => ("MULS.B", &[R, R]),
+            Opcode::MulsRegRegWord => ("MULS.W", &[R, R]),
+            Opcode::MulsRegNumByte => ("MULS.B", &[R, B]),
+            Opcode::MulsRegNumWord => ("MULS.W", &[R, W]),
+            Opcode::MulsRegAddrByte => ("MULS.B", &[R, A]),
+            Opcode::MulsRegAddrWord => ("MULS.W", &[R, A]),
+            Opcode::MulsAddrRegByte => ("MULS.B", &[A, R]),
+            Opcode::MulsAddrRegWord => ("MULS.W", &[A, R]),
+            Opcode::MulsAddrNumByte => ("MULS.B", &[A, B]),
+            Opcode::MulsAddrNumWord => ("MULS.W", &[A, W]),
+            Opcode::MulsAddrAddrByte => ("MULS.B", &[A, A]),
+            Opcode::MulsAddrAddrWord => ("MULS.W", &[A, A]),
+            Opcode::DivRegRegByte => ("DIV.B", &[R, R]),
+            Opcode::DivRegRegWord => ("DIV.W", &[R, R]),
+            Opcode::DivRegNumByte => ("DIV.B", &[R, B]),
+            Opcode::DivRegNumWord => ("DIV.W", &[R, W]),
+            Opcode::DivRegAddrByte => ("DIV.B", &[R, A]),
+            Opcode::DivRegAddrWord => ("DIV.W", &[R, A]),
+            Opcode::DivAddrRegByte => ("DIV.B", &[A, R]),
+            Opcode::DivAddrRegWord => ("DIV.W", &[A, R]),
+            Opcode::DivAddrNumByte => ("DIV.B", &[A, B]),
+            Opcode::DivAddrNumWord => ("DIV.W", &[A, W]),
+            Opcode::DivAddrAddrByte => ("DIV.B", &[A, A]),
+            Opcode::DivAddrAddrWord => ("DIV.W", &[A, A]),
+            Opcode::DivsRegRegByte => ("DIVS.B", &[R, R]),
+            Opcode::DivsRegRegWord => ("DIVS.W", &[R, R]),
+            Opcode::DivsRegNumByte => ("DIVS.B", &[R, B]),
+            Opcode::DivsRegNumWord => ("DIVS.W", &[R, W]),
+            Opcode::DivsRegAddrByte => ("DIVS.B", &[R, A]),
+            Opcode::DivsRegAddrWord => ("DIVS.W", &[R, A]),
+            Opcode::DivsAddrRegByte => ("DIVS.B", &[A, R]),
+            Opcode::DivsAddrRegWord => ("DIVS.W", &[A, R]),
+            Opcode::DivsAddrNumByte => ("DIVS.B", &[A, B]),
+            Opcode::DivsAddrNumWord => ("DIVS.W", &[A, W]),
+            Opcode::DivsAddrAddrByte => ("DIVS.B", &[A, A]),
+            Opcode::DivsAddrAddrWord => ("DIVS.W", &[A, A]),
+            Opcode::JmpAddr => ("JMP", &[A]),
+            Opcode::JmpReg => ("JMP", &[R]),
+            Opcode::JeAddr => ("JE", &[A]),
+            Opcode::JeReg => ("JE", &[R]),
+            Opcode::JneAddr => ("JNE", &[A]),
+            Opcode::JneReg => ("JNE", &[R]),
+            Opcode::JlAddr => ("JL", &[A]),
+            Opcode::JlReg => ("JL", &[R]),
+            Opcode::JgAddr => ("JG", &[A]),
+            Opcode::JgReg => ("JG", &[R]),
+            Opcode::JleAddr => ("JLE", &[A]),
+            Opcode::JleReg => ("JLE", &[R]),
+            Opcode::JgeAddr => ("JGE", &[A]),
+            Opcode::JgeReg => ("JGE", &[R]),
+            Opcode::CmpRegNumByte => ("CMP.B", &[R, B]),
+            Opcode::CmpRegNumWord => ("CMP.W", &[R, W]),
+            Opcode::CmpRegRegByte => ("CMP.B", &[R, R]),
+            Opcode::CmpRegRegWord => ("CMP.W", &[R, R]),
+            Opcode::CmpsRegNumByte => ("CMPS.B", &[R, B]),
+            Opcode::CmpsRegNumWord => ("CMPS.W", &[R, W]),
+            Opcode::CmpsRegRegByte => ("CMPS.B", &[R, R]),
+            Opcode::CmpsRegRegWord => ("CMPS.W", &[R, R]),
+            Opcode::JbcRegReg => ("JBC", &[R, R]),
+            Opcode::JbsRegReg => ("JBS", &[R, R]),
+            Opcode::JbcAddrReg => ("JBC", &[A, R]),
+            Opcode::JbsAddrReg => ("JBS", &[A, R]),
+            Opcode::JbcRegNum => ("JBC", &[R, B]),
+            Opcode::JbsRegNum => ("JBS", &[R, B]),
+            Opcode::JbcAddrNum => ("JBC", &[A, B]),
+            Opcode::JbsAddrNum => ("JBS", &[A, B]),
+            Opcode::RclRegNumByte => ("RCL.B", &[R, B]),
+            Opcode::RclRegNumWord => ("RCL.W", &[R, W]),
+            Opcode::RclRegRegByte => ("RCL.B", &[R, R]),
+            Opcode::RclRegRegWord => ("RCL.W", &[R, R]),
+            Opcode::RclAddrByte => ("RCL.B", &[A]),
+            Opcode::RclAddrWord => ("RCL.W", &[A]),
+            Opcode::RcrRegNumByte => ("RCR.B", &[R, B]),
+            Opcode::RcrRegNumWord => ("RCR.W", &[R, W]),
+            Opcode::RcrRegRegByte => ("RCR.B", &[R, R]),
+            Opcode::RcrRegRegWord => ("RCR.W", &[R, R]),
+            Opcode::RcrAddrByte => ("RCR.B", &[A]),
+            Opcode::RcrAddrWord => ("RCR.W", &[A]),
+            Opcode::AddcRegRegByte => ("ADDC.B", &[R, R]),
+            Opcode::AddcRegRegWord => ("ADDC.W", &[R, R]),
+            Opcode::AddcRegNumByte => ("ADDC.B", &[R, B]),
+            Opcode::AddcRegNumWord => ("ADDC.W", &[R, W]),
+            Opcode::AddcRegAddrByte => ("ADDC.B", &[R, A]),
+            Opcode::AddcRegAddrWord => ("ADDC.W", &[R, A]),
+            Opcode::AddcAddrRegByte => ("ADDC.B", &[A, R]),
+            Opcode::AddcAddrRegWord => ("ADDC.W", &[A, R]),
+            Opcode::AddcAddrNumByte => ("ADDC.B", &[A, B]),
+            Opcode::AddcAddrNumWord => ("ADDC.W", &[A, W]),
+            Opcode::AddcAddrAddrByte => ("ADDC.B", &[A, A]),
+            Opcode::AddcAddrAddrWord => ("ADDC.W", &[A, A]),
+            Opcode::SubcRegRegByte => ("SUBC.B", &[R, R]),
+            Opcode::SubcRegRegWord => ("SUBC.W", &[R, R]),
+            Opcode::SubcRegNumByte => ("SUBC.B", &[R, B]),
+            Opcode::SubcRegNumWord => ("SUBC.W", &[R, W]),
+            Opcode::SubcRegAddrByte => ("SUBC.B", &[R, A]),
+            Opcode::SubcRegAddrWord => ("SUBC.W", &[R, A]),
+            Opcode::SubcAddrRegByte => ("SUBC.B", &[A, R]),
+            Opcode::SubcAddrRegWord => ("SUBC.W", &[A, R]),
+            Opcode::SubcAddrNumByte => ("SUBC.B", &[A, B]),
+            Opcode::SubcAddrNumWord => ("SUBC.W", &[A, W]),
+            Opcode::SubcAddrAddrByte => ("SUBC.B", &[A, A]),
+            Opcode::SubcAddrAddrWord => ("SUBC.W", &[A, A]),
+            Opcode::BmulAddrAddr => ("BMUL", &[A, A]),
+            Opcode::BmulAddrNum => ("BMUL", &[A, B]),
+            Opcode::BmulAddrReg => ("BMUL", &[A, R]),
+            Opcode::BmulRegAddr => ("BMUL", &[R, A]),
+            Opcode::BmulRegNum => ("BMUL", &[R, B]),
+            Opcode::BmulRegReg => ("BMUL", &[R, R]),
+            Opcode::Ehalt => ("EHALT", &[]),
+            Opcode::Sleep => ("SLEEP", &[]),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AddressingMode {
+    /// `AL`
+    Direct,
+    /// `(AX)`
+    Indirect,
+    /// `+AL`
+    PreInc,
+    /// `-AL`
+    PreDec,
+    /// `AL+`
+    PostInc,
+    /// `AL-`
+    PostDec,
+    /// `(+AX)`
+    IndirectPreInc,
+    /// `(-AX)`
+    IndirectPreDec,
+    /// `(AX+)`
+    IndirectPostInc,
+    /// `(AX-)`
+    IndirectPostDec,
+    /// `(AX+CL)`, offset is a byte register id
+    OffsetReg(u8),
+    /// `(AX+BX)`, offset is a word register id
+    OffsetExtReg(u8),
+    /// `(AX+20)`
+    OffsetNum(i16),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RegisterOperand {
+    /// See [maikor_platform::registers::id]
+    pub id: u8,
+    pub mode: AddressingMode,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operand {
+    Register(RegisterOperand),
+    /// Memory address
+    Addr(u16),
+    Byte(u8),
+    Word(u16),
+}
+
+/// Decoded op
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    operands: [Operand; MAX_OPERANDS],
+    operand_count: usize,
+    /// Number of bytes used by op and operands
+    pub len: usize,
+}
+
+impl Instruction {
+    pub fn op(&self) -> u8 {
+        self.opcode as u8
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.opcode.mnemonic()
+    }
+
+    /// Operands in the order they're written, offsets are included in register operands
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DecodeError {
+    UnknownOp(u8),
+    /// Register byte (including addressing mode) is invalid
+    InvalidRegister(u8),
+    /// Not enough bytes for the operands
+    Truncated,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnknownOp(op) => write!(f, "Unknown op: {op:02X}"),
+            DecodeError::InvalidRegister(byte) => write!(f, "Invalid register: {byte:02X}"),
+            DecodeError::Truncated => write!(f, "Not enough bytes for operands"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decode op at the start of `bytes`
+pub fn decode(bytes: &[u8]) -> Result<Instruction, DecodeError> {
+    let op = *bytes.first().ok_or(DecodeError::Truncated)?;
+    let opcode = Opcode::from_byte(op).ok_or(DecodeError::UnknownOp(op))?;
+    let kinds = opcode.layout().1;
+    let mut reader = Reader { bytes, idx: 1 };
+    let mut operands = [Operand::Byte(0); MAX_OPERANDS];
+    //offset addressing bytes are read after all operands
+    let mut offsets = [0; MAX_OPERANDS];
+    let mut offset_count = 0;
+    for (i, kind) in kinds.iter().enumerate() {
+        operands[i] = match kind {
+            Kind::Reg => {
+                let byte = reader.byte()?;
+                let (id, mode) = parse_register(byte)?;
+                if matches!(
+                    byte & MASK,
+                    IND_OFFSET_REG | IND_OFFSET_EXT_REG | IND_OFFSET_NUM
+                ) {
+                    offsets[offset_count] = i;
+                    offset_count += 1;
+                }
+                Operand::Register(RegisterOperand { id, mode })
+            }
+            Kind::Addr => Operand::Addr(reader.word()?),
+            Kind::Byte => Operand::Byte(reader.byte()?),
+            Kind::Word => Operand::Word(reader.word()?),
+        };
+    }
+    let offsets = &mut offsets[..offset_count];
+    if src_first(opcode) {
+        offsets.sort_by_key(|i| match i {
+            0 => 1,
+            1 => 0,
+            i => *i,
+        });
+    }
+    for i in offsets {
+        if let Operand::Register(reg) = &mut operands[*i] {
+            reg.mode = match reg.mode {
+                AddressingMode::OffsetReg(_) => AddressingMode::OffsetReg(reader.byte()? & 0x0F),
+                AddressingMode::OffsetExtReg(_) => {
+                    AddressingMode::OffsetExtReg(reader.byte()? & 0x0F)
+                }
+                _ => AddressingMode::OffsetNum(reader.word()? as i16),
+            };
+        }
+    }
+    Ok(Instruction {
+        opcode,
+        operands,
+        operand_count: kinds.len(),
+        len: reader.idx,
+    })
+}
+
+impl VM {
+    /// Decode op at `addr` without executing it
+    /// Memory is read directly and so watchpoints are not triggered
+    pub fn decode(&self, addr: u16) -> Result<Instruction, DecodeError> {
+        let end = (addr as usize + MAX_LEN).min(self.memory.len());
+        decode(&self.memory[addr as usize..end])
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    idx: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self.bytes.get(self.idx).ok_or(DecodeError::Truncated)?;
+        self.idx += 1;
+        Ok(byte)
+    }
+
+    fn word(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_be_bytes([self.byte()?, self.byte()?]))
+    }
+}
+
+/// Offset values are filled in after all operands are read
+fn parse_register(byte: u8) -> Result<(u8, AddressingMode), DecodeError> {
+    let id = byte & 0x0F;
+    if id > id::DX {
+        return Err(DecodeError::InvalidRegister(byte));
+    }
+    let mode = match byte & MASK {
+        REGISTER => AddressingMode::Direct,
+        INDIRECT => AddressingMode::Indirect,
+        PRE_INC => AddressingMode::PreInc,
+        PRE_DEC => AddressingMode::PreDec,
+        POST_INC => AddressingMode::PostInc,
+        POST_DEC => AddressingMode::PostDec,
+        IND_PRE_INC => AddressingMode::IndirectPreInc,
+        IND_PRE_DEC => AddressingMode::IndirectPreDec,
+        IND_POST_INC => AddressingMode::IndirectPostInc,
+        IND_POST_DEC => AddressingMode::IndirectPostDec,
+        IND_OFFSET_REG => AddressingMode::OffsetReg(0),
+        IND_OFFSET_EXT_REG => AddressingMode::OffsetExtReg(0),
+        IND_OFFSET_NUM => AddressingMode::OffsetNum(0),
+        _ => return Err(DecodeError::InvalidRegister(byte)),
+    };
+    Ok((id, mode))
+}
+
+/// These ops read the offset for their source register (second operand) before
+/// their destination register (first operand)
+fn src_first(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::AslRegRegByte
+            | Opcode::AslRegRegWord
+            | Opcode::AsrRegRegByte
+            | Opcode::AsrRegRegWord
+            | Opcode::LsrRegRegByte
+            | Opcode::LsrRegRegWord
+            | Opcode::RolRegRegByte
+            | Opcode::RolRegRegWord
+            | Opcode::RorRegRegByte
+            | Opcode::RorRegRegWord
+            | Opcode::RclRegRegByte
+            | Opcode::RclRegRegWord
+            | Opcode::RcrRegRegByte
+            | Opcode::RcrRegRegWord
+            | Opcode::JbcRegReg
+            | Opcode::JbsRegReg
+            | Opcode::MemCpyRegRegByte
+            | Opcode::MemCpyRegRegReg
+            | Opcode::MswpRegRegByte
+            | Opcode::MswpRegRegReg
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        assert_eq!(decode(&[]), Err(DecodeError::Truncated));
+        assert_eq!(decode(&[ops::JMP_ADDR, 0]), Err(DecodeError::Truncated));
+        assert_eq!(
+            decode(&[ops::INC_REG_BYTE, 13]),
+            Err(DecodeError::InvalidRegister(13))
+        );
+        assert_eq!(decode(&[0x6E]), Err(DecodeError::UnknownOp(0x6E)));
+    }
+
+    #[test]
+    fn opcodes() {
+        for byte in 0..=255 {
+            if let Some(opcode) = Opcode::from_byte(byte) {
+                assert_eq!(opcode as u8, byte);
+            }
+        }
+        assert_eq!(Opcode::from_byte(ops::NOP), Some(Opcode::Nop));
+        assert_eq!(Opcode::MemCpyRegRegReg.mnemonic(), "MCPY");
+    }
+
+    #[test]
+    fn operands() {
+        let instruction = decode(&[ops::CPY_REG_NUM_WORD, id::AX | POST_INC, 0x01, 0x02]).unwrap();
+        assert_eq!(instruction.opcode, Opcode::CpyRegNumWord);
+        assert_eq!(instruction.len, 4);
+        assert_eq!(
+            instruction.operands(),
+            &[
+                Operand::Register(RegisterOperand {
+                    id: id::AX,
+                    mode: AddressingMode::PostInc
+                }),
+                Operand::Word(0x0102)
+            ]
+        );
+    }
+
+    #[test]
+    fn src_offset_first() {
+        let instruction = decode(&[
+            ops::MEM_CPY_REG_REG_REG,
+            id::AX | IND_OFFSET_NUM,
+            id::BX | IND_OFFSET_NUM,
+            id::CL | IND_OFFSET_REG,
+            0,
+            1,
+            0,
+            2,
+            id::DL,
+        ])
+        .unwrap();
+        let modes: Vec<AddressingMode> = instruction
+            .operands()
+            .iter()
+            .map(|operand| match operand {
+                Operand::Register(reg) => reg.mode,
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                AddressingMode::OffsetNum(2),
+                AddressingMode::OffsetNum(1),
+                AddressingMode::OffsetReg(id::DL)
+            ]
+        );
+    }
+
+    #[test]
+    fn vm_decode() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0x100, &[ops::INC_REG_BYTE, id::AL]);
+        let instruction = vm.decode(0x100).unwrap();
+        assert_eq!(instruction.opcode, Opcode::IncRegByte);
+        assert_eq!(vm.pc, 0);
+        assert_eq!(vm.registers[1], 0);
+    }
+}
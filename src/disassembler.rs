@@ -0,0 +1,175 @@
+//! Text output for decoded [Instruction]s, using the same notation as the assembler
+//!
+//! Registers are written as `AL`, `(AX)`, `(AX+)`, `(-AX)`, `(AX+20)`, etc, addresses as `$256`,
+//! words as `x100` and bytes as `10`
+
+use crate::decoder::{decode, AddressingMode, Instruction, Operand, RegisterOperand};
+use crate::VM;
+use maikor_platform::registers::id;
+use std::fmt::{Display, Formatter};
+
+impl Display for RegisterOperand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = id::to_name(self.id).unwrap_or("??");
+        match self.mode {
+            AddressingMode::Direct => write!(f, "{name}"),
+            AddressingMode::Indirect => write!(f, "({name})"),
+            AddressingMode::PreInc => write!(f, "+{name}"),
+            AddressingMode::PreDec => write!(f, "-{name}"),
+            AddressingMode::PostInc => write!(f, "{name}+"),
+            AddressingMode::PostDec => write!(f, "{name}-"),
+            AddressingMode::IndirectPreInc => write!(f, "(+{name})"),
+            AddressingMode::IndirectPreDec => write!(f, "(-{name})"),
+            AddressingMode::IndirectPostInc => write!(f, "({name}+)"),
+            AddressingMode::IndirectPostDec => write!(f, "({name}-)"),
+            AddressingMode::OffsetReg(offset) | AddressingMode::OffsetExtReg(offset) => {
+                write!(f, "({name}+{})", id::to_name(offset).unwrap_or("??"))
+            }
+            AddressingMode::OffsetNum(offset) if offset < 0 => write!(f, "({name}{offset})"),
+            AddressingMode::OffsetNum(offset) => write!(f, "({name}+{offset})"),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(reg) => write!(f, "{reg}"),
+            Operand::Addr(addr) => write!(f, "${addr}"),
+            Operand::Byte(byte) => write!(f, "{byte}"),
+            Operand::Word(word) => write!(f, "x{word:X}"),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, operand) in self.operands().iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{separator}{operand}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode ops in `bytes` until the end or an invalid op is found
+/// Returns list of op addresses (starting at `addr`) and instructions
+pub fn disassemble(bytes: &[u8], addr: u16) -> Vec<(u16, Instruction)> {
+    let mut output = vec![];
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match decode(&bytes[idx..]) {
+            Ok(instruction) => {
+                let len = instruction.len;
+                output.push((addr.wrapping_add(idx as u16), instruction));
+                idx += len;
+            }
+            Err(_) => break,
+        }
+    }
+    output
+}
+
+impl VM {
+    /// Decode up to `count` ops starting at `addr`, stops early if an invalid op is found
+    /// Memory is read directly and so watchpoints are not triggered
+    pub fn disassemble(&self, addr: u16, count: usize) -> Vec<(u16, Instruction)> {
+        let mut output = Vec::with_capacity(count);
+        let mut addr = addr;
+        while output.len() < count {
+            match self.decode(addr) {
+                Ok(instruction) => {
+                    let len = instruction.len as u16;
+                    output.push((addr, instruction));
+                    addr = addr.wrapping_add(len);
+                }
+                Err(_) => break,
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::op_params::*;
+    use maikor_platform::ops;
+
+    fn text(bytes: &[u8]) -> String {
+        decode(bytes).unwrap().to_string()
+    }
+
+    #[test]
+    fn simple() {
+        assert_eq!(text(&[ops::NOP]), "NOP");
+        assert_eq!(text(&[ops::CPY_REG_NUM_BYTE, id::AL, 1]), "CPY.B AL, 1");
+        assert_eq!(
+            text(&[ops::CPY_ADDR_NUM_WORD, 0x01, 0x00, 0xFF, 0x01]),
+            "CPY.W $256, xFF01"
+        );
+        assert_eq!(decode(&[ops::JMP_ADDR, 0, 10]).unwrap().len, 3);
+    }
+
+    #[test]
+    fn addressing_modes() {
+        assert_eq!(
+            text(&[ops::ADD_REG_REG_BYTE, id::AH, id::AL | POST_INC]),
+            "ADD.B AH, AL+"
+        );
+        assert_eq!(
+            text(&[
+                ops::CPY_REG_REG_BYTE,
+                id::AH | INDIRECT,
+                id::BX | IND_PRE_DEC
+            ]),
+            "CPY.B (AH), (-BX)"
+        );
+        assert_eq!(
+            text(&[ops::INC_REG_BYTE, id::AX | IND_OFFSET_NUM, 0xFF, 0xFE]),
+            "INC.B (AX-2)"
+        );
+    }
+
+    #[test]
+    fn offset_order() {
+        let instruction = decode(&[
+            ops::CPY_REG_REG_BYTE,
+            id::AX | IND_OFFSET_NUM,
+            id::BX | IND_OFFSET_REG,
+            0,
+            20,
+            id::CL,
+        ])
+        .unwrap();
+        assert_eq!(instruction.to_string(), "CPY.B (AX+20), (BX+CL)");
+        assert_eq!(instruction.len, 6);
+
+        //source offset is read first
+        let instruction = decode(&[
+            ops::ASL_REG_REG_BYTE,
+            id::AX | IND_OFFSET_NUM,
+            id::BX | IND_OFFSET_EXT_REG,
+            id::CX,
+            0,
+            20,
+        ])
+        .unwrap();
+        assert_eq!(instruction.to_string(), "ASL.B (AX+20), (BX+CX)");
+    }
+
+    #[test]
+    fn vm_disassemble() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::INC_REG_BYTE, id::AL, ops::JMP_ADDR, 0, 0]);
+        let output = vm.disassemble(0, 3);
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[0].0, 0);
+        assert_eq!(output[0].1.to_string(), "INC.B AL");
+        assert_eq!(output[1].0, 2);
+        assert_eq!(output[1].1.to_string(), "JMP $0");
+        assert_eq!(output[2].0, 5);
+        assert_eq!(output[2].1.to_string(), "NOP");
+    }
+}
@@ -0,0 +1,293 @@
+use crate::decoder::DecodeError;
+use crate::save_state::{ByteReader, ByteWriter, SaveStateError};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Type of bank, used in [VmError::InvalidBank]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BankKind {
+    Ram,
+    Save,
+}
+
+/// Why the VM halted, see [VM::error](crate::VM::error)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VmError {
+    /// Op at `pc` is unknown or not supported by this VM
+    UnsupportedOp { op: u8, pc: u16 },
+    /// Op at `pc` goes past the end of memory
+    TruncatedOp { pc: u16 },
+    /// Register byte (including addressing mode) of op at `pc` is invalid
+    InvalidRegister { value: u8, pc: u16 },
+    /// Op at `pc` needed more stack space than is available
+    StackOverflow { pc: u16 },
+    /// Op at `pc` tried to read more from the stack than has been written to it
+    StackUnderflow { pc: u16 },
+    /// RET at `pc` restored a frame pointer that isn't inside the stack, see [VM::strict_stack](crate::VM::strict_stack)
+    InvalidFrame { fp: u16, pc: u16 },
+    /// Bank `requested` was used but there are only `available` banks of `kind`
+    InvalidBank {
+        kind: BankKind,
+        requested: usize,
+        available: usize,
+    },
+    /// Interrupt id passed to [VM::trigger_interrupt()](crate::VM::trigger_interrupt) is not valid
+    InvalidInterrupt(u8),
+    /// Game executed EHALT at `pc`
+    HaltedByProgram { pc: u16 },
+    /// Game file header is invalid, contains the reasons
+    GameValidation(String),
+}
+
+impl VmError {
+    pub(crate) fn from_decode(error: DecodeError, pc: u16) -> VmError {
+        match error {
+            DecodeError::UnknownOp(op) => VmError::UnsupportedOp { op, pc },
+            DecodeError::InvalidRegister(value) => VmError::InvalidRegister { value, pc },
+            DecodeError::Truncated => VmError::TruncatedOp { pc },
+        }
+    }
+
+    /// Returns true if the error was caused by the game (i.e. bad code or EHALT) rather than
+    /// the VM setup or host
+    pub fn is_game_error(&self) -> bool {
+        !matches!(
+            self,
+            VmError::InvalidInterrupt(_) | VmError::GameValidation(_)
+        )
+    }
+}
+
+impl BankKind {
+    fn from_byte(value: u8) -> Result<BankKind, SaveStateError> {
+        match value {
+            0 => Ok(BankKind::Ram),
+            1 => Ok(BankKind::Save),
+            _ => Err(SaveStateError::InvalidFormat("invalid bank kind")),
+        }
+    }
+}
+
+impl VmError {
+    pub(crate) fn write(&self, writer: &mut ByteWriter) {
+        match self {
+            VmError::UnsupportedOp { op, pc } => {
+                writer.write_u8(0);
+                writer.write_u8(*op);
+                writer.write_u16(*pc);
+            }
+            VmError::TruncatedOp { pc } => {
+                writer.write_u8(1);
+                writer.write_u16(*pc);
+            }
+            VmError::InvalidRegister { value, pc } => {
+                writer.write_u8(2);
+                writer.write_u8(*value);
+                writer.write_u16(*pc);
+            }
+            VmError::StackOverflow { pc } => {
+                writer.write_u8(3);
+                writer.write_u16(*pc);
+            }
+            VmError::StackUnderflow { pc } => {
+                writer.write_u8(4);
+                writer.write_u16(*pc);
+            }
+            VmError::InvalidBank {
+                kind,
+                requested,
+                available,
+            } => {
+                writer.write_u8(5);
+                writer.write_u8(*kind as u8);
+                writer.write_u32(*requested as u32);
+                writer.write_u32(*available as u32);
+            }
+            VmError::InvalidInterrupt(id) => {
+                writer.write_u8(6);
+                writer.write_u8(*id);
+            }
+            VmError::HaltedByProgram { pc } => {
+                writer.write_u8(7);
+                writer.write_u16(*pc);
+            }
+            VmError::GameValidation(reason) => {
+                writer.write_u8(8);
+                writer.write_u32(reason.len() as u32);
+                writer.write_bytes(reason.as_bytes());
+            }
+            VmError::InvalidFrame { fp, pc } => {
+                writer.write_u8(9);
+                writer.write_u16(*fp);
+                writer.write_u16(*pc);
+            }
+        }
+    }
+
+    pub(crate) fn read(reader: &mut ByteReader) -> Result<VmError, SaveStateError> {
+        Ok(match reader.read_u8()? {
+            0 => VmError::UnsupportedOp {
+                op: reader.read_u8()?,
+                pc: reader.read_u16()?,
+            },
+            1 => VmError::TruncatedOp {
+                pc: reader.read_u16()?,
+            },
+            2 => VmError::InvalidRegister {
+                value: reader.read_u8()?,
+                pc: reader.read_u16()?,
+            },
+            3 => VmError::StackOverflow {
+                pc: reader.read_u16()?,
+            },
+            4 => VmError::StackUnderflow {
+                pc: reader.read_u16()?,
+            },
+            5 => VmError::InvalidBank {
+                kind: BankKind::from_byte(reader.read_u8()?)?,
+                requested: reader.read_u32()? as usize,
+                available: reader.read_u32()? as usize,
+            },
+            6 => VmError::InvalidInterrupt(reader.read_u8()?),
+            7 => VmError::HaltedByProgram {
+                pc: reader.read_u16()?,
+            },
+            8 => {
+                let len = reader.read_u32()? as usize;
+                let bytes = reader.read_bytes(len)?;
+                VmError::GameValidation(String::from_utf8_lossy(bytes).to_string())
+            }
+            9 => VmError::InvalidFrame {
+                fp: reader.read_u16()?,
+                pc: reader.read_u16()?,
+            },
+            _ => return Err(SaveStateError::InvalidFormat("invalid error")),
+        })
+    }
+}
+
+impl Display for BankKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BankKind::Ram => "RAM",
+            BankKind::Save => "save",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::UnsupportedOp { op, pc } => {
+                write!(f, "Unsupported op: {op:02X} at {pc:04X}")
+            }
+            VmError::TruncatedOp { pc } => write!(f, "Op at {pc:04X} goes past end of memory"),
+            VmError::InvalidRegister { value, pc } => {
+                write!(f, "Invalid register: {value:02X} at {pc:04X}")
+            }
+            VmError::StackOverflow { pc } => write!(f, "Stack overflow at {pc:04X}"),
+            VmError::StackUnderflow { pc } => write!(f, "Stack underflow at {pc:04X}"),
+            VmError::InvalidFrame { fp, pc } => {
+                write!(f, "Invalid frame pointer {fp:04X} restored at {pc:04X}")
+            }
+            VmError::InvalidBank {
+                kind,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Attempted load {kind} bank {requested}, but only {available} available"
+            ),
+            VmError::InvalidInterrupt(id) => {
+                write!(f, "Attempted to trigger invalid interrupt id: {id}")
+            }
+            VmError::HaltedByProgram { pc } => write!(f, "Halted by program at {pc:04X}"),
+            VmError::GameValidation(reason) => write!(f, "Invalid game: {reason}"),
+        }
+    }
+}
+
+impl Error for VmError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{address, sizes, VM};
+    use maikor_platform::ops;
+
+    #[test]
+    fn halted_by_program() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::NOP, ops::EHALT]);
+        vm.step();
+        vm.step();
+        assert!(vm.halted);
+        assert_eq!(vm.error, Some(VmError::HaltedByProgram { pc: 1 }));
+        assert!(vm
+            .error_report()
+            .unwrap()
+            .starts_with("Halted by program at 0001\n"));
+    }
+
+    #[test]
+    fn unsupported_op() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::NOP, 0x6E]);
+        vm.step();
+        vm.step();
+        assert_eq!(vm.error, Some(VmError::UnsupportedOp { op: 0x6E, pc: 1 }));
+        assert!(vm.error.unwrap().is_game_error());
+
+        //decoded but not implemented, should stop in the same way as an unknown op
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::NOP, ops::CMP_REG_REG_BYTE, 0, 1]);
+        vm.step();
+        vm.step();
+        assert!(vm.halted);
+        assert_eq!(
+            vm.error,
+            Some(VmError::UnsupportedOp {
+                op: ops::CMP_REG_REG_BYTE,
+                pc: 1
+            })
+        );
+        assert_eq!(vm.pc, 1);
+        assert_eq!(vm.op_executed, 1);
+        assert_eq!(vm.cycles_executed, 3);
+    }
+
+    #[test]
+    fn invalid_bank() {
+        let mut vm = VM::new_test();
+        vm.ram_banks = vec![[0; sizes::RAM_BANK]];
+        let ram_id = (address::RAM_BANK_1_ID as u16).to_be_bytes();
+        let ram = (address::RAM_BANK_1 as u16).to_be_bytes();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CPY_ADDR_NUM_BYTE, ram_id[0], ram_id[1], 3,
+            ops::CPY_ADDR_NUM_BYTE, ram[0], ram[1], 1,
+        ]);
+        vm.step();
+        assert!(!vm.halted);
+        vm.step();
+        assert!(vm.halted);
+        assert_eq!(
+            vm.error,
+            Some(VmError::InvalidBank {
+                kind: BankKind::Ram,
+                requested: 3,
+                available: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_interrupt() {
+        let mut vm = VM::new_test();
+        vm.memory[address::IRQ_CONTROL] = 0xFF;
+        vm.trigger_interrupt(0xFF);
+        assert_eq!(vm.error, Some(VmError::InvalidInterrupt(0xFF)));
+        assert!(!vm.error.unwrap().is_game_error());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::history::Event;
+use crate::renderer::{render_line, BUFFER_SIZE};
+use crate::{address, VM};
+use maikor_platform::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use maikor_platform::mem::interrupt_flags;
+
+/// VM cycles per scanline
+pub const CYCLES_PER_LINE: usize = 400;
+/// Lines after the last visible line before the next frame starts
+pub const VBLANK_LINES: usize = 15;
+/// Visible and vblank lines
+pub const LINES_PER_FRAME: usize = SCREEN_HEIGHT + VBLANK_LINES;
+/// VM cycles per frame, at the default speed (2^22 cycles per second) this is ~60 FPS
+pub const CYCLES_PER_FRAME: usize = CYCLES_PER_LINE * LINES_PER_FRAME;
+
+impl VM {
+    /// Execute one frame worth of cycles
+    ///
+    /// `DATETIME` and `INPUT` are updated at the start of the frame, see [VM::update_datetime()]
+    /// and [VM::update_input()]
+    ///
+    /// At the start of each visible line the line number is written to `VLINE` and
+    /// `IRQ_LINE_DRAW` is triggered, at the start of vblank `IRQ_SCREEN_DRAW` is triggered
+    ///
+    /// Ops aren't split so a line may run over by a few cycles, these are taken from the next
+    /// line (including the first line of the next frame)
+    ///
+    /// Returns the number of cycles executed, this will be less than [CYCLES_PER_FRAME] if the
+    /// VM halts
+    pub fn run_frame(&mut self) -> usize {
+        self.run_frame_inner(None)
+    }
+
+    /// Same as [VM::run_frame()] but also draws each visible line into `buffer` at the end of
+    /// the line, so changes made to video memory between lines (i.e. by `IRQ_LINE_DRAW`)
+    /// only affect the lines after them
+    ///
+    /// Lines not reached because the VM halted are left unchanged
+    ///
+    /// # Panics
+    /// If `buffer` is smaller than [BUFFER_SIZE]
+    pub fn run_frame_and_render(&mut self, buffer: &mut [u32]) -> usize {
+        assert!(buffer.len() >= BUFFER_SIZE, "buffer is too small");
+        self.run_frame_inner(Some(buffer))
+    }
+
+    fn run_frame_inner(&mut self, mut buffer: Option<&mut [u32]>) -> usize {
+        let start = self.cycles_executed;
+        self.update_datetime();
+        self.update_input();
+        for line in 0..LINES_PER_FRAME {
+            if self.halted {
+                break;
+            }
+            self.start_line(line as u8);
+            while self.line_cycles < CYCLES_PER_LINE && !self.halted {
+                //ops that take no cycles still count as one so the line always ends
+                self.line_cycles += self.step().max(1);
+            }
+            if let Some(buffer) = buffer.as_deref_mut() {
+                if line < SCREEN_HEIGHT && !self.halted {
+                    let start = line * SCREEN_WIDTH;
+                    render_line(self, line, &mut buffer[start..start + SCREEN_WIDTH]);
+                }
+            }
+        }
+        self.log_event(Event::EndFrame);
+        self.cycles_executed - start
+    }
+
+    /// Carry over cycles from the last line, write `line` to `VLINE` and trigger the line or
+    /// screen interrupt
+    /// Recorded in history as a single op
+    pub(crate) fn start_line(&mut self, line: u8) {
+        self.begin_history_record();
+        self.log_event(Event::Line(line));
+        self.line_cycles = self.line_cycles.saturating_sub(CYCLES_PER_LINE);
+        self.log_mem(address::VLINE, 1, false);
+        self.memory[address::VLINE] = line;
+        if (line as usize) < SCREEN_HEIGHT {
+            self.trigger_interrupt(interrupt_flags::IRQ_LINE_DRAW);
+        } else if line as usize == SCREEN_HEIGHT {
+            self.trigger_interrupt(interrupt_flags::IRQ_SCREEN_DRAW);
+        }
+        self.end_history_record();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::datetime::{Clock, DateTime};
+    use maikor_platform::mem::address::interrupt;
+    use maikor_platform::ops;
+
+    const LINE_COUNTER: u16 = 0x5000;
+    const SCREEN_COUNTER: u16 = 0x5002;
+    const LAST_LINE: u16 = 0x5003;
+
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now(&mut self) -> DateTime {
+            DateTime::default()
+        }
+    }
+
+    fn setup() -> VM {
+        let mut vm = VM::new_test();
+        vm.set_clock(Box::new(FixedClock));
+        let line = LINE_COUNTER.to_be_bytes();
+        let screen = SCREEN_COUNTER.to_be_bytes();
+        let last_line = LAST_LINE.to_be_bytes();
+        let vline = (address::VLINE as u16).to_be_bytes();
+        vm.debug_set_mem_range(0, &[ops::JMP_ADDR, 0, 0]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(interrupt::IRQ_LINE_DRAW, &[
+            ops::INC_ADDR_WORD, line[0], line[1],
+            ops::MEM_CPY_ADDR_ADDR_BYTE, last_line[0], last_line[1], vline[0], vline[1], 1,
+            ops::RETI,
+        ]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(interrupt::IRQ_SCREEN_DRAW, &[
+            ops::INC_ADDR_BYTE, screen[0], screen[1],
+            ops::RETI,
+        ]);
+        vm.debug_set_mem(
+            address::IRQ_CONTROL as u16,
+            interrupt_flags::IRQ_LINE_DRAW | interrupt_flags::IRQ_SCREEN_DRAW,
+        );
+        vm
+    }
+
+    #[test]
+    fn interrupts() {
+        let mut vm = setup();
+        let cycles = vm.run_frame();
+        assert!(!vm.halted, "{:?}", vm.error);
+        assert!(cycles >= CYCLES_PER_FRAME - CYCLES_PER_LINE);
+        assert!(cycles < CYCLES_PER_FRAME + CYCLES_PER_LINE);
+        assert_eq!(
+            u16::from_be_bytes([
+                vm.memory[LINE_COUNTER as usize],
+                vm.memory[LINE_COUNTER as usize + 1]
+            ]),
+            SCREEN_HEIGHT as u16
+        );
+        assert_eq!(vm.memory[SCREEN_COUNTER as usize], 1);
+        assert_eq!(vm.memory[LAST_LINE as usize], SCREEN_HEIGHT as u8 - 1);
+        assert_eq!(vm.memory[address::VLINE], LINES_PER_FRAME as u8 - 1);
+
+        vm.run_frame();
+        assert_eq!(vm.memory[SCREEN_COUNTER as usize], 2);
+    }
+
+    #[test]
+    fn cycles_carry_over() {
+        let mut vm = setup();
+        let mut total = 0;
+        for _ in 0..10 {
+            total += vm.run_frame();
+        }
+        assert!(total.abs_diff(CYCLES_PER_FRAME * 10) < CYCLES_PER_LINE);
+    }
+
+    #[test]
+    fn halted() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::NOP, ops::HALT]);
+        assert_eq!(vm.run_frame(), 3);
+        assert!(vm.halted);
+        assert_eq!(vm.run_frame(), 0);
+    }
+
+    #[test]
+    fn raster_effect() {
+        let mut vm = VM::new_test();
+        let color = (address::PALETTES as u16 + 3).to_be_bytes();
+        let vline = (address::VLINE as u16).to_be_bytes();
+        vm.debug_set_mem_range(0, &[ops::JMP_ADDR, 0, 0]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(interrupt::IRQ_LINE_DRAW, &[
+            ops::MEM_CPY_ADDR_ADDR_BYTE, color[0], color[1], vline[0], vline[1], 1,
+            ops::RETI,
+        ]);
+        vm.debug_set_mem(address::IRQ_CONTROL as u16, interrupt_flags::IRQ_LINE_DRAW);
+        //layer 0 is tile 0 everywhere, which is color 1 of palette 0
+        vm.memory[address::PALETTES + 5] = 255;
+        vm.memory[address::LAYER_HEADERS + 2] = 1;
+        for row in 0..8 {
+            let start = address::ATLAS1 + row * 100;
+            vm.memory[start..start + 4].fill(0x11);
+        }
+
+        let mut buffer = vec![0; BUFFER_SIZE];
+        vm.run_frame_and_render(&mut buffer);
+        for y in 0..SCREEN_HEIGHT {
+            assert_eq!(
+                buffer[y * SCREEN_WIDTH + y],
+                u32::from_be_bytes([y as u8, 0, 255, 255])
+            );
+        }
+    }
+
+    #[test]
+    fn rewind_frame() {
+        let mut vm = setup();
+        vm.enable_history(100000);
+        let memory = vm.memory;
+        let registers = vm.registers;
+        vm.run_frame();
+        while vm.step_back() {}
+        assert_eq!(vm.pc, 0);
+        assert_eq!(vm.registers, registers);
+        assert_eq!(vm.cycles_executed, 0);
+        assert_eq!(vm.line_cycles, 0);
+        assert!(vm.memory == memory);
+    }
+
+    #[test]
+    fn replay_frame() {
+        let mut recorded = setup();
+        recorded.enable_history(1000000);
+        let mut replayed = setup();
+        replayed.enable_history(200);
+        replayed.set_history_checkpoints(200, 1000);
+        for _ in 0..2 {
+            recorded.run_frame();
+            replayed.run_frame();
+        }
+        let mut count = 0;
+        while recorded.step_back() {
+            assert!(replayed.step_back());
+            assert_eq!(recorded.pc, replayed.pc);
+            assert_eq!(recorded.registers, replayed.registers);
+            assert_eq!(recorded.cycles_executed, replayed.cycles_executed);
+            assert_eq!(recorded.line_cycles, replayed.line_cycles);
+            if count % 1000 == 0 {
+                assert!(
+                    recorded.memory == replayed.memory,
+                    "memory differs after {count}"
+                );
+            }
+            count += 1;
+        }
+        assert!(!replayed.step_back());
+        assert!(recorded.memory == replayed.memory);
+    }
+}
@@ -0,0 +1,201 @@
+//! Run games without a host and compare screenshots against golden images
+//!
+//! Images are binary PPM (P6) files as they need no extra dependencies and can be opened by
+//! most image tools
+//!
+//! Set the `MAIKOR_UPDATE_GOLDEN` env var to write the golden images instead of comparing them
+//!
+//! Requires the `test-support` feature
+
+use crate::error::VmError;
+use crate::renderer::BUFFER_SIZE;
+use crate::{sizes, VM};
+use maikor_platform::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use maikor_vm_file::GameFile;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// Env var that makes [assert_golden()] write the golden image
+pub const UPDATE_GOLDEN_VAR: &str = "MAIKOR_UPDATE_GOLDEN";
+/// Color used for pixels that don't match in diff images
+const DIFF_COLOR: u32 = 0xFF0000FF;
+
+/// Load `game` into a VM (without audio output) and run it for `frames` frames
+/// Returns the VM and the last frame
+pub fn run_game(
+    game: GameFile,
+    saves: &[[u8; sizes::SAVE_BANK]],
+    frames: usize,
+) -> Result<(VM, Vec<u32>), VmError> {
+    let mut vm = VM::new_test();
+    vm.load_game(game, saves)?;
+    vm.init();
+    let buffer = run_frames(&mut vm, frames);
+    Ok((vm, buffer))
+}
+
+/// Run `frames` frames (see [VM::run_frame_and_render()]) and return the last one
+/// Stops early if the VM halts
+pub fn run_frames(vm: &mut VM, frames: usize) -> Vec<u32> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    for _ in 0..frames {
+        if vm.halted {
+            break;
+        }
+        vm.run_frame_and_render(&mut buffer);
+    }
+    buffer
+}
+
+/// Write a frame as a PPM image
+pub fn write_ppm<W: Write>(buffer: &[u32], writer: &mut W) -> std::io::Result<()> {
+    write!(writer, "P6\n{SCREEN_WIDTH} {SCREEN_HEIGHT}\n255\n")?;
+    let mut bytes = Vec::with_capacity(BUFFER_SIZE * 3);
+    for pixel in &buffer[..BUFFER_SIZE] {
+        bytes.extend_from_slice(&pixel.to_be_bytes()[..3]);
+    }
+    writer.write_all(&bytes)
+}
+
+/// Read a PPM image written by [write_ppm()]
+pub fn read_ppm<R: Read>(reader: &mut R) -> std::io::Result<Vec<u32>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let header = format!("P6\n{SCREEN_WIDTH} {SCREEN_HEIGHT}\n255\n");
+    if !bytes.starts_with(header.as_bytes()) || bytes.len() != header.len() + BUFFER_SIZE * 3 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("not a {SCREEN_WIDTH}x{SCREEN_HEIGHT} binary PPM"),
+        ));
+    }
+    Ok(bytes[header.len()..]
+        .chunks_exact(3)
+        .map(|rgb| u32::from_be_bytes([rgb[0], rgb[1], rgb[2], 0xFF]))
+        .collect())
+}
+
+/// Write a frame to `path` as a PPM image
+pub fn save_screenshot<P: AsRef<Path>>(buffer: &[u32], path: P) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_ppm(buffer, &mut writer)?;
+    writer.flush()
+}
+
+/// Number of pixels that are different, alpha is ignored
+pub fn count_differences(expected: &[u32], actual: &[u32]) -> usize {
+    expected[..BUFFER_SIZE]
+        .iter()
+        .zip(&actual[..BUFFER_SIZE])
+        .filter(|(expected, actual)| (*expected | 0xFF) != (*actual | 0xFF))
+        .count()
+}
+
+/// Image where different pixels are red and the rest are a dimmed grayscale of `actual`
+pub fn diff_image(expected: &[u32], actual: &[u32]) -> Vec<u32> {
+    expected[..BUFFER_SIZE]
+        .iter()
+        .zip(&actual[..BUFFER_SIZE])
+        .map(|(&expected, &actual)| {
+            if (expected | 0xFF) != (actual | 0xFF) {
+                DIFF_COLOR
+            } else {
+                let [r, g, b, _] = actual.to_be_bytes();
+                let gray = ((r as u16 + g as u16 + b as u16) / 12) as u8;
+                u32::from_be_bytes([gray, gray, gray, 0xFF])
+            }
+        })
+        .collect()
+}
+
+/// Compare `buffer` with the golden image at `path`
+///
+/// If they don't match then `<path>.actual.ppm` and `<path>.diff.ppm` are written
+/// and this panics
+///
+/// If [UPDATE_GOLDEN_VAR] is set then `buffer` is written to `path` instead
+pub fn assert_golden<P: AsRef<Path>>(buffer: &[u32], path: P) {
+    let path = path.as_ref();
+    if env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("creating golden dir");
+        }
+        save_screenshot(buffer, path).expect("writing golden image");
+        return;
+    }
+    let expected = match File::open(path).and_then(|mut file| read_ppm(&mut file)) {
+        Ok(expected) => expected,
+        Err(err) => panic!(
+            "reading golden image {}: {err} (set {UPDATE_GOLDEN_VAR} to create it)",
+            path.display()
+        ),
+    };
+    let differences = count_differences(&expected, buffer);
+    if differences > 0 {
+        let actual_path = with_suffix(path, "actual");
+        let diff_path = with_suffix(path, "diff");
+        save_screenshot(buffer, &actual_path).expect("writing actual image");
+        save_screenshot(&diff_image(&expected, buffer), &diff_path).expect("writing diff image");
+        panic!(
+            "{differences} pixels don't match {}, see {} and {}",
+            path.display(),
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}.ppm"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image() -> Vec<u32> {
+        (0..BUFFER_SIZE as u32).map(|i| (i << 8) | 0xFF).collect()
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let buffer = image();
+        let mut bytes = vec![];
+        write_ppm(&buffer, &mut bytes).unwrap();
+        assert!(bytes.starts_with(b"P6\n240 160\n255\n"));
+        assert_eq!(read_ppm(&mut bytes.as_slice()).unwrap(), buffer);
+        assert!(read_ppm(&mut &bytes[..100]).is_err());
+    }
+
+    #[test]
+    fn diff() {
+        let expected = image();
+        let mut actual = expected.clone();
+        actual[5] = 0;
+        actual[9] = 0x000000FF;
+        assert_eq!(count_differences(&expected, &expected), 0);
+        assert_eq!(count_differences(&expected, &actual), 2);
+        let diff = diff_image(&expected, &actual);
+        assert_eq!(diff[5], DIFF_COLOR);
+        assert_eq!(diff[9], DIFF_COLOR);
+        assert_eq!(diff[0], 0x000000FF);
+    }
+
+    #[test]
+    fn golden_mismatch() {
+        let dir = env::temp_dir().join(format!("maikor_golden_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.ppm");
+        save_screenshot(&image(), &path).unwrap();
+        assert_golden(&image(), &path);
+
+        let result = std::panic::catch_unwind(|| assert_golden(&vec![0; BUFFER_SIZE], &path));
+        assert!(result.is_err());
+        assert!(dir.join("test.ppm.actual.ppm").exists());
+        assert!(dir.join("test.ppm.diff.ppm").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
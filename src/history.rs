@@ -0,0 +1,627 @@
+use crate::debugger::StopReason;
+use crate::error::VmError;
+use crate::input::InputState;
+use crate::snapshot::Snapshot;
+use crate::sound::SoundSnapshot;
+use crate::timer::Timers;
+use crate::VM;
+use maikor_platform::constants::SAVE_COUNT;
+use maikor_platform::registers;
+use std::collections::VecDeque;
+
+/// Memory byte before it was changed by an op
+#[derive(Debug, Copy, Clone)]
+struct MemChange {
+    addr: u16,
+    value: u8,
+    /// if true the change was also written to the loaded bank and so must be undone there too
+    write_through: bool,
+}
+
+/// Everything needed to undo a single op
+#[derive(Clone)]
+struct OpRecord {
+    pc: u16,
+    arg_ptr: u16,
+    registers: [u8; registers::SIZE],
+    halted: bool,
+    error: Option<VmError>,
+    op_executed: usize,
+    cycles_executed: usize,
+    line_cycles: usize,
+    input: InputState,
+    input_latch: InputState,
+    save_dirty_flag: [bool; SAVE_COUNT],
+    timers: Timers,
+    /// in order of change
+    memory: Vec<MemChange>,
+    /// sound state before the first write to sound memory by this op
+    sound: Option<SoundSnapshot>,
+}
+
+/// Change made by the VM outside of an op, stored so it can be repeated when replaying from
+/// a checkpoint
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Event {
+    /// See [VM::start_line()], makes a record
+    Line(u8),
+    /// End of [VM::run_frame()], ops after this aren't counted towards a line
+    EndFrame,
+    /// See [VM::write_datetime()], makes a record
+    DateTime([u8; 6]),
+    /// Held and latched buttons, see [VM::write_input()], makes a record
+    Input(InputState, InputState),
+}
+
+impl Event {
+    fn makes_record(&self) -> bool {
+        match self {
+            Event::Line(_) | Event::DateTime(_) | Event::Input(..) => true,
+            Event::EndFrame => false,
+        }
+    }
+}
+
+/// VM state before the record at `position` was made
+struct Checkpoint {
+    position: usize,
+    snapshot: Snapshot,
+    /// if ops were being executed by [VM::run_frame()]
+    in_frame: bool,
+}
+
+pub(crate) struct History {
+    max_ops: usize,
+    records: VecDeque<OpRecord>,
+    /// record for the op being executed
+    current: Option<OpRecord>,
+    /// number of records made, minus the number undone
+    position: usize,
+    /// records between checkpoints, 0 if checkpoints are disabled
+    checkpoint_interval: usize,
+    max_checkpoints: usize,
+    /// oldest first
+    checkpoints: VecDeque<Checkpoint>,
+    /// events since the oldest checkpoint and the position they happened at, oldest first
+    events: VecDeque<(usize, Event)>,
+    /// true between [Event::Line] and [Event::EndFrame]
+    in_frame: bool,
+    /// true while ops are being executed again to reach a position after a checkpoint
+    replaying: bool,
+}
+
+impl History {
+    /// Remove events from the current position onwards (other than those before the record at
+    /// the current position) as they're from a timeline that's been stepped back from
+    fn discard_future_events(&mut self) {
+        while let Some((position, event)) = self.events.back() {
+            if *position > self.position || (*position == self.position && event.makes_record()) {
+                self.events.pop_back();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl VM {
+    /// Record the changes made by the last `max_ops` ops so they can be undone with
+    /// [VM::step_back()], this replaces any existing history
+    ///
+    /// Each line started by [VM::run_frame()], each change made by [VM::update_datetime()]
+    /// and each change to the buttons (see [VM::set_input_state()]) is recorded as an op
+    ///
+    /// Changes made by the host (i.e. [VM::debug_set_mem()] or calling
+    /// [VM::trigger_interrupt()] directly) and sound timing are not recorded
+    ///
+    /// See [VM::set_history_checkpoints()] to go back further than `max_ops`
+    pub fn enable_history(&mut self, max_ops: usize) {
+        self.history = Some(History {
+            max_ops,
+            records: VecDeque::new(),
+            current: None,
+            position: 0,
+            checkpoint_interval: 0,
+            max_checkpoints: 0,
+            checkpoints: VecDeque::new(),
+            events: VecDeque::new(),
+            in_frame: false,
+            replaying: false,
+        });
+    }
+
+    /// Store a snapshot every `interval` ops, keeping the last `max_count`
+    /// Once the recorded ops run out [VM::step_back()] restores the latest snapshot before the
+    /// target op and executes ops again up to it, so history can reach back
+    /// `interval * max_count` ops while only storing `max_ops` records
+    ///
+    /// If changes are made by the host after a snapshot then the replayed ops may not match
+    /// the original ones
+    ///
+    /// Does nothing if history is disabled, a snapshot is taken immediately and existing
+    /// snapshots are discarded
+    /// Use an `interval` of 0 to disable snapshots
+    pub fn set_history_checkpoints(&mut self, interval: usize, max_count: usize) {
+        let snapshot = self.snapshot();
+        if let Some(history) = &mut self.history {
+            history.checkpoint_interval = if max_count == 0 { 0 } else { interval };
+            history.max_checkpoints = max_count;
+            history.checkpoints.clear();
+            history.events.clear();
+            if history.checkpoint_interval > 0 {
+                history.checkpoints.push_back(Checkpoint {
+                    position: history.position,
+                    snapshot,
+                    in_frame: false,
+                });
+            }
+        }
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Number of ops that can be undone
+    pub fn history_len(&self) -> usize {
+        self.history
+            .as_ref()
+            .map(|history| history.records.len())
+            .unwrap_or_default()
+    }
+
+    /// Undo the last op
+    /// Returns false if there's no history
+    pub fn step_back(&mut self) -> bool {
+        let record = match self.history.as_mut().and_then(|history| {
+            let record = history.records.pop_back();
+            if record.is_some() {
+                history.position -= 1;
+            }
+            record
+        }) {
+            Some(record) => record,
+            None => return self.replay_to_previous(),
+        };
+        if let Some(sound) = &record.sound {
+            self.sound.restore(sound);
+        }
+        for change in record.memory.iter().rev() {
+            self.memory[change.addr as usize] = change.value;
+            if change.write_through {
+                let _ = self.write_mem_change_to_bank(change.addr as usize, change.value);
+            }
+        }
+        self.pc = record.pc;
+        self.arg_ptr = record.arg_ptr;
+        self.registers = record.registers;
+        self.halted = record.halted;
+        self.error = record.error;
+        self.op_executed = record.op_executed;
+        self.cycles_executed = record.cycles_executed;
+        self.line_cycles = record.line_cycles;
+        self.input = record.input;
+        self.input_latch = record.input_latch;
+        self.save_dirty_flag = record.save_dirty_flag;
+        self.timers = record.timers;
+        true
+    }
+
+    /// Undo ops until PC is at a breakpoint (and its conditions match) or there's no history
+    /// At least one op is undone
+    /// Returns [StopReason::Breakpoint] or [StopReason::HistoryStart]
+    pub fn run_backwards_until(&mut self) -> StopReason {
+        loop {
+            if !self.step_back() {
+                return StopReason::HistoryStart;
+            }
+            if self.is_at_breakpoint() {
+                return StopReason::Breakpoint(self.pc);
+            }
+        }
+    }
+
+    /// Restore the latest checkpoint before the current position and execute ops (and repeat
+    /// events) up to the position before the current one
+    /// Returns false if there's no checkpoint to restore
+    fn replay_to_previous(&mut self) -> bool {
+        let (target, checkpoint, events) = match &self.history {
+            Some(history) if history.position > 0 => {
+                let target = history.position - 1;
+                match history
+                    .checkpoints
+                    .iter()
+                    .rev()
+                    .find(|checkpoint| checkpoint.position <= target)
+                {
+                    Some(checkpoint) => {
+                        let events: Vec<(usize, Event)> = history
+                            .events
+                            .iter()
+                            .filter(|(position, _)| *position >= checkpoint.position)
+                            .copied()
+                            .collect();
+                        let checkpoint = Checkpoint {
+                            position: checkpoint.position,
+                            snapshot: checkpoint.snapshot.clone(),
+                            in_frame: checkpoint.in_frame,
+                        };
+                        (target, checkpoint, events)
+                    }
+                    None => return false,
+                }
+            }
+            _ => return false,
+        };
+        self.restore_state(&checkpoint.snapshot);
+        let tracer = self.tracer.take();
+        let watch_hits = self.take_watch_hits();
+        self.sound.set_discard_output(true);
+        if let Some(history) = &mut self.history {
+            history.position = checkpoint.position;
+            history.in_frame = checkpoint.in_frame;
+            history.replaying = true;
+        }
+        let mut events = events.into_iter().peekable();
+        loop {
+            let position = self.history_position();
+            if position >= target {
+                break;
+            }
+            if let Some((_, event)) = events.next_if(|(at, _)| *at == position) {
+                self.apply_event(event);
+            } else if self.halted {
+                break;
+            } else {
+                let cycles = self.step();
+                if self.history.as_ref().map(|history| history.in_frame) == Some(true) {
+                    self.line_cycles += cycles.max(1);
+                }
+            }
+        }
+        if let Some(history) = &mut self.history {
+            history.in_frame = false;
+            history.replaying = false;
+        }
+        self.sound.set_discard_output(false);
+        self.restore_watch_hits(watch_hits);
+        self.tracer = tracer;
+        true
+    }
+
+    fn apply_event(&mut self, event: Event) {
+        match event {
+            Event::Line(line) => self.start_line(line),
+            Event::EndFrame => self.log_event(event),
+            Event::DateTime(bytes) => self.write_datetime(bytes),
+            Event::Input(input, latch) => self.write_input(input, latch),
+        }
+    }
+
+    fn history_position(&self) -> usize {
+        self.history
+            .as_ref()
+            .map(|history| history.position)
+            .unwrap_or_default()
+    }
+
+    /// Remove all records and checkpoints, used when the VM state is replaced
+    pub(crate) fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.records.clear();
+            history.checkpoints.clear();
+            history.events.clear();
+            history.in_frame = false;
+            history.position = 0;
+        }
+    }
+
+    /// Start recording changes for the op about to be executed
+    pub(crate) fn begin_history_record(&mut self) {
+        self.update_checkpoints();
+        if let Some(history) = &mut self.history {
+            history.current = Some(OpRecord {
+                pc: self.pc,
+                arg_ptr: self.arg_ptr,
+                registers: self.registers,
+                halted: self.halted,
+                error: self.error.clone(),
+                op_executed: self.op_executed,
+                cycles_executed: self.cycles_executed,
+                line_cycles: self.line_cycles,
+                input: self.input,
+                input_latch: self.input_latch,
+                save_dirty_flag: self.save_dirty_flag,
+                timers: self.timers,
+                memory: vec![],
+                sound: None,
+            });
+        }
+    }
+
+    /// Discard checkpoints made after the current position (as a different op is about
+    /// to be executed) and take a new one if needed
+    fn update_checkpoints(&mut self) {
+        let needed = match &mut self.history {
+            Some(history) if history.checkpoint_interval > 0 && !history.replaying => {
+                let position = history.position;
+                while history
+                    .checkpoints
+                    .back()
+                    .map(|checkpoint| checkpoint.position > position)
+                    .unwrap_or_default()
+                {
+                    history.checkpoints.pop_back();
+                }
+                history.discard_future_events();
+                history
+                    .checkpoints
+                    .back()
+                    .map(|checkpoint| position - checkpoint.position >= history.checkpoint_interval)
+                    .unwrap_or(true)
+            }
+            _ => false,
+        };
+        if needed {
+            let snapshot = self.snapshot();
+            if let Some(history) = &mut self.history {
+                if history.checkpoints.len() >= history.max_checkpoints {
+                    history.checkpoints.pop_front();
+                }
+                history.checkpoints.push_back(Checkpoint {
+                    position: history.position,
+                    snapshot,
+                    in_frame: history.in_frame,
+                });
+                let oldest = history.checkpoints[0].position;
+                while history
+                    .events
+                    .front()
+                    .map(|(position, _)| *position < oldest)
+                    .unwrap_or_default()
+                {
+                    history.events.pop_front();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn end_history_record(&mut self) {
+        if let Some(history) = &mut self.history {
+            if let Some(record) = history.current.take() {
+                history.position += 1;
+                if history.max_ops == 0 {
+                    return;
+                }
+                if history.records.len() >= history.max_ops {
+                    history.records.pop_front();
+                }
+                history.records.push_back(record);
+            }
+        }
+    }
+
+    /// Store current value of memory in `start..start+len` before it's changed by an op
+    #[inline(always)]
+    pub(crate) fn log_mem(&mut self, start: usize, len: usize, write_through: bool) {
+        if let Some(record) = self
+            .history
+            .as_mut()
+            .and_then(|history| history.current.as_mut())
+        {
+            for addr in start..start + len {
+                record.memory.push(MemChange {
+                    addr: addr as u16,
+                    value: self.memory[addr],
+                    write_through,
+                });
+            }
+        }
+    }
+
+    /// Store an event so it can be repeated when replaying from a checkpoint
+    /// Must be called after [VM::begin_history_record()] for events that make a record
+    pub(crate) fn log_event(&mut self, event: Event) {
+        if let Some(history) = &mut self.history {
+            match event {
+                Event::Line(_) => history.in_frame = true,
+                Event::EndFrame => history.in_frame = false,
+                Event::DateTime(_) | Event::Input(..) => {}
+            }
+            if history.checkpoint_interval > 0 && !history.replaying {
+                history.discard_future_events();
+                history.events.push_back((history.position, event));
+            }
+        }
+    }
+
+    /// Store sound state before it's changed by an op
+    pub(crate) fn log_sound(&mut self) {
+        if let Some(record) = self
+            .history
+            .as_mut()
+            .and_then(|history| history.current.as_mut())
+        {
+            if record.sound.is_none() {
+                record.sound = Some(self.sound.snapshot());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::debugger::StopReason;
+    use crate::{address, sizes, VM};
+    use maikor_platform::op_params::INDIRECT;
+    use maikor_platform::ops;
+    use maikor_platform::registers::id;
+
+    #[test]
+    fn step_back() {
+        let mut vm = VM::new_test();
+        vm.registers[0] = 0x02;
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::INC_REG_BYTE, id::AL,
+            ops::CPY_REG_REG_BYTE, id::AX | INDIRECT, id::AL,
+            ops::PUSH_REG_WORD, id::AX,
+            ops::MEM_CPY_ADDR_ADDR_BYTE, 0x02, 0x10, 0x02, 0x00, 4,
+            ops::HALT,
+        ]);
+        vm.enable_history(10);
+        let mut states = vec![];
+        while !vm.halted {
+            states.push((vm.registers, vm.pc, vm.memory, vm.cycles_executed));
+            vm.step();
+        }
+        assert_eq!(vm.history_len(), 5);
+        assert_eq!(vm.memory[0x201], 1);
+        assert_eq!(vm.memory[0x210..0x214], [0, 1, 0, 0]);
+
+        for (registers, pc, memory, cycles) in states.iter().rev() {
+            assert!(vm.step_back());
+            assert_eq!(vm.registers, *registers);
+            assert_eq!(vm.pc, *pc);
+            assert_eq!(vm.cycles_executed, *cycles);
+            assert!(vm.memory == *memory);
+        }
+        assert!(!vm.halted);
+        assert!(!vm.step_back());
+
+        //replay produces the same result
+        while !vm.halted {
+            vm.step();
+        }
+        assert_eq!(vm.memory[0x210..0x214], [0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn limit() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::INC_REG_BYTE, id::AL, ops::JMP_ADDR, 0, 0]);
+        vm.enable_history(3);
+        for _ in 0..10 {
+            vm.step();
+        }
+        assert_eq!(vm.registers[1], 5);
+        assert_eq!(vm.history_len(), 3);
+        while vm.step_back() {}
+        assert_eq!(vm.registers[1], 4);
+        assert_eq!(vm.pc, 2);
+    }
+
+    #[test]
+    fn banks() {
+        let mut vm = VM::new_test();
+        vm.ram_banks = vec![[0; sizes::RAM_BANK], [0; sizes::RAM_BANK]];
+        vm.ram_banks[1][0] = 5;
+        vm.debug_set_mem(address::RAM_BANK_1_ID as u16, 0);
+        let ram = (address::RAM_BANK_1 as u16).to_be_bytes();
+        let ram_id = (address::RAM_BANK_1_ID as u16).to_be_bytes();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CPY_ADDR_NUM_BYTE, ram[0], ram[1], 9,
+            ops::CPY_ADDR_NUM_BYTE, ram_id[0], ram_id[1], 1,
+            ops::CPY_ADDR_NUM_BYTE, ram[0], ram[1], 7,
+        ]);
+        vm.enable_history(10);
+        vm.step();
+        vm.step();
+        vm.step();
+        assert_eq!(vm.ram_banks[0][0], 9);
+        assert_eq!(vm.ram_banks[1][0], 7);
+
+        vm.step_back();
+        assert_eq!(vm.ram_banks[1][0], 5);
+        assert_eq!(vm.memory[address::RAM_BANK_1], 5);
+        vm.step_back();
+        assert_eq!(vm.memory[address::RAM_BANK_1_ID], 0);
+        assert_eq!(vm.memory[address::RAM_BANK_1], 9);
+        assert_eq!(vm.ram_banks[0][0], 9);
+        assert_eq!(vm.ram_banks[1][0], 5);
+        vm.step_back();
+        assert_eq!(vm.memory[address::RAM_BANK_1], 0);
+        assert_eq!(vm.ram_banks[0][0], 0);
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut vm = VM::new_test();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::INC_REG_BYTE, id::AL,
+            ops::CPY_ADDR_REG_BYTE, 0x50, 0x00, id::AL,
+            ops::JMP_ADDR, 0, 0,
+        ]);
+        vm.enable_history(5);
+        vm.set_history_checkpoints(20, 10);
+        let mut states = vec![];
+        for _ in 0..100 {
+            states.push((vm.registers, vm.pc, vm.memory, vm.cycles_executed));
+            vm.step();
+        }
+        assert_eq!(vm.history_len(), 5);
+
+        for (registers, pc, memory, cycles) in states[50..].iter().rev() {
+            assert!(vm.step_back());
+            assert_eq!(vm.registers, *registers);
+            assert_eq!(vm.pc, *pc);
+            assert_eq!(vm.cycles_executed, *cycles);
+            assert!(vm.memory == *memory);
+        }
+
+        //executing different ops from an earlier point replaces the later checkpoints
+        //(the host change isn't recorded so ops before the next checkpoint can't be replayed)
+        vm.registers[1] = 0x80;
+        let mut new_states = vec![];
+        for _ in 0..30 {
+            new_states.push((vm.registers, vm.pc, vm.memory, vm.cycles_executed));
+            vm.step();
+        }
+        for (registers, pc, memory, cycles) in new_states[10..].iter().rev() {
+            assert!(vm.step_back());
+            assert_eq!(vm.registers, *registers);
+            assert_eq!(vm.pc, *pc);
+            assert_eq!(vm.cycles_executed, *cycles);
+            assert!(vm.memory == *memory);
+        }
+        while vm.step_back() {}
+        assert_eq!(vm.registers, states[0].0);
+        assert_eq!(vm.pc, 0);
+        assert!(!vm.step_back());
+    }
+
+    #[test]
+    fn checkpoint_limit() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::INC_REG_BYTE, id::AL, ops::JMP_ADDR, 0, 0]);
+        vm.enable_history(0);
+        vm.set_history_checkpoints(10, 3);
+        for _ in 0..100 {
+            vm.step();
+        }
+        while vm.step_back() {}
+        assert_eq!(vm.op_executed, 70);
+        assert_eq!(vm.registers[1], 35);
+    }
+
+    #[test]
+    fn run_backwards() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &[ops::INC_REG_BYTE, id::AL, ops::JMP_ADDR, 0, 0]);
+        vm.enable_history(100);
+        for _ in 0..10 {
+            vm.step();
+        }
+        vm.add_breakpoint(2, vec![]);
+        assert_eq!(vm.run_backwards_until(), StopReason::Breakpoint(2));
+        assert_eq!(vm.registers[1], 5);
+        assert_eq!(vm.run_backwards_until(), StopReason::Breakpoint(2));
+        assert_eq!(vm.registers[1], 4);
+        vm.clear_breakpoints();
+        assert_eq!(vm.run_backwards_until(), StopReason::HistoryStart);
+        assert_eq!(vm.registers[1], 0);
+        assert_eq!(vm.pc, 0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Controller input
+//!
+//! `INPUT` is 2 bytes, directions then actions, see [mask] for the bits
+//!
+//! A bit is set while the button is held, and a button pressed during a frame stays set until
+//! the start of the next frame even if released before then, so games polling once per frame
+//! don't miss short presses
+//!
+//! `IRQ_INPUT` is triggered whenever `INPUT` changes
+
+use crate::history::Event;
+use crate::{address, VM};
+use maikor_platform::input::mask;
+use maikor_platform::mem::interrupt_flags;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Button {
+    Up,
+    Down,
+    Left,
+    Right,
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    Start,
+}
+
+impl Button {
+    pub const ALL: [Button; 11] = [
+        Button::Up,
+        Button::Down,
+        Button::Left,
+        Button::Right,
+        Button::A,
+        Button::B,
+        Button::X,
+        Button::Y,
+        Button::L,
+        Button::R,
+        Button::Start,
+    ];
+
+    /// Byte in `INPUT` (0 or 1) and bit mask
+    pub fn mask(&self) -> (usize, u8) {
+        match self {
+            Button::Up => (0, mask::UP),
+            Button::Down => (0, mask::DOWN),
+            Button::Left => (0, mask::LEFT),
+            Button::Right => (0, mask::RIGHT),
+            Button::A => (1, mask::A),
+            Button::B => (1, mask::B),
+            Button::X => (1, mask::X),
+            Button::Y => (1, mask::Y),
+            Button::L => (1, mask::L),
+            Button::R => (1, mask::R),
+            Button::Start => (1, mask::START),
+        }
+    }
+}
+
+/// State of every button, in the same format as `INPUT`
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct InputState {
+    /// [mask::UP], [mask::DOWN], etc
+    pub direction: u8,
+    /// [mask::A], [mask::B], etc
+    pub action: u8,
+}
+
+impl InputState {
+    pub fn is_pressed(&self, button: Button) -> bool {
+        let (byte, mask) = button.mask();
+        self.to_bytes()[byte] & mask != 0
+    }
+
+    pub fn set(&mut self, button: Button, pressed: bool) {
+        let (byte, mask) = button.mask();
+        let value = if byte == 0 {
+            &mut self.direction
+        } else {
+            &mut self.action
+        };
+        if pressed {
+            *value |= mask;
+        } else {
+            *value &= !mask;
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 2] {
+        [self.direction, self.action]
+    }
+}
+
+impl VM {
+    /// Buttons currently held, as last set by the host
+    pub fn input_state(&self) -> InputState {
+        self.input
+    }
+
+    /// Set whether `button` is held, see [VM::set_input_state()]
+    pub fn set_button_state(&mut self, button: Button, pressed: bool) {
+        let mut state = self.input;
+        state.set(button, pressed);
+        self.set_input_state(state);
+    }
+
+    /// Set which buttons are held, buttons that are newly pressed are kept in `INPUT`
+    /// until the next [VM::update_input()]
+    /// Triggers `IRQ_INPUT` if `INPUT` changed
+    pub fn set_input_state(&mut self, state: InputState) {
+        let [direction, action] = state.to_bytes();
+        let [held_direction, held_action] = self.input.to_bytes();
+        let mut latch = self.input_latch;
+        latch.direction |= direction & !held_direction;
+        latch.action |= action & !held_action;
+        self.write_input(state, latch);
+    }
+
+    /// Release buttons that were pressed last frame but are no longer held
+    /// Called by [VM::run_frame()], hosts using [VM::step()] directly must call this
+    /// once per frame
+    pub fn update_input(&mut self) {
+        self.write_input(self.input, InputState::default());
+    }
+
+    /// Replace the held and latched buttons and update `INPUT`, triggering `IRQ_INPUT` if it
+    /// changed
+    /// Recorded in history as a single op
+    pub(crate) fn write_input(&mut self, input: InputState, latch: InputState) {
+        let [direction, action] = input.to_bytes();
+        let [latch_direction, latch_action] = latch.to_bytes();
+        let value = [direction | latch_direction, action | latch_action];
+        let changed = self.memory[address::INPUT..address::INPUT + value.len()] != value;
+        if !changed && input == self.input && latch == self.input_latch {
+            return;
+        }
+        self.begin_history_record();
+        self.log_event(Event::Input(input, latch));
+        self.input = input;
+        self.input_latch = latch;
+        if changed {
+            self.log_mem(address::INPUT, value.len(), false);
+            self.memory[address::INPUT..address::INPUT + value.len()].copy_from_slice(&value);
+            self.trigger_interrupt(interrupt_flags::IRQ_INPUT);
+        }
+        self.end_history_record();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::mem::address::interrupt;
+    use maikor_platform::ops;
+
+    const COUNTER: u16 = 0x5000;
+
+    fn setup() -> VM {
+        let mut vm = VM::new_test();
+        let counter = COUNTER.to_be_bytes();
+        vm.debug_set_mem_range(0, &[ops::JMP_ADDR, 0, 0]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(interrupt::IRQ_INPUT, &[
+            ops::INC_ADDR_BYTE, counter[0], counter[1],
+            ops::RETI,
+        ]);
+        vm.debug_set_mem(address::IRQ_CONTROL as u16, interrupt_flags::IRQ_INPUT);
+        vm
+    }
+
+    fn run_irq(vm: &mut VM) {
+        vm.step();
+        vm.step();
+    }
+
+    #[test]
+    fn input_state() {
+        let mut state = InputState::default();
+        state.set(Button::Left, true);
+        state.set(Button::Start, true);
+        assert_eq!(state.to_bytes(), [mask::LEFT, mask::START]);
+        assert!(state.is_pressed(Button::Left));
+        assert!(!state.is_pressed(Button::Right));
+        state.set(Button::Left, false);
+        assert_eq!(state.to_bytes(), [0, mask::START]);
+        for button in Button::ALL {
+            state.set(button, true);
+        }
+        assert_eq!(state.to_bytes(), [0x0F, 0x7F]);
+    }
+
+    #[test]
+    fn held() {
+        let mut vm = setup();
+        vm.set_button_state(Button::A, true);
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[address::INPUT..address::INPUT + 2], [0, mask::A]);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+
+        vm.set_button_state(Button::A, true);
+        vm.update_input();
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[address::INPUT + 1], mask::A);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+
+        vm.set_button_state(Button::A, false);
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[address::INPUT + 1], 0);
+        assert_eq!(vm.memory[COUNTER as usize], 2);
+    }
+
+    #[test]
+    fn pressed_this_frame() {
+        let mut vm = setup();
+        vm.set_button_state(Button::Up, true);
+        run_irq(&mut vm);
+        vm.set_button_state(Button::Up, false);
+        assert_eq!(vm.memory[address::INPUT], mask::UP);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+        assert!(!vm.input_state().is_pressed(Button::Up));
+
+        vm.run_frame();
+        assert_eq!(vm.memory[address::INPUT], 0);
+        assert_eq!(vm.memory[COUNTER as usize], 2);
+        vm.run_frame();
+        assert_eq!(vm.memory[COUNTER as usize], 2);
+    }
+
+    #[test]
+    fn restore() {
+        let mut vm = setup();
+        vm.set_button_state(Button::A, true);
+        run_irq(&mut vm);
+        let snapshot = vm.snapshot();
+        let state = vm.save_state(false);
+
+        vm.set_button_state(Button::A, false);
+        vm.set_button_state(Button::B, true);
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[COUNTER as usize], 2);
+
+        vm.restore(&snapshot);
+        assert!(vm.input_state().is_pressed(Button::A));
+        vm.update_input();
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+        assert_eq!(vm.memory[address::INPUT + 1], mask::A);
+
+        vm.set_button_state(Button::A, false);
+        vm.load_state(&state).unwrap();
+        assert!(vm.input_state().is_pressed(Button::A));
+        vm.update_input();
+        run_irq(&mut vm);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+    }
+
+    #[test]
+    fn history() {
+        let mut vm = setup();
+        vm.enable_history(10);
+        vm.step();
+        vm.set_button_state(Button::Up, true);
+        assert_eq!(vm.pc, interrupt::IRQ_INPUT);
+        assert!(vm.step_back());
+        assert_eq!(vm.pc, 0);
+        assert_eq!(vm.memory[address::INPUT], 0);
+        assert!(!vm.input_state().is_pressed(Button::Up));
+
+        vm.update_input();
+        assert_eq!(vm.memory[address::INPUT], 0);
+        assert!(vm.step_back());
+        assert!(!vm.step_back());
+    }
+}
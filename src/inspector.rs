@@ -0,0 +1,184 @@
+//! Video memory inspector
+//!
+//! Decodes the currently banked atlases, palettes and sprite table for graphics debuggers,
+//! see [renderer](crate::renderer) for the format
+//!
+//! Images are RGBA (`0xRRGGBBAA`) like the renderer, except transparent pixels are
+//! [TRANSPARENT] instead of being skipped
+
+use crate::renderer::{atlas_address, atlas_index, palette_color};
+use crate::{address, sizes, VM};
+use maikor_platform::constants::{
+    ATLAS_TILE_WIDTH, PALETTE_COUNT, SPRITE_COUNT, TILES_PER_ATLAS_ROW, TILE_HEIGHT, TILE_WIDTH,
+};
+use maikor_platform::models::{Byteable, Sprite};
+
+/// Number of atlases in memory
+pub const ATLAS_COUNT: usize = 4;
+/// Width of an atlas image in pixels
+pub const ATLAS_IMAGE_WIDTH: usize = TILES_PER_ATLAS_ROW * TILE_WIDTH;
+/// Height of an atlas image in pixels
+pub const ATLAS_IMAGE_HEIGHT: usize = sizes::ATLAS / (TILES_PER_ATLAS_ROW * ATLAS_TILE_WIDTH);
+/// Number of complete tiles in an atlas
+pub const TILES_PER_ATLAS: usize = TILES_PER_ATLAS_ROW * (ATLAS_IMAGE_HEIGHT / TILE_HEIGHT);
+/// Pixels in a tile
+pub const TILE_PIXELS: usize = TILE_WIDTH * TILE_HEIGHT;
+/// Colors in a palette
+pub const PALETTE_COLORS: usize = 16;
+/// Color used for transparent pixels
+pub const TRANSPARENT: u32 = 0;
+
+impl VM {
+    /// Bank currently loaded in `atlas`
+    ///
+    /// # Panics
+    /// If `atlas` is not less than [ATLAS_COUNT]
+    pub fn atlas_bank(&self, atlas: usize) -> u8 {
+        let addr = match atlas {
+            0 => address::ATLAS1_BANK_ID,
+            1 => address::ATLAS2_BANK_ID,
+            2 => address::ATLAS3_BANK_ID,
+            3 => address::ATLAS4_BANK_ID,
+            _ => panic!("invalid atlas {atlas}"),
+        };
+        self.memory[addr]
+    }
+
+    /// Palette color indexes of `tile` in `atlas`, row by row
+    ///
+    /// # Panics
+    /// If `atlas` is not less than [ATLAS_COUNT] or `tile` is not less than [TILES_PER_ATLAS]
+    pub fn atlas_tile(&self, atlas: usize, tile: usize) -> [u8; TILE_PIXELS] {
+        assert!(atlas < ATLAS_COUNT, "invalid atlas {atlas}");
+        assert!(tile < TILES_PER_ATLAS, "invalid tile {tile}");
+        let mut pixels = [0; TILE_PIXELS];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = atlas_index(self, atlas, tile, i % TILE_WIDTH, i / TILE_WIDTH).unwrap_or(0);
+        }
+        pixels
+    }
+
+    /// Image of `tile` in `atlas` using `palette`, row by row
+    ///
+    /// # Panics
+    /// See [VM::atlas_tile()], also if `palette` is not less than [PALETTE_COUNT]
+    pub fn atlas_tile_image(
+        &self,
+        atlas: usize,
+        tile: usize,
+        palette: usize,
+    ) -> [u32; TILE_PIXELS] {
+        assert!(palette < PALETTE_COUNT, "invalid palette {palette}");
+        self.atlas_tile(atlas, tile)
+            .map(|index| palette_color(self, palette, index as usize).unwrap_or(TRANSPARENT))
+    }
+
+    /// Image of the whole of `atlas` using `palette`,
+    /// [ATLAS_IMAGE_WIDTH] x [ATLAS_IMAGE_HEIGHT] pixels
+    ///
+    /// # Panics
+    /// If `atlas` is not less than [ATLAS_COUNT] or `palette` is not less than [PALETTE_COUNT]
+    pub fn atlas_image(&self, atlas: usize, palette: usize) -> Vec<u32> {
+        assert!(atlas < ATLAS_COUNT, "invalid atlas {atlas}");
+        assert!(palette < PALETTE_COUNT, "invalid palette {palette}");
+        let start = atlas_address(atlas);
+        self.memory[start..start + sizes::ATLAS]
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0F])
+            .map(|index| palette_color(self, palette, index as usize).unwrap_or(TRANSPARENT))
+            .collect()
+    }
+
+    /// Colors of each palette, 0,0,0 is [TRANSPARENT]
+    pub fn palettes(&self) -> [[u32; PALETTE_COLORS]; PALETTE_COUNT] {
+        let mut palettes = [[TRANSPARENT; PALETTE_COLORS]; PALETTE_COUNT];
+        for (palette, colors) in palettes.iter_mut().enumerate() {
+            for (index, color) in colors.iter_mut().enumerate() {
+                *color = palette_color(self, palette, index).unwrap_or(TRANSPARENT);
+            }
+        }
+        palettes
+    }
+
+    /// Every entry in the sprite table, including disabled sprites
+    pub fn sprites(&self) -> Vec<Sprite> {
+        (0..SPRITE_COUNT)
+            .map(|i| Sprite::from_bytes(&self.memory[address::SPRITE_TABLE + i * Sprite::SIZE..]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn atlas() {
+        let mut vm = VM::new_test();
+        vm.memory[address::PALETTES + 3..address::PALETTES + 6].copy_from_slice(&[255, 0, 0]);
+        vm.memory[address::PALETTES + 48 + 6..address::PALETTES + 48 + 9]
+            .copy_from_slice(&[0, 255, 0]);
+        //tile 1 top left pixel is 1, bottom right pixel is 2
+        vm.memory[address::ATLAS2 + 4] = 0x10;
+        vm.memory[address::ATLAS2 + 7 * 100 + 7] = 0x02;
+
+        let tile = vm.atlas_tile(1, 1);
+        assert_eq!(tile[0], 1);
+        assert_eq!(tile[63], 2);
+        assert_eq!(tile.iter().filter(|&&index| index != 0).count(), 2);
+        assert_eq!(vm.atlas_tile(0, 1), [0; TILE_PIXELS]);
+
+        let image = vm.atlas_tile_image(1, 1, 0);
+        assert_eq!(image[0], 0xFF0000FF);
+        assert_eq!(image[63], TRANSPARENT);
+        assert_eq!(vm.atlas_tile_image(1, 1, 1)[63], 0x00FF00FF);
+
+        let image = vm.atlas_image(1, 0);
+        assert_eq!(image.len(), ATLAS_IMAGE_WIDTH * ATLAS_IMAGE_HEIGHT);
+        assert_eq!(image[8], 0xFF0000FF);
+        assert_eq!(
+            image.iter().filter(|&&pixel| pixel != TRANSPARENT).count(),
+            1
+        );
+        assert_eq!(TILES_PER_ATLAS, 125);
+    }
+
+    #[test]
+    fn palettes() {
+        let mut vm = VM::new_test();
+        vm.memory[address::PALETTES + 3 * 48 + 45..address::PALETTES + 4 * 48]
+            .copy_from_slice(&[1, 2, 3]);
+        let palettes = vm.palettes();
+        assert_eq!(palettes[3][15], 0x010203FF);
+        assert_eq!(palettes[0], [TRANSPARENT; PALETTE_COLORS]);
+    }
+
+    #[test]
+    fn sprites() {
+        let mut vm = VM::new_test();
+        let sprite = Sprite {
+            x: 20,
+            y: 30,
+            id: 7,
+            flip_h: true,
+            palette: 2,
+            order: 3,
+            atlas: 1,
+            enabled: true,
+            ..Sprite::default()
+        };
+        let addr = address::SPRITE_TABLE + 4 * Sprite::SIZE;
+        vm.memory[addr..addr + Sprite::SIZE].copy_from_slice(&sprite.to_bytes());
+        let sprites = vm.sprites();
+        assert_eq!(sprites.len(), SPRITE_COUNT);
+        assert_eq!(sprites[4], sprite);
+        assert!(!sprites[0].enabled);
+    }
+
+    #[test]
+    fn atlas_bank() {
+        let mut vm = VM::new_test();
+        vm.memory[address::ATLAS3_BANK_ID] = 5;
+        assert_eq!(vm.atlas_bank(2), 5);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::{BankKind, VmError};
+use crate::timer::TIMER_COUNT;
 use crate::{address, sizes, VM};
 use maikor_platform::mem::address::is_special_memory;
 
@@ -6,11 +8,10 @@ impl VM {
     /// Get number in memory at address
     /// Returns the value and cycles used
     #[must_use]
-    pub fn read_word_mem(&self, addr: u16) -> (u16, usize) {
-        let mut value = self.memory[addr as usize] as u16;
-        value <<= 8;
-        value += self.memory[(addr + 1) as usize] as u16;
-        (value, 2)
+    pub fn read_word_mem(&mut self, addr: u16) -> (u16, usize) {
+        let (high, cost1) = self.read_byte_mem(addr);
+        let (low, cost2) = self.read_byte_mem(addr + 1);
+        (u16::from_be_bytes([high, low]), cost1 + cost2)
     }
 
     #[must_use]
@@ -24,16 +25,26 @@ impl VM {
 /// Byte memory access
 impl VM {
     /// Get number in memory at address
+    /// Sound and wave table reads are handled by [Sound::read()](crate::sound::Sound::read)
     /// Returns the value and cycles used
     #[inline(always)]
     #[must_use]
-    pub fn read_byte_mem(&self, addr: u16) -> (u8, usize) {
-        (self.memory[addr as usize], 1)
+    pub fn read_byte_mem(&mut self, addr: u16) -> (u8, usize) {
+        let addr_idx = addr as usize;
+        let value = if is_inside_sound(addr_idx) {
+            self.sound.read(addr)
+        } else {
+            self.memory[addr_idx]
+        };
+        self.watch_read(addr, value);
+        (value, 1)
     }
 
     #[must_use]
     pub fn write_byte_mem(&mut self, addr: u16, value: u8) -> usize {
         let addr_idx = addr as usize;
+        self.watch_write(addr, self.memory[addr_idx], value);
+        self.log_mem(addr_idx, 1, true);
         self.memory[addr_idx] = value;
         let bank_update_cost = self.write_mem_change_to_bank(addr_idx, value);
         let bank_load_cost = if is_special_memory(addr) {
@@ -41,12 +52,19 @@ impl VM {
         } else {
             0
         };
+        if is_inside_timer_values(addr_idx) {
+            self.timer_value_written(addr_idx - address::TIMER_VALUE1, value);
+        }
         #[allow(clippy::manual_range_contains)] //range is 2x slower
         if ((addr_idx >= address::SOUND && addr_idx <= address::SOUND + sizes::SOUND)
             || (addr_idx >= address::WAVE_TABLE
                 && addr_idx <= address::WAVE_TABLE + sizes::WAVE_TABLE))
-            && self.sound.update(addr, value)
+            && {
+                self.log_sound();
+                self.sound.update(addr, value)
+            }
         {
+            self.log_mem(address::SOUND, sizes::SOUND, false);
             self.memory[address::SOUND..address::SOUND + sizes::SOUND]
                 .as_mut()
                 .fill(0);
@@ -56,6 +74,7 @@ impl VM {
 
     #[inline]
     fn load_bank(&mut self, address: usize, size: usize, bank: *const u8) {
+        self.log_mem(address, size, false);
         unsafe {
             let dst = self.get_memory_mut(address, size).as_mut_ptr();
             std::ptr::copy_nonoverlapping(bank, dst, size);
@@ -187,7 +206,7 @@ impl VM {
     // also it might be slower, currently reading from mem is just `mem[addr]` if banks were not
     // copied across then it would become `if in code_bank, else if in ram_bank, etc, else mem[addr]`
     // need to perf test but `match` might be ok for speed here
-    fn write_mem_change_to_bank(&mut self, addr: usize, value: u8) -> usize {
+    pub(crate) fn write_mem_change_to_bank(&mut self, addr: usize, value: u8) -> usize {
         if is_inside_ram_bank_1(addr) {
             let idx = self.memory[address::RAM_BANK_1_ID] as usize;
             if idx < self.ram_banks.len() {
@@ -196,11 +215,11 @@ impl VM {
                 ram_bank[ram_bank_addr] = value;
                 return 1;
             } else {
-                self.fail(format!(
-                    "Attempted load RAM bank {}, but only {} available",
-                    idx,
-                    self.ram_banks.len()
-                ));
+                self.fail(VmError::InvalidBank {
+                    kind: BankKind::Ram,
+                    requested: idx,
+                    available: self.ram_banks.len(),
+                });
             }
         } else if is_inside_ram_bank_2(addr) {
             let idx = self.memory[address::RAM_BANK_1_ID] as usize;
@@ -210,11 +229,11 @@ impl VM {
                 ram_bank[ram_bank_addr] = value;
                 return 1;
             } else {
-                self.fail(format!(
-                    "Attempted load RAM bank {}, but only {} available",
-                    idx,
-                    self.ram_banks.len()
-                ));
+                self.fail(VmError::InvalidBank {
+                    kind: BankKind::Ram,
+                    requested: idx,
+                    available: self.ram_banks.len(),
+                });
             }
         } else if is_inside_save_bank(addr) {
             let bank_id = self.memory[address::SAVE_BANK_ID] as usize;
@@ -225,11 +244,11 @@ impl VM {
                 self.save_dirty_flag[bank_id] = true;
                 return 1;
             } else {
-                self.fail(format!(
-                    "Attempted load save bank {}, but only {} available",
-                    bank_id,
-                    self.save_banks.len()
-                ));
+                self.fail(VmError::InvalidBank {
+                    kind: BankKind::Save,
+                    requested: bank_id,
+                    available: self.save_banks.len(),
+                });
             }
         }
         0
@@ -254,6 +273,19 @@ fn is_inside_save_bank(addr: usize) -> bool {
     addr >= address::SAVE_BANK && addr < address::SAVE_BANK + sizes::SAVE_BANK
 }
 
+#[inline(always)]
+#[allow(clippy::manual_range_contains)] //range is 2x slower
+fn is_inside_timer_values(addr: usize) -> bool {
+    addr >= address::TIMER_VALUE1 && addr < address::TIMER_VALUE1 + TIMER_COUNT
+}
+
+#[inline(always)]
+#[allow(clippy::manual_range_contains)] //range is 2x slower
+fn is_inside_sound(addr: usize) -> bool {
+    (addr >= address::SOUND && addr < address::SOUND + sizes::SOUND)
+        || (addr >= address::WAVE_TABLE && addr < address::WAVE_TABLE + sizes::WAVE_TABLE)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -304,4 +336,20 @@ mod test {
         assert_eq!(value, 312);
         assert_eq!(cost, 2);
     }
+
+    #[test]
+    fn sound_mem_read() {
+        let mut vm = VM::new_test();
+        let status = (address::SOUND + 22) as u16;
+
+        vm.debug_set_mem(status, 0x80);
+        vm.debug_set_mem((address::SOUND + 7) as u16, 0xF0);
+        vm.debug_set_mem((address::SOUND + 9) as u16, 0x80);
+        vm.debug_set_mem(address::WAVE_TABLE as u16, 0x12);
+        vm.debug_set_mem((address::WAVE_TABLE + 1) as u16, 0x34);
+
+        assert_eq!(vm.memory[status as usize], 0x80);
+        assert_eq!(vm.read_byte_mem(status), (0x82, 1));
+        assert_eq!(vm.read_word_mem(address::WAVE_TABLE as u16), (0x1234, 2));
+    }
 }
@@ -7,13 +7,11 @@ pub mod reg_access;
 impl VM {
     #[inline(always)]
     pub fn get_sp(&self) -> u16 {
-        let (value, _) = self.read_word_mem(address::SP as u16);
-        value
+        u16::from_be_bytes([self.memory[address::SP], self.memory[address::SP + 1]])
     }
 
     #[inline(always)]
     pub fn get_fp(&self) -> u16 {
-        let (value, _) = self.read_word_mem(address::FP as u16);
-        value
+        u16::from_be_bytes([self.memory[address::FP], self.memory[address::FP + 1]])
     }
 }
@@ -1,7 +1,15 @@
+use crate::datetime::{Clock, DateTimeInterrupt, SystemClock};
+use crate::debugger::Debugger;
+use crate::decoder::{decode, Operand, MAX_OPERANDS};
+use crate::error::VmError;
+use crate::history::History;
+use crate::input::InputState;
 use crate::mem::{address, sizes};
 use crate::register::offset;
 use crate::sizes::MAIN_CODE;
 use crate::sound::Sound;
+use crate::timer::Timers;
+use crate::trace::Tracer;
 use maikor_platform::constants::SAVE_COUNT;
 use maikor_platform::input::controller_type;
 use maikor_platform::mem::address::interrupt;
@@ -10,11 +18,28 @@ use maikor_platform::registers;
 use maikor_platform::registers::flags::INTERRUPTS;
 use maikor_vm_file::GameFile;
 
+pub mod datetime;
+pub mod debugger;
+pub mod decoder;
+pub mod disassembler;
+pub mod error;
+pub mod frame;
+#[cfg(feature = "test-support")]
+pub mod headless;
+mod history;
+pub mod input;
+pub mod inspector;
 mod internals;
 mod mem;
 mod ops;
+pub mod recording;
 mod register;
-mod sound;
+pub mod renderer;
+pub mod save_state;
+pub mod snapshot;
+pub mod sound;
+pub mod timer;
+pub mod trace;
 
 pub struct VM {
     /// Order is AH, AL, BH, BL, CH, CL, DH, DL, FLG
@@ -35,17 +60,39 @@ pub struct VM {
     /// `memory[SAVE_CONTROL]` should set to 0)
     pub save_dirty_flag: [bool; SAVE_COUNT],
     pub atlas_banks: Vec<[u8; sizes::ATLAS]>,
-    /// error message from EHALT
-    pub error: Option<String>,
+    /// why the VM halted, None if halted by HALT
+    pub error: Option<VmError>,
     /// if true the VM has stopped (EoF or error) and can't continue
     pub halted: bool,
     /// Count of operations executed this session
     pub op_executed: usize,
     /// Count of cycles executed this session
     pub cycles_executed: usize,
-    /// index in memory where arguments are being read from
+    /// cycles already used in the current line by [VM::run_frame()]
+    line_cycles: usize,
+    /// if true RET also fails if the restored FP isn't 0 or inside the stack
+    pub strict_stack: bool,
+    /// index in memory of the op after the one being executed
     arg_ptr: u16,
+    /// operands of op being executed
+    args: [Operand; MAX_OPERANDS],
+    /// index of next operand to read from args
+    arg_idx: usize,
     pub sound: Sound,
+    timers: Timers,
+    clock: Box<dyn Clock>,
+    datetime_interrupt: DateTimeInterrupt,
+    /// buttons held, as set by the host
+    input: InputState,
+    /// buttons pressed since the last [VM::update_input()]
+    input_latch: InputState,
+    /// ID of loaded game, used to validate save states
+    game_id: String,
+    /// Hash of loaded game, used to validate save states
+    game_hash: u32,
+    debugger: Debugger,
+    tracer: Option<Tracer>,
+    history: Option<History>,
 }
 
 impl VM {
@@ -58,6 +105,9 @@ impl VM {
         let stack = (address::STACK as u16).to_be_bytes();
         memory[address::SP] = stack[0];
         memory[address::SP + 1] = stack[1];
+        let mut clock = SystemClock;
+        let datetime = clock.now().to_bytes();
+        memory[address::DATETIME..address::DATETIME + datetime.len()].copy_from_slice(&datetime);
         Self {
             registers,
             pc: 0,
@@ -72,8 +122,22 @@ impl VM {
             halted: false,
             op_executed: 0,
             cycles_executed: 0,
+            line_cycles: 0,
+            strict_stack: false,
             arg_ptr: 0,
+            args: [Operand::Byte(0); MAX_OPERANDS],
+            arg_idx: 0,
             sound: Sound::new(player),
+            timers: Timers::default(),
+            clock: Box::new(clock),
+            datetime_interrupt: DateTimeInterrupt::default(),
+            input: InputState::default(),
+            input_latch: InputState::default(),
+            game_id: String::new(),
+            game_hash: 0,
+            debugger: Debugger::default(),
+            tracer: None,
+            history: None,
         }
     }
 
@@ -96,8 +160,8 @@ impl VM {
 }
 
 impl VM {
-    fn fail(&mut self, error_message: String) {
-        self.error = Some(format!("{}\n{}", error_message, self.dump()));
+    fn fail(&mut self, error: VmError) {
+        self.error = Some(error);
         self.halted = true;
     }
 }
@@ -110,8 +174,12 @@ impl VM {
         &mut self,
         mut game: GameFile,
         saves: &[[u8; sizes::SAVE_BANK]],
-    ) -> Result<(), String> {
-        game.header.validate()?;
+    ) -> Result<(), VmError> {
+        game.header.validate().map_err(VmError::GameValidation)?;
+        let mut code: Vec<&[u8]> = vec![&game.main_code];
+        code.extend(game.code_banks.iter().map(|bank| bank.as_slice()));
+        self.game_hash = save_state::game_hash(&game.header.id, game.header.build, &code);
+        self.game_id = game.header.id.clone();
         unsafe {
             let dst = self.get_memory_mut(0, MAIN_CODE).as_mut_ptr();
             let src = game.main_code.as_mut_ptr();
@@ -248,22 +316,42 @@ impl VM {
         if self.halted {
             return 0;
         }
-        let op_byte = self.memory[self.pc as usize];
-        self.arg_ptr = self.pc + 1;
-        match self.execute(op_byte) {
+        self.begin_history_record();
+        let instruction = match self.decode(self.pc) {
+            Ok(instruction) => instruction,
+            Err(err) => {
+                self.fail(VmError::from_decode(err, self.pc));
+                self.end_history_record();
+                return 0;
+            }
+        };
+        self.arg_ptr = self.pc.wrapping_add(instruction.len as u16);
+        let trace_entry = self.begin_trace_entry(&instruction);
+        self.debugger.current_op = Some(instruction.op());
+        let result = self.execute(&instruction);
+        self.debugger.current_op = None;
+        let cycles = match result {
             Ok((jumped, cycles)) => {
                 if !jumped {
-                    //arg_ptr is advanced as operands are read
-                    //and should be at byte of the next op when this one has completed
+                    //arg_ptr is the byte after this op's operands
                     self.pc = self.arg_ptr;
                 }
                 self.cycles_executed += cycles;
                 self.op_executed += 1;
-                return cycles;
+                self.sound.do_vm_cycles(cycles);
+                self.advance_timers(cycles);
+                cycles
+            }
+            Err(err) => {
+                self.fail(err);
+                0
             }
-            Err(msg) => self.fail(msg),
+        };
+        self.end_history_record();
+        if let Some(entry) = trace_entry {
+            self.end_trace_entry(entry, cycles);
         }
-        0
+        cycles
     }
 
     /// Attempt to trigger the interrupt that matches `interrupt_id`
@@ -280,12 +368,12 @@ impl VM {
                 interrupt_flags::IRQ_SCREEN_DRAW => interrupt::IRQ_SCREEN_DRAW,
                 interrupt_flags::IRQ_TIMER => interrupt::IRQ_TIMER,
                 _ => {
-                    self.fail(format!(
-                        "Attempted to trigger invalid interrupt id: {interrupt_id}"
-                    ));
+                    self.fail(VmError::InvalidInterrupt(interrupt_id));
                     return;
                 }
             };
+            self.log_mem(address::IRQ_REG_DUMP, 9, false);
+            self.log_mem(address::IRQ_REG_ADDR, 2, false);
             unsafe {
                 let dst = self.get_memory_mut(address::IRQ_REG_DUMP, 9).as_mut_ptr();
                 let src = self.registers.as_mut_ptr();
@@ -312,25 +400,45 @@ impl VM {
     }
 
     /// Run arbitrary op, does not advance PC automatically (JMP, etc ops still work)
-    /// This works by writing the bytes to a section of reserved and setting the PC to there
+    /// VM will halt if the bytes aren't a valid op
     pub fn execute_op(&mut self, bytes: &[u8]) {
         if bytes.is_empty() {
             panic!("Must have at least one byte");
         }
-        for (i, b) in bytes.iter().enumerate() {
-            self.memory[address::RESERVED + i] = *b;
-        }
-        self.arg_ptr = (address::RESERVED + 1) as u16;
-        let result = self.execute(bytes[0]);
-        if let Err(msg) = result {
-            self.fail(msg);
+        let instruction = match decode(bytes) {
+            Ok(instruction) => instruction,
+            Err(err) => {
+                self.fail(VmError::from_decode(err, self.pc));
+                return;
+            }
+        };
+        self.debugger.current_op = Some(instruction.op());
+        let result = self.execute(&instruction);
+        self.debugger.current_op = None;
+        if let Err(err) = result {
+            self.fail(err);
         }
     }
 
+    /// Error (if any) followed by [VM::dump()] and [VM::call_stack()]
+    pub fn error_report(&self) -> Option<String> {
+        self.error.as_ref().map(|error| {
+            let mut report = format!("{}\n{}\nCall stack:", error, self.dump());
+            let call_stack = self.call_stack();
+            if call_stack.is_empty() {
+                report.push_str(" empty");
+            }
+            for frame in call_stack {
+                report.push_str(&format!("\n  {frame}"));
+            }
+            report
+        })
+    }
+
     /// Writes registers to String
     pub fn dump(&self) -> String {
         format!(
-            "{}\n{}\n{}\n{}\n{}",
+            "{}\n{}\n{}\n{}\n{}\n{}",
             format_args!(
                 "AH: {:02X}  AL: {:02X}  BH: {:02X}  BL: {:02X}",
                 self.registers[0], self.registers[1], self.registers[2], self.registers[3]
@@ -362,7 +470,14 @@ impl VM {
                     .map(|num| format!("{:02X}", num))
                     .collect::<Vec<String>>()
                     .join(" ")
-            })
+            }),
+            format_args!(
+                "Op: {}",
+                self.disassemble(self.pc, 1)
+                    .first()
+                    .map(|(_, instruction)| instruction.to_string())
+                    .unwrap_or_else(|| String::from("??"))
+            )
         )
     }
 }
@@ -19,6 +19,7 @@ pub mod address {
     pub const RAM_BANK_1: usize = address::RAM_BANK_1 as usize;
     pub const CODE_BANK_2: usize = address::CODE_BANK_2 as usize;
     pub const RAM_BANK_2: usize = address::RAM_BANK_2 as usize;
+    pub const INPUT: usize = address::INPUT as usize;
     pub const SOUND: usize = address::SOUND as usize;
     pub const WAVE_TABLE: usize = address::WAVE_TABLE as usize;
     pub const SAVE_BANK_ID: usize = address::SAVE_BANK_ID as usize;
@@ -27,6 +28,10 @@ pub mod address {
     pub const ATLAS2: usize = address::ATLAS2 as usize;
     pub const ATLAS3: usize = address::ATLAS3 as usize;
     pub const ATLAS4: usize = address::ATLAS4 as usize;
+    pub const PALETTES: usize = address::PALETTES as usize;
+    pub const SPRITE_TABLE: usize = address::SPRITE_TABLE as usize;
+    pub const LAYER_HEADERS: usize = address::LAYER_HEADERS as usize;
+    pub const LAYERS: usize = address::LAYERS as usize;
     pub const CODE_BANK_1_ID: usize = address::CODE_BANK_1_ID as usize;
     pub const RAM_BANK_1_ID: usize = address::RAM_BANK_1_ID as usize;
     pub const CODE_BANK_2_ID: usize = address::CODE_BANK_2_ID as usize;
@@ -42,6 +47,9 @@ pub mod address {
     pub const IRQ_REG_ADDR: usize = address::IRQ_RET_ADDR as usize;
     pub const SP: usize = address::SP as usize;
     pub const FP: usize = address::FP as usize;
-    pub const RESERVED: usize = address::RESERVED as usize;
     pub const STACK: usize = address::STACK as usize;
+    pub const VLINE: usize = address::VLINE as usize;
+    pub const TIMER_CONTROL: usize = address::TIMER_CONTROL as usize;
+    pub const TIMER_VALUE1: usize = address::TIMER_VALUE1 as usize;
+    pub const DATETIME: usize = address::DATETIME as usize;
 }
@@ -82,8 +82,9 @@ impl VM {
 mod test {
     use crate::ops::bitlogic::{ByteMethod, WordMethod};
     use crate::ops::test::check_cycles;
+    use crate::ops::test::vm_with_args;
     use crate::VM;
-    use maikor_platform::mem::address::RESERVED;
+    use maikor_platform::ops;
     use std::ops::{BitAnd, BitOr, BitXor};
 
     pub fn bitwise_check_cycles_byte(
@@ -92,11 +93,7 @@ mod test {
         method: fn(&mut VM, ByteMethod) -> usize,
         bmethod: ByteMethod,
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        let mut vm = vm_with_args(bytes);
         assert_eq!(method(&mut vm, bmethod), expected_cycles)
     }
 
@@ -106,31 +103,87 @@ mod test {
         method: fn(&mut VM, WordMethod) -> usize,
         bmethod: WordMethod,
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        let mut vm = vm_with_args(bytes);
         assert_eq!(method(&mut vm, bmethod), expected_cycles)
     }
 
     #[test]
     fn test_costs() {
-        check_cycles(&[0, 0], 2, VM::not_reg_byte);
-        check_cycles(&[0, 0], 4, VM::not_reg_word);
+        check_cycles(&[ops::NOT_REG_BYTE, 0, 0], 2, VM::not_reg_byte);
+        check_cycles(&[ops::NOT_REG_WORD, 0, 0], 4, VM::not_reg_word);
 
-        bitwise_check_cycles_byte(&[0, 0], 3, VM::bl_reg_reg_byte, u8::bitand);
-        bitwise_check_cycles_byte(&[0, 0], 3, VM::bl_reg_reg_byte, u8::bitor);
-        bitwise_check_cycles_byte(&[0, 0], 3, VM::bl_reg_reg_byte, u8::bitxor);
-        bitwise_check_cycles_word(&[0, 0], 6, VM::bl_reg_reg_word, u16::bitand);
-        bitwise_check_cycles_word(&[0, 0], 6, VM::bl_reg_reg_word, u16::bitor);
-        bitwise_check_cycles_word(&[0, 0], 6, VM::bl_reg_reg_word, u16::bitxor);
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_REG_BYTE, 0, 0],
+            3,
+            VM::bl_reg_reg_byte,
+            u8::bitand,
+        );
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_REG_BYTE, 0, 0],
+            3,
+            VM::bl_reg_reg_byte,
+            u8::bitor,
+        );
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_REG_BYTE, 0, 0],
+            3,
+            VM::bl_reg_reg_byte,
+            u8::bitxor,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_REG_WORD, 0, 0],
+            6,
+            VM::bl_reg_reg_word,
+            u16::bitand,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_REG_WORD, 0, 0],
+            6,
+            VM::bl_reg_reg_word,
+            u16::bitor,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_REG_WORD, 0, 0],
+            6,
+            VM::bl_reg_reg_word,
+            u16::bitxor,
+        );
 
-        bitwise_check_cycles_byte(&[0, 0], 2, VM::bl_reg_num_byte, u8::bitand);
-        bitwise_check_cycles_byte(&[0, 0], 2, VM::bl_reg_num_byte, u8::bitor);
-        bitwise_check_cycles_byte(&[0, 0], 2, VM::bl_reg_num_byte, u8::bitxor);
-        bitwise_check_cycles_word(&[0, 0], 4, VM::bl_reg_num_word, u16::bitand);
-        bitwise_check_cycles_word(&[0, 0], 4, VM::bl_reg_num_word, u16::bitor);
-        bitwise_check_cycles_word(&[0, 0], 4, VM::bl_reg_num_word, u16::bitxor);
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_NUM_BYTE, 0, 0],
+            2,
+            VM::bl_reg_num_byte,
+            u8::bitand,
+        );
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_NUM_BYTE, 0, 0],
+            2,
+            VM::bl_reg_num_byte,
+            u8::bitor,
+        );
+        bitwise_check_cycles_byte(
+            &[ops::OR_REG_NUM_BYTE, 0, 0],
+            2,
+            VM::bl_reg_num_byte,
+            u8::bitxor,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_NUM_WORD, 0, 0],
+            4,
+            VM::bl_reg_num_word,
+            u16::bitand,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_NUM_WORD, 0, 0],
+            4,
+            VM::bl_reg_num_word,
+            u16::bitor,
+        );
+        bitwise_check_cycles_word(
+            &[ops::OR_REG_NUM_WORD, 0, 0],
+            4,
+            VM::bl_reg_num_word,
+            u16::bitxor,
+        );
     }
 }
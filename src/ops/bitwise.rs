@@ -479,55 +479,56 @@ fn asr_word(value: u16, amount: u32) -> u16 {
 mod test {
     use crate::ops::test::check_cycles;
     use crate::VM;
+    use maikor_platform::ops;
 
     #[test]
     fn test_costs() {
-        check_cycles(&[0, 0], 2, VM::asl_addr_byte);
-        check_cycles(&[0, 0], 2, VM::asr_addr_byte);
-        check_cycles(&[0, 0], 2, VM::lsr_addr_byte);
-        check_cycles(&[0, 0], 2, VM::rol_addr_byte);
-        check_cycles(&[0, 0], 2, VM::ror_addr_byte);
-        check_cycles(&[0, 0], 5, VM::rcr_addr_byte);
-        check_cycles(&[0, 0], 5, VM::rcl_addr_byte);
-
-        check_cycles(&[0, 0], 4, VM::asl_addr_word);
-        check_cycles(&[0, 0], 4, VM::asr_addr_word);
-        check_cycles(&[0, 0], 4, VM::lsr_addr_word);
-        check_cycles(&[0, 0], 4, VM::rol_addr_word);
-        check_cycles(&[0, 0], 4, VM::ror_addr_word);
-        check_cycles(&[0, 0], 7, VM::rcr_addr_word);
-        check_cycles(&[0, 0], 7, VM::rcl_addr_word);
-
-        check_cycles(&[0, 0], 2, VM::asl_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::asr_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::lsr_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::rol_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::ror_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::rcl_reg_num_byte);
-        check_cycles(&[0, 0], 2, VM::rcr_reg_num_byte);
-
-        check_cycles(&[0, 0], 4, VM::asl_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::asr_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::lsr_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::rol_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::ror_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::rcl_reg_num_word);
-        check_cycles(&[0, 0], 4, VM::rcr_reg_num_word);
-
-        check_cycles(&[0, 0], 3, VM::asl_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::asr_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::lsr_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::rol_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::ror_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::rcl_reg_reg_byte);
-        check_cycles(&[0, 0], 3, VM::rcr_reg_reg_byte);
-
-        check_cycles(&[0, 0], 6, VM::asl_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::asr_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::lsr_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::rol_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::ror_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::rcl_reg_reg_word);
-        check_cycles(&[0, 0], 6, VM::rcr_reg_reg_word);
+        check_cycles(&[ops::ASL_ADDR_BYTE, 0, 0], 2, VM::asl_addr_byte);
+        check_cycles(&[ops::ASR_ADDR_BYTE, 0, 0], 2, VM::asr_addr_byte);
+        check_cycles(&[ops::LSR_ADDR_BYTE, 0, 0], 2, VM::lsr_addr_byte);
+        check_cycles(&[ops::ROL_ADDR_BYTE, 0, 0], 2, VM::rol_addr_byte);
+        check_cycles(&[ops::ROR_ADDR_BYTE, 0, 0], 2, VM::ror_addr_byte);
+        check_cycles(&[ops::RCR_ADDR_BYTE, 0, 0], 5, VM::rcr_addr_byte);
+        check_cycles(&[ops::RCL_ADDR_BYTE, 0, 0], 5, VM::rcl_addr_byte);
+
+        check_cycles(&[ops::ASL_ADDR_WORD, 0, 0], 4, VM::asl_addr_word);
+        check_cycles(&[ops::ASR_ADDR_WORD, 0, 0], 4, VM::asr_addr_word);
+        check_cycles(&[ops::LSR_ADDR_WORD, 0, 0], 4, VM::lsr_addr_word);
+        check_cycles(&[ops::ROL_ADDR_WORD, 0, 0], 4, VM::rol_addr_word);
+        check_cycles(&[ops::ROR_ADDR_WORD, 0, 0], 4, VM::ror_addr_word);
+        check_cycles(&[ops::RCR_ADDR_WORD, 0, 0], 7, VM::rcr_addr_word);
+        check_cycles(&[ops::RCL_ADDR_WORD, 0, 0], 7, VM::rcl_addr_word);
+
+        check_cycles(&[ops::ASL_REG_NUM_BYTE, 0, 0], 2, VM::asl_reg_num_byte);
+        check_cycles(&[ops::ASR_REG_NUM_BYTE, 0, 0], 2, VM::asr_reg_num_byte);
+        check_cycles(&[ops::LSR_REG_NUM_BYTE, 0, 0], 2, VM::lsr_reg_num_byte);
+        check_cycles(&[ops::ROL_REG_NUM_BYTE, 0, 0], 2, VM::rol_reg_num_byte);
+        check_cycles(&[ops::ROR_REG_NUM_BYTE, 0, 0], 2, VM::ror_reg_num_byte);
+        check_cycles(&[ops::RCL_REG_NUM_BYTE, 0, 0], 2, VM::rcl_reg_num_byte);
+        check_cycles(&[ops::RCR_REG_NUM_BYTE, 0, 0], 2, VM::rcr_reg_num_byte);
+
+        check_cycles(&[ops::ASL_REG_NUM_WORD, 0, 0], 4, VM::asl_reg_num_word);
+        check_cycles(&[ops::ASR_REG_NUM_WORD, 0, 0], 4, VM::asr_reg_num_word);
+        check_cycles(&[ops::LSR_REG_NUM_WORD, 0, 0], 4, VM::lsr_reg_num_word);
+        check_cycles(&[ops::ROL_REG_NUM_WORD, 0, 0], 4, VM::rol_reg_num_word);
+        check_cycles(&[ops::ROR_REG_NUM_WORD, 0, 0], 4, VM::ror_reg_num_word);
+        check_cycles(&[ops::RCL_REG_NUM_WORD, 0, 0], 4, VM::rcl_reg_num_word);
+        check_cycles(&[ops::RCR_REG_NUM_WORD, 0, 0], 4, VM::rcr_reg_num_word);
+
+        check_cycles(&[ops::ASL_REG_REG_BYTE, 0, 0], 3, VM::asl_reg_reg_byte);
+        check_cycles(&[ops::ASR_REG_REG_BYTE, 0, 0], 3, VM::asr_reg_reg_byte);
+        check_cycles(&[ops::LSR_REG_REG_BYTE, 0, 0], 3, VM::lsr_reg_reg_byte);
+        check_cycles(&[ops::ROL_REG_REG_BYTE, 0, 0], 3, VM::rol_reg_reg_byte);
+        check_cycles(&[ops::ROR_REG_REG_BYTE, 0, 0], 3, VM::ror_reg_reg_byte);
+        check_cycles(&[ops::RCL_REG_REG_BYTE, 0, 0], 3, VM::rcl_reg_reg_byte);
+        check_cycles(&[ops::RCR_REG_REG_BYTE, 0, 0], 3, VM::rcr_reg_reg_byte);
+
+        check_cycles(&[ops::ASL_REG_REG_WORD, 0, 0], 6, VM::asl_reg_reg_word);
+        check_cycles(&[ops::ASR_REG_REG_WORD, 0, 0], 6, VM::asr_reg_reg_word);
+        check_cycles(&[ops::LSR_REG_REG_WORD, 0, 0], 6, VM::lsr_reg_reg_word);
+        check_cycles(&[ops::ROL_REG_REG_WORD, 0, 0], 6, VM::rol_reg_reg_word);
+        check_cycles(&[ops::ROR_REG_REG_WORD, 0, 0], 6, VM::ror_reg_reg_word);
+        check_cycles(&[ops::RCL_REG_REG_WORD, 0, 0], 6, VM::rcl_reg_reg_word);
+        check_cycles(&[ops::RCR_REG_REG_WORD, 0, 0], 6, VM::rcr_reg_reg_word);
     }
 }
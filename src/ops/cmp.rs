@@ -23,13 +23,22 @@ impl VM {
 #[cfg(test)]
 mod test {
     use super::*;
-    use maikor_platform::mem::address::RESERVED;
+    use crate::ops::test::vm_with_args;
+    use maikor_platform::ops;
     use maikor_platform::registers::id;
 
     #[test]
     fn test_costs() {
-        check_jmp_cycles(&[id::AL as u8, 15], 1, VM::cmp_reg_num_byte);
-        check_jmp_cycles(&[id::AL as u8, 15], 2, VM::cmp_reg_num_word);
+        check_jmp_cycles(
+            &[ops::CMP_REG_NUM_BYTE, id::AL as u8, 15],
+            1,
+            VM::cmp_reg_num_byte,
+        );
+        check_jmp_cycles(
+            &[ops::CMP_REG_NUM_WORD, id::AL as u8, 15],
+            2,
+            VM::cmp_reg_num_word,
+        );
     }
 
     pub fn check_jmp_cycles(
@@ -37,12 +46,7 @@ mod test {
         expected_cycles: usize,
         method: fn(&mut VM, bool) -> usize,
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
-        assert_eq!(method(&mut vm, true), expected_cycles);
-        assert_eq!(method(&mut vm, false), expected_cycles);
+        assert_eq!(method(&mut vm_with_args(bytes), true), expected_cycles);
+        assert_eq!(method(&mut vm_with_args(bytes), false), expected_cycles);
     }
 }
@@ -107,35 +107,90 @@ mod test {
     use super::*;
     use crate::ops::test::check_cycles;
     use maikor_platform::op_params::*;
+    use maikor_platform::ops;
     use maikor_platform::registers::id;
 
     #[test]
     fn test_costs() {
-        check_cycles(&[id::AL as u8, 10], 1, VM::cpy_reg_num_byte);
-        check_cycles(&[id::AL as u8, id::AH as u8], 2, VM::cpy_reg_reg_byte);
-        check_cycles(&[id::AL as u8, 0, 0], 2, VM::cpy_reg_addr_byte);
-        check_cycles(&[0, 0, 10], 1, VM::cpy_addr_num_byte);
-        check_cycles(&[3, 4, id::CH as u8], 2, VM::cpy_addr_reg_byte);
-        check_cycles(&[1, 2, 3, 4], 2, VM::cpy_addr_addr_byte);
-        check_cycles(&[id::AX as u8, 10], 2, VM::cpy_reg_num_word);
-        check_cycles(&[id::AL as u8, id::BX as u8], 4, VM::cpy_reg_reg_word);
-        check_cycles(&[id::AX as u8, 10, 1], 4, VM::cpy_reg_addr_word);
-        check_cycles(&[9, 9, 10], 2, VM::cpy_addr_num_word);
-        check_cycles(&[1, 2, id::CX as u8], 4, VM::cpy_addr_reg_word);
-        check_cycles(&[6, 7, 8, 9], 4, VM::cpy_addr_addr_word);
+        check_cycles(
+            &[ops::CPY_REG_NUM_BYTE, id::AL as u8, 10],
+            1,
+            VM::cpy_reg_num_byte,
+        );
+        check_cycles(
+            &[ops::CPY_REG_REG_BYTE, id::AL as u8, id::AH as u8],
+            2,
+            VM::cpy_reg_reg_byte,
+        );
+        check_cycles(
+            &[ops::CPY_REG_ADDR_BYTE, id::AL as u8, 0, 0],
+            2,
+            VM::cpy_reg_addr_byte,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_NUM_BYTE, 0, 0, 10],
+            1,
+            VM::cpy_addr_num_byte,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_REG_BYTE, 3, 4, id::CH as u8],
+            2,
+            VM::cpy_addr_reg_byte,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_ADDR_BYTE, 1, 2, 3, 4],
+            2,
+            VM::cpy_addr_addr_byte,
+        );
+        check_cycles(
+            &[ops::CPY_REG_NUM_WORD, id::AX as u8, 10],
+            2,
+            VM::cpy_reg_num_word,
+        );
+        check_cycles(
+            &[ops::CPY_REG_REG_WORD, id::AL as u8, id::BX as u8],
+            4,
+            VM::cpy_reg_reg_word,
+        );
+        check_cycles(
+            &[ops::CPY_REG_ADDR_WORD, id::AX as u8, 10, 1],
+            4,
+            VM::cpy_reg_addr_word,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_NUM_WORD, 9, 9, 10],
+            2,
+            VM::cpy_addr_num_word,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_REG_WORD, 1, 2, id::CX as u8],
+            4,
+            VM::cpy_addr_reg_word,
+        );
+        check_cycles(
+            &[ops::CPY_ADDR_ADDR_WORD, 6, 7, 8, 9],
+            4,
+            VM::cpy_addr_addr_word,
+        );
 
         check_cycles(
-            &[id::CX as u8 | INDIRECT, id::AH as u8],
+            &[ops::CPY_REG_REG_BYTE, id::CX as u8 | INDIRECT, id::AH as u8],
             4,
             VM::cpy_reg_reg_byte,
         );
         check_cycles(
-            &[id::AL as u8 | IND_OFFSET_REG, 0, 0, 0],
+            &[
+                ops::CPY_REG_ADDR_BYTE,
+                id::AL as u8 | IND_OFFSET_REG,
+                0,
+                0,
+                0,
+            ],
             5,
             VM::cpy_reg_addr_byte,
         );
         check_cycles(
-            &[id::AX as u8 | IND_PRE_DEC, 10, 1],
+            &[ops::CPY_REG_ADDR_WORD, id::AX as u8 | IND_PRE_DEC, 10, 1],
             8,
             VM::cpy_reg_addr_word,
         );
@@ -1,258 +1,288 @@
+use crate::decoder::{Instruction, Opcode};
+use crate::error::VmError;
 use crate::ops::mathc::{u16_carrying_add, u16_carrying_sub, u8_carrying_add, u8_carrying_sub};
 use crate::ops::maths::{u16_divs, u16_muls, u8_divs, u8_muls};
 use crate::VM;
-use maikor_platform::ops;
 use std::ops::{BitAnd, BitOr, BitXor};
 
 #[rustfmt::skip]
 impl VM {
-    /// Execute op with params
+    /// Execute decoded op, see [VM::decode()]
     /// Returns true if op has adjusted PC (and so VM shouldn't automatically advance)
     ///     and cycles
-    pub fn execute(&mut self, op: u8) -> Result<(bool, usize), String> {
-        Ok(match op {
-            ops::NOP => (false, 3),
-            ops::HALT => {
+    pub fn execute(&mut self, instruction: &Instruction) -> Result<(bool, usize), VmError> {
+        self.load_args(instruction);
+        Ok(match instruction.opcode {
+            Opcode::Nop => (false, 3),
+            Opcode::Halt => {
                 self.halted = true;
                 (true, 0)
             }
-            ops::EHALT => {
+            Opcode::Ehalt => {
                 self.halted = true;
-                self.error = Some(String::from("Halted by program"));
+                self.error = Some(VmError::HaltedByProgram { pc: self.pc });
                 (true, 0)
             }
-            ops::RET => (true, self.ret()),
-            ops::RETI => {
+            Opcode::Ret => (true, self.ret()),
+            Opcode::Reti => {
                 self.return_from_interrupt();
                 (true, 4)
             }
-            ops::CALL_ADDR => {
+            Opcode::CallAddr => {
                 (true, self.call_addr())
             }
-            ops::CALL_REG => {
+            Opcode::CallReg => {
                 (true, self.call_reg())
             }
-            ops::INC_REG_BYTE => (false, self.inc_reg_byte()),
-            ops::DEC_REG_BYTE => (false, self.dec_reg_byte()),
-            ops::INC_REG_WORD => (false, self.inc_reg_word()),
-            ops::DEC_REG_WORD => (false, self.dec_reg_word()),
-            ops::INC_ADDR_BYTE => (false, self.inc_addr_byte()),
-            ops::DEC_ADDR_BYTE => (false, self.dec_addr_byte()),
-            ops::INC_ADDR_WORD => (false, self.inc_addr_word()),
-            ops::DEC_ADDR_WORD => (false, self.dec_addr_word()),
-            ops::SWAP_REG_REG_BYTE => (false, self.swap_byte()),
-            ops::SWAP_REG_REG_WORD => (false, self.swap_word()),
-            ops::ADD_REG_NUM_BYTE => (false, self.math_reg_num_byte(u8::overflowing_add)),
-            ops::ADD_REG_REG_BYTE => (false, self.math_reg_reg_byte(u8::overflowing_add)),
-            ops::ADD_REG_ADDR_BYTE => (false, self.math_reg_addr_byte(u8::overflowing_add)),
-            ops::ADD_REG_NUM_WORD => (false, self.math_reg_num_word(u16::overflowing_add)),
-            ops::ADD_REG_REG_WORD => (false, self.math_reg_reg_word(u16::overflowing_add)),
-            ops::ADD_REG_ADDR_WORD => (false, self.math_reg_addr_word(u16::overflowing_add)),
-            ops::ADD_ADDR_NUM_BYTE => (false, self.math_addr_num_byte(u8::overflowing_add)),
-            ops::ADD_ADDR_REG_BYTE => (false, self.math_addr_reg_byte(u8::overflowing_add)),
-            ops::ADD_ADDR_ADDR_BYTE => (false, self.math_addr_addr_byte(u8::overflowing_add)),
-            ops::ADD_ADDR_NUM_WORD => (false, self.math_addr_num_word(u16::overflowing_add)),
-            ops::ADD_ADDR_REG_WORD => (false, self.math_addr_reg_word(u16::overflowing_add)),
-            ops::ADD_ADDR_ADDR_WORD => (false, self.math_addr_addr_word(u16::overflowing_add)),
-            ops::SUB_REG_NUM_BYTE => (false, self.math_reg_num_byte(u8::overflowing_sub)),
-            ops::SUB_REG_REG_BYTE => (false, self.math_reg_reg_byte(u8::overflowing_sub)),
-            ops::SUB_REG_ADDR_BYTE => (false, self.math_reg_addr_byte(u8::overflowing_sub)),
-            ops::SUB_REG_NUM_WORD => (false, self.math_reg_num_word(u16::overflowing_sub)),
-            ops::SUB_REG_REG_WORD => (false, self.math_reg_reg_word(u16::overflowing_sub)),
-            ops::SUB_REG_ADDR_WORD => (false, self.math_reg_addr_word(u16::overflowing_sub)),
-            ops::SUB_ADDR_NUM_BYTE => (false, self.math_addr_num_byte(u8::overflowing_sub)),
-            ops::SUB_ADDR_REG_BYTE => (false, self.math_addr_reg_byte(u8::overflowing_sub)),
-            ops::SUB_ADDR_ADDR_BYTE => (false, self.math_addr_addr_byte(u8::overflowing_sub)),
-            ops::SUB_ADDR_NUM_WORD => (false, self.math_addr_num_word(u16::overflowing_sub)),
-            ops::SUB_ADDR_REG_WORD => (false, self.math_addr_reg_word(u16::overflowing_sub)),
-            ops::SUB_ADDR_ADDR_WORD => (false, self.math_addr_addr_word(u16::overflowing_sub)),
-            ops::MUL_REG_NUM_BYTE => (false, self.math_reg_num_byte(u8::overflowing_mul)),
-            ops::MUL_REG_REG_BYTE => (false, self.math_reg_reg_byte(u8::overflowing_mul)),
-            ops::MUL_REG_ADDR_BYTE => (false, self.math_reg_addr_byte(u8::overflowing_mul)),
-            ops::MUL_REG_NUM_WORD => (false, self.math_reg_num_word(u16::overflowing_mul)),
-            ops::MUL_REG_REG_WORD => (false, self.math_reg_reg_word(u16::overflowing_mul)),
-            ops::MUL_REG_ADDR_WORD => (false, self.math_reg_addr_word(u16::overflowing_mul)),
-            ops::MUL_ADDR_NUM_BYTE => (false, self.math_addr_num_byte(u8::overflowing_mul)),
-            ops::MUL_ADDR_REG_BYTE => (false, self.math_addr_reg_byte(u8::overflowing_mul)),
-            ops::MUL_ADDR_ADDR_BYTE => (false, self.math_addr_addr_byte(u8::overflowing_mul)),
-            ops::MUL_ADDR_NUM_WORD => (false, self.math_addr_num_word(u16::overflowing_mul)),
-            ops::MUL_ADDR_REG_WORD => (false, self.math_addr_reg_word(u16::overflowing_mul)),
-            ops::MUL_ADDR_ADDR_WORD => (false, self.math_addr_addr_word(u16::overflowing_mul)),
-            ops::CPY_REG_NUM_BYTE => (false, self.cpy_reg_num_byte()),
-            ops::CPY_REG_REG_BYTE => (false, self.cpy_reg_reg_byte()),
-            ops::CPY_REG_ADDR_BYTE => (false, self.cpy_reg_addr_byte()),
-            ops::CPY_REG_NUM_WORD => (false, self.cpy_reg_num_word()),
-            ops::CPY_REG_REG_WORD => (false, self.cpy_reg_reg_word()),
-            ops::CPY_REG_ADDR_WORD => (false, self.cpy_reg_addr_word()),
-            ops::CPY_ADDR_NUM_BYTE => (false, self.cpy_addr_num_byte()),
-            ops::CPY_ADDR_REG_BYTE => (false, self.cpy_addr_reg_byte()),
-            ops::CPY_ADDR_ADDR_BYTE => (false, self.cpy_addr_addr_byte()),
-            ops::CPY_ADDR_NUM_WORD => (false, self.cpy_addr_num_word()),
-            ops::CPY_ADDR_REG_WORD => (false, self.cpy_addr_reg_word()),
-            ops::CPY_ADDR_ADDR_WORD => (false, self.cpy_addr_addr_word()),
-            ops::JMP_ADDR => self.jmp_addr(),
-            ops::JE_ADDR => self.je_addr(),
-            ops::JNE_ADDR => self.jne_addr(),
-            ops::JG_ADDR => self.jg_addr(),
-            ops::JL_ADDR => self.jl_addr(),
-            ops::JGE_ADDR => self.jge_addr(),
-            ops::JLE_ADDR => self.jle_addr(),
-            ops::JMP_REG => self.jmp_reg(),
-            ops::JE_REG => self.je_reg(),
-            ops::JNE_REG => self.jne_reg(),
-            ops::JG_REG => self.jg_reg(),
-            ops::JL_REG => self.jl_reg(),
-            ops::JGE_REG => self.jge_reg(),
-            ops::JLE_REG => self.jle_reg(),
-            ops::JRF_BYTE => {
+            Opcode::IncRegByte => (false, self.inc_reg_byte()),
+            Opcode::DecRegByte => (false, self.dec_reg_byte()),
+            Opcode::IncRegWord => (false, self.inc_reg_word()),
+            Opcode::DecRegWord => (false, self.dec_reg_word()),
+            Opcode::IncAddrByte => (false, self.inc_addr_byte()),
+            Opcode::DecAddrByte => (false, self.dec_addr_byte()),
+            Opcode::IncAddrWord => (false, self.inc_addr_word()),
+            Opcode::DecAddrWord => (false, self.dec_addr_word()),
+            Opcode::SwapRegRegByte => (false, self.swap_byte()),
+            Opcode::SwapRegRegWord => (false, self.swap_word()),
+            Opcode::AddRegNumByte => (false, self.math_reg_num_byte(u8::overflowing_add)),
+            Opcode::AddRegRegByte => (false, self.math_reg_reg_byte(u8::overflowing_add)),
+            Opcode::AddRegAddrByte => (false, self.math_reg_addr_byte(u8::overflowing_add)),
+            Opcode::AddRegNumWord => (false, self.math_reg_num_word(u16::overflowing_add)),
+            Opcode::AddRegRegWord => (false, self.math_reg_reg_word(u16::overflowing_add)),
+            Opcode::AddRegAddrWord => (false, self.math_reg_addr_word(u16::overflowing_add)),
+            Opcode::AddAddrNumByte => (false, self.math_addr_num_byte(u8::overflowing_add)),
+            Opcode::AddAddrRegByte => (false, self.math_addr_reg_byte(u8::overflowing_add)),
+            Opcode::AddAddrAddrByte => (false, self.math_addr_addr_byte(u8::overflowing_add)),
+            Opcode::AddAddrNumWord => (false, self.math_addr_num_word(u16::overflowing_add)),
+            Opcode::AddAddrRegWord => (false, self.math_addr_reg_word(u16::overflowing_add)),
+            Opcode::AddAddrAddrWord => (false, self.math_addr_addr_word(u16::overflowing_add)),
+            Opcode::SubRegNumByte => (false, self.math_reg_num_byte(u8::overflowing_sub)),
+            Opcode::SubRegRegByte => (false, self.math_reg_reg_byte(u8::overflowing_sub)),
+            Opcode::SubRegAddrByte => (false, self.math_reg_addr_byte(u8::overflowing_sub)),
+            Opcode::SubRegNumWord => (false, self.math_reg_num_word(u16::overflowing_sub)),
+            Opcode::SubRegRegWord => (false, self.math_reg_reg_word(u16::overflowing_sub)),
+            Opcode::SubRegAddrWord => (false, self.math_reg_addr_word(u16::overflowing_sub)),
+            Opcode::SubAddrNumByte => (false, self.math_addr_num_byte(u8::overflowing_sub)),
+            Opcode::SubAddrRegByte => (false, self.math_addr_reg_byte(u8::overflowing_sub)),
+            Opcode::SubAddrAddrByte => (false, self.math_addr_addr_byte(u8::overflowing_sub)),
+            Opcode::SubAddrNumWord => (false, self.math_addr_num_word(u16::overflowing_sub)),
+            Opcode::SubAddrRegWord => (false, self.math_addr_reg_word(u16::overflowing_sub)),
+            Opcode::SubAddrAddrWord => (false, self.math_addr_addr_word(u16::overflowing_sub)),
+            Opcode::MulRegNumByte => (false, self.math_reg_num_byte(u8::overflowing_mul)),
+            Opcode::MulRegRegByte => (false, self.math_reg_reg_byte(u8::overflowing_mul)),
+            Opcode::MulRegAddrByte => (false, self.math_reg_addr_byte(u8::overflowing_mul)),
+            Opcode::MulRegNumWord => (false, self.math_reg_num_word(u16::overflowing_mul)),
+            Opcode::MulRegRegWord => (false, self.math_reg_reg_word(u16::overflowing_mul)),
+            Opcode::MulRegAddrWord => (false, self.math_reg_addr_word(u16::overflowing_mul)),
+            Opcode::MulAddrNumByte => (false, self.math_addr_num_byte(u8::overflowing_mul)),
+            Opcode::MulAddrRegByte => (false, self.math_addr_reg_byte(u8::overflowing_mul)),
+            Opcode::MulAddrAddrByte => (false, self.math_addr_addr_byte(u8::overflowing_mul)),
+            Opcode::MulAddrNumWord => (false, self.math_addr_num_word(u16::overflowing_mul)),
+            Opcode::MulAddrRegWord => (false, self.math_addr_reg_word(u16::overflowing_mul)),
+            Opcode::MulAddrAddrWord => (false, self.math_addr_addr_word(u16::overflowing_mul)),
+            Opcode::CpyRegNumByte => (false, self.cpy_reg_num_byte()),
+            Opcode::CpyRegRegByte => (false, self.cpy_reg_reg_byte()),
+            Opcode::CpyRegAddrByte => (false, self.cpy_reg_addr_byte()),
+            Opcode::CpyRegNumWord => (false, self.cpy_reg_num_word()),
+            Opcode::CpyRegRegWord => (false, self.cpy_reg_reg_word()),
+            Opcode::CpyRegAddrWord => (false, self.cpy_reg_addr_word()),
+            Opcode::CpyAddrNumByte => (false, self.cpy_addr_num_byte()),
+            Opcode::CpyAddrRegByte => (false, self.cpy_addr_reg_byte()),
+            Opcode::CpyAddrAddrByte => (false, self.cpy_addr_addr_byte()),
+            Opcode::CpyAddrNumWord => (false, self.cpy_addr_num_word()),
+            Opcode::CpyAddrRegWord => (false, self.cpy_addr_reg_word()),
+            Opcode::CpyAddrAddrWord => (false, self.cpy_addr_addr_word()),
+            Opcode::JmpAddr => self.jmp_addr(),
+            Opcode::JeAddr => self.je_addr(),
+            Opcode::JneAddr => self.jne_addr(),
+            Opcode::JgAddr => self.jg_addr(),
+            Opcode::JlAddr => self.jl_addr(),
+            Opcode::JgeAddr => self.jge_addr(),
+            Opcode::JleAddr => self.jle_addr(),
+            Opcode::JmpReg => self.jmp_reg(),
+            Opcode::JeReg => self.je_reg(),
+            Opcode::JneReg => self.jne_reg(),
+            Opcode::JgReg => self.jg_reg(),
+            Opcode::JlReg => self.jl_reg(),
+            Opcode::JgeReg => self.jge_reg(),
+            Opcode::JleReg => self.jle_reg(),
+            Opcode::JrfByte => {
                 self.pc = self.pc.wrapping_add(self.read_arg_byte() as u16);
                 (true, 1)
             }
-            ops::JRB_BYTE => {
+            Opcode::JrbByte => {
                 self.pc = self.pc.wrapping_sub(self.read_arg_byte() as u16);
                 (true, 1)
             }
-            ops::CMP_REG_NUM_BYTE => (false, self.cmp_reg_num_byte(false)),
-            ops::CMPS_REG_NUM_BYTE => (false, self.cmp_reg_num_byte(true)),
-            ops::CMP_REG_NUM_WORD => (false, self.cmp_reg_num_word(false)),
-            ops::CMPS_REG_NUM_WORD => (false, self.cmp_reg_num_word(true)),
-            ops::MEM_CPY_ADDR_ADDR_BYTE => (false, self.mem_addr_addr_byte(VM::copy_mem)),
-            ops::MEM_CPY_ADDR_ADDR_REG => (false, self.mem_addr_addr_reg(VM::copy_mem)),
-            ops::MEM_CPY_ADDR_REG_BYTE => (false, self.mem_addr_reg_byte(VM::copy_mem)),
-            ops::MEM_CPY_ADDR_REG_REG => (false, self.mem_addr_reg_reg(VM::copy_mem)),
-            ops::MEM_CPY_REG_ADDR_BYTE => (false, self.mem_reg_addr_byte(VM::copy_mem)),
-            ops::MEM_CPY_REG_ADDR_REG => (false, self.mem_reg_addr_reg(VM::copy_mem)),
-            ops::MEM_CPY_REG_REG_BYTE => (false, self.mem_reg_reg_byte(VM::copy_mem)),
-            ops::MEM_CPY_REG_REG_REG => (false, self.mem_reg_reg_reg(VM::copy_mem)),
-            ops::NOT_REG_BYTE => (false, self.not_reg_byte()),
-            ops::NOT_REG_WORD => (false, self.not_reg_word()),
-            ops::OR_REG_REG_BYTE => (false, self.bl_reg_reg_byte(u8::bitor)),
-            ops::OR_REG_REG_WORD => (false, self.bl_reg_reg_word(u16::bitor)),
-            ops::OR_REG_NUM_BYTE => (false, self.bl_reg_num_byte(u8::bitor)),
-            ops::OR_REG_NUM_WORD => (false, self.bl_reg_num_word(u16::bitor)),
-            ops::XOR_REG_REG_BYTE => (false, self.bl_reg_reg_byte(u8::bitxor)),
-            ops::XOR_REG_REG_WORD => (false, self.bl_reg_reg_word(u16::bitxor)),
-            ops::XOR_REG_NUM_BYTE => (false, self.bl_reg_num_byte(u8::bitxor)),
-            ops::XOR_REG_NUM_WORD => (false, self.bl_reg_num_word(u16::bitxor)),
-            ops::AND_REG_REG_BYTE => (false, self.bl_reg_reg_byte(u8::bitand)),
-            ops::AND_REG_REG_WORD => (false, self.bl_reg_reg_word(u16::bitand)),
-            ops::AND_REG_NUM_BYTE => (false, self.bl_reg_num_byte(u8::bitand)),
-            ops::AND_REG_NUM_WORD => (false, self.bl_reg_num_word(u16::bitand)),
-            ops::PUSH_REG_BYTE => (false, self.push_reg_byte()),
-            ops::PUSH_REG_WORD => (false, self.push_reg_word()),
-            ops::PUSH_NUM_BYTE => (false, self.push_num_byte()),
-            ops::PUSH_NUM_WORD => (false, self.push_num_word()),
-            ops::POP_REG_BYTE => (false, self.pop_reg_byte()),
-            ops::POP_REG_WORD => (false, self.pop_reg_word()),
-            ops::MSWP_ADDR_ADDR_BYTE => (false, self.mem_addr_addr_byte(VM::swap_mem)),
-            ops::MSWP_ADDR_ADDR_REG => (false, self.mem_addr_addr_reg(VM::swap_mem)),
-            ops::MSWP_ADDR_REG_BYTE => (false, self.mem_addr_reg_byte(VM::swap_mem)),
-            ops::MSWP_ADDR_REG_REG => (false, self.mem_addr_reg_reg(VM::swap_mem)),
-            ops::MSWP_REG_ADDR_BYTE => (false, self.mem_reg_addr_byte(VM::swap_mem)),
-            ops::MSWP_REG_ADDR_REG => (false, self.mem_reg_addr_reg(VM::swap_mem)),
-            ops::MSWP_REG_REG_BYTE => (false, self.mem_reg_reg_byte(VM::swap_mem)),
-            ops::MSWP_REG_REG_REG => (false, self.mem_reg_reg_reg(VM::swap_mem)),
-            ops::JBC_REG_REG => self.jb_reg_reg(false),
-            ops::JBC_ADDR_REG => self.jb_addr_reg(false),
-            ops::JBC_REG_NUM => self.jb_reg_num(false),
-            ops::JBC_ADDR_NUM => self.jb_addr_num(false),
-            ops::JBS_REG_REG => self.jb_reg_reg(true),
-            ops::JBS_ADDR_REG => self.jb_addr_reg(true),
-            ops::JBS_REG_NUM => self.jb_reg_num(true),
-            ops::JBS_ADDR_NUM => self.jb_addr_num(true),
-            ops::ADDC_REG_NUM_BYTE => (false, self.mathc_reg_num_byte(u8_carrying_add)),
-            ops::ADDC_REG_REG_BYTE => (false, self.mathc_reg_reg_byte(u8_carrying_add)),
-            ops::ADDC_REG_ADDR_BYTE => (false, self.mathc_reg_addr_byte(u8_carrying_add)),
-            ops::ADDC_REG_NUM_WORD => (false, self.mathc_reg_num_word(u16_carrying_add)),
-            ops::ADDC_REG_REG_WORD => (false, self.mathc_reg_reg_word(u16_carrying_add)),
-            ops::ADDC_REG_ADDR_WORD => (false, self.mathc_reg_addr_word(u16_carrying_add)),
-            ops::ADDC_ADDR_NUM_BYTE => (false, self.mathc_addr_num_byte(u8_carrying_add)),
-            ops::ADDC_ADDR_REG_BYTE => (false, self.mathc_addr_reg_byte(u8_carrying_add)),
-            ops::ADDC_ADDR_ADDR_BYTE => (false, self.mathc_addr_addr_byte(u8_carrying_add)),
-            ops::ADDC_ADDR_NUM_WORD => (false, self.mathc_addr_num_word(u16_carrying_add)),
-            ops::ADDC_ADDR_REG_WORD => (false, self.mathc_addr_reg_word(u16_carrying_add)),
-            ops::ADDC_ADDR_ADDR_WORD => (false, self.mathc_addr_addr_word(u16_carrying_add)),
-            ops::SUBC_REG_NUM_BYTE => (false, self.mathc_reg_num_byte(u8_carrying_sub)),
-            ops::SUBC_REG_REG_BYTE => (false, self.mathc_reg_reg_byte(u8_carrying_sub)),
-            ops::SUBC_REG_ADDR_BYTE => (false, self.mathc_reg_addr_byte(u8_carrying_sub)),
-            ops::SUBC_REG_NUM_WORD => (false, self.mathc_reg_num_word(u16_carrying_sub)),
-            ops::SUBC_REG_REG_WORD => (false, self.mathc_reg_reg_word(u16_carrying_sub)),
-            ops::SUBC_REG_ADDR_WORD => (false, self.mathc_reg_addr_word(u16_carrying_sub)),
-            ops::SUBC_ADDR_NUM_BYTE => (false, self.mathc_addr_num_byte(u8_carrying_sub)),
-            ops::SUBC_ADDR_REG_BYTE => (false, self.mathc_addr_reg_byte(u8_carrying_sub)),
-            ops::SUBC_ADDR_ADDR_BYTE => (false, self.mathc_addr_addr_byte(u8_carrying_sub)),
-            ops::SUBC_ADDR_NUM_WORD => (false, self.mathc_addr_num_word(u16_carrying_sub)),
-            ops::SUBC_ADDR_REG_WORD => (false, self.mathc_addr_reg_word(u16_carrying_sub)),
-            ops::SUBC_ADDR_ADDR_WORD => (false, self.mathc_addr_addr_word(u16_carrying_sub)),
-            ops::MULS_REG_NUM_BYTE => (false, self.maths_reg_num_byte(u8_muls)),
-            ops::MULS_REG_REG_BYTE => (false, self.maths_reg_reg_byte(u8_muls)),
-            ops::MULS_REG_ADDR_BYTE => (false, self.maths_reg_addr_byte(u8_muls)),
-            ops::MULS_REG_NUM_WORD => (false, self.maths_reg_num_word(u16_muls)),
-            ops::MULS_REG_REG_WORD => (false, self.maths_reg_reg_word(u16_muls)),
-            ops::MULS_REG_ADDR_WORD => (false, self.maths_reg_addr_word(u16_muls)),
-            ops::MULS_ADDR_NUM_BYTE => (false, self.maths_addr_num_byte(u8_muls)),
-            ops::MULS_ADDR_REG_BYTE => (false, self.maths_addr_reg_byte(u8_muls)),
-            ops::MULS_ADDR_ADDR_BYTE => (false, self.maths_addr_addr_byte(u8_muls)),
-            ops::MULS_ADDR_NUM_WORD => (false, self.maths_addr_num_word(u16_muls)),
-            ops::MULS_ADDR_REG_WORD => (false, self.maths_addr_reg_word(u16_muls)),
-            ops::MULS_ADDR_ADDR_WORD => (false, self.maths_addr_addr_word(u16_muls)),
-            ops::DIVS_REG_NUM_BYTE => (false, self.maths_reg_num_byte(u8_divs)),
-            ops::DIVS_REG_REG_BYTE => (false, self.maths_reg_reg_byte(u8_divs)),
-            ops::DIVS_REG_ADDR_BYTE => (false, self.maths_reg_addr_byte(u8_divs)),
-            ops::DIVS_REG_NUM_WORD => (false, self.maths_reg_num_word(u16_divs)),
-            ops::DIVS_REG_REG_WORD => (false, self.maths_reg_reg_word(u16_divs)),
-            ops::DIVS_REG_ADDR_WORD => (false, self.maths_reg_addr_word(u16_divs)),
-            ops::DIVS_ADDR_NUM_BYTE => (false, self.maths_addr_num_byte(u8_divs)),
-            ops::DIVS_ADDR_REG_BYTE => (false, self.maths_addr_reg_byte(u8_divs)),
-            ops::DIVS_ADDR_ADDR_BYTE => (false, self.maths_addr_addr_byte(u8_divs)),
-            ops::DIVS_ADDR_NUM_WORD => (false, self.maths_addr_num_word(u16_divs)),
-            ops::DIVS_ADDR_REG_WORD => (false, self.maths_addr_reg_word(u16_divs)),
-            ops::DIVS_ADDR_ADDR_WORD => (false, self.maths_addr_addr_word(u16_divs)),
-            ops::ASL_ADDR_BYTE => (false, self.asl_addr_byte()),
-            ops::ASR_ADDR_BYTE => (false, self.asr_addr_byte()),
-            ops::LSR_ADDR_BYTE => (false, self.lsr_addr_byte()),
-            ops::ROL_ADDR_BYTE => (false, self.rol_addr_byte()),
-            ops::ROR_ADDR_BYTE => (false, self.ror_addr_byte()),
-            ops::RCL_ADDR_BYTE => (false, self.rcl_addr_byte()),
-            ops::RCR_ADDR_BYTE => (false, self.rcr_addr_byte()),
-            ops::ASL_ADDR_WORD => (false, self.asl_addr_word()),
-            ops::ASR_ADDR_WORD => (false, self.asr_addr_word()),
-            ops::LSR_ADDR_WORD => (false, self.lsr_addr_word()),
-            ops::ROL_ADDR_WORD => (false, self.rol_addr_word()),
-            ops::ROR_ADDR_WORD => (false, self.ror_addr_word()),
-            ops::RCL_ADDR_WORD => (false, self.rcl_addr_word()),
-            ops::RCR_ADDR_WORD => (false, self.rcr_addr_word()),
-            ops::ASL_REG_NUM_BYTE => (false, self.asl_reg_num_byte()),
-            ops::ASR_REG_NUM_BYTE => (false, self.asr_reg_num_byte()),
-            ops::LSR_REG_NUM_BYTE => (false, self.lsr_reg_num_byte()),
-            ops::ROL_REG_NUM_BYTE => (false, self.rol_reg_num_byte()),
-            ops::ROR_REG_NUM_BYTE => (false, self.ror_reg_num_byte()),
-            ops::RCL_REG_NUM_BYTE => (false, self.rcl_reg_num_byte()),
-            ops::RCR_REG_NUM_BYTE => (false, self.rcr_reg_num_byte()),
-            ops::ASL_REG_NUM_WORD => (false, self.asl_reg_num_word()),
-            ops::ASR_REG_NUM_WORD => (false, self.asr_reg_num_word()),
-            ops::LSR_REG_NUM_WORD => (false, self.lsr_reg_num_word()),
-            ops::ROL_REG_NUM_WORD => (false, self.rol_reg_num_word()),
-            ops::ROR_REG_NUM_WORD => (false, self.ror_reg_num_word()),
-            ops::RCL_REG_NUM_WORD => (false, self.rcl_reg_num_word()),
-            ops::RCR_REG_NUM_WORD => (false, self.rcr_reg_num_word()),
-            ops::ASL_REG_REG_BYTE => (false, self.asl_reg_reg_byte()),
-            ops::ASR_REG_REG_BYTE => (false, self.asr_reg_reg_byte()),
-            ops::LSR_REG_REG_BYTE => (false, self.lsr_reg_reg_byte()),
-            ops::ROL_REG_REG_BYTE => (false, self.rol_reg_reg_byte()),
-            ops::ROR_REG_REG_BYTE => (false, self.ror_reg_reg_byte()),
-            ops::RCL_REG_REG_BYTE => (false, self.rcl_reg_reg_byte()),
-            ops::RCR_REG_REG_BYTE => (false, self.rcr_reg_reg_byte()),
-            ops::ASL_REG_REG_WORD => (false, self.asl_reg_reg_word()),
-            ops::ASR_REG_REG_WORD => (false, self.asr_reg_reg_word()),
-            ops::LSR_REG_REG_WORD => (false, self.lsr_reg_reg_word()),
-            ops::ROL_REG_REG_WORD => (false, self.rol_reg_reg_word()),
-            ops::ROR_REG_REG_WORD => (false, self.ror_reg_reg_word()),
-            ops::RCL_REG_REG_WORD => (false, self.rcl_reg_reg_word()),
-            ops::RCR_REG_REG_WORD => (false, self.rcr_reg_reg_word()),
-            _ => {
-                self.fail(format!("Unsupported op: {:02X}", op));
-                (false, 0)
+            Opcode::CmpRegNumByte => (false, self.cmp_reg_num_byte(false)),
+            Opcode::CmpsRegNumByte => (false, self.cmp_reg_num_byte(true)),
+            Opcode::CmpRegNumWord => (false, self.cmp_reg_num_word(false)),
+            Opcode::CmpsRegNumWord => (false, self.cmp_reg_num_word(true)),
+            Opcode::MemCpyAddrAddrByte => (false, self.mem_addr_addr_byte(VM::copy_mem)),
+            Opcode::MemCpyAddrAddrReg => (false, self.mem_addr_addr_reg(VM::copy_mem)),
+            Opcode::MemCpyAddrRegByte => (false, self.mem_addr_reg_byte(VM::copy_mem)),
+            Opcode::MemCpyAddrRegReg => (false, self.mem_addr_reg_reg(VM::copy_mem)),
+            Opcode::MemCpyRegAddrByte => (false, self.mem_reg_addr_byte(VM::copy_mem)),
+            Opcode::MemCpyRegAddrReg => (false, self.mem_reg_addr_reg(VM::copy_mem)),
+            Opcode::MemCpyRegRegByte => (false, self.mem_reg_reg_byte(VM::copy_mem)),
+            Opcode::MemCpyRegRegReg => (false, self.mem_reg_reg_reg(VM::copy_mem)),
+            Opcode::NotRegByte => (false, self.not_reg_byte()),
+            Opcode::NotRegWord => (false, self.not_reg_word()),
+            Opcode::OrRegRegByte => (false, self.bl_reg_reg_byte(u8::bitor)),
+            Opcode::OrRegRegWord => (false, self.bl_reg_reg_word(u16::bitor)),
+            Opcode::OrRegNumByte => (false, self.bl_reg_num_byte(u8::bitor)),
+            Opcode::OrRegNumWord => (false, self.bl_reg_num_word(u16::bitor)),
+            Opcode::XorRegRegByte => (false, self.bl_reg_reg_byte(u8::bitxor)),
+            Opcode::XorRegRegWord => (false, self.bl_reg_reg_word(u16::bitxor)),
+            Opcode::XorRegNumByte => (false, self.bl_reg_num_byte(u8::bitxor)),
+            Opcode::XorRegNumWord => (false, self.bl_reg_num_word(u16::bitxor)),
+            Opcode::AndRegRegByte => (false, self.bl_reg_reg_byte(u8::bitand)),
+            Opcode::AndRegRegWord => (false, self.bl_reg_reg_word(u16::bitand)),
+            Opcode::AndRegNumByte => (false, self.bl_reg_num_byte(u8::bitand)),
+            Opcode::AndRegNumWord => (false, self.bl_reg_num_word(u16::bitand)),
+            Opcode::PushRegByte => (false, self.push_reg_byte()),
+            Opcode::PushRegWord => (false, self.push_reg_word()),
+            Opcode::PushNumByte => (false, self.push_num_byte()),
+            Opcode::PushNumWord => (false, self.push_num_word()),
+            Opcode::PopRegByte => (false, self.pop_reg_byte()),
+            Opcode::PopRegWord => (false, self.pop_reg_word()),
+            Opcode::MswpAddrAddrByte => (false, self.mem_addr_addr_byte(VM::swap_mem)),
+            Opcode::MswpAddrAddrReg => (false, self.mem_addr_addr_reg(VM::swap_mem)),
+            Opcode::MswpAddrRegByte => (false, self.mem_addr_reg_byte(VM::swap_mem)),
+            Opcode::MswpAddrRegReg => (false, self.mem_addr_reg_reg(VM::swap_mem)),
+            Opcode::MswpRegAddrByte => (false, self.mem_reg_addr_byte(VM::swap_mem)),
+            Opcode::MswpRegAddrReg => (false, self.mem_reg_addr_reg(VM::swap_mem)),
+            Opcode::MswpRegRegByte => (false, self.mem_reg_reg_byte(VM::swap_mem)),
+            Opcode::MswpRegRegReg => (false, self.mem_reg_reg_reg(VM::swap_mem)),
+            Opcode::JbcRegReg => self.jb_reg_reg(false),
+            Opcode::JbcAddrReg => self.jb_addr_reg(false),
+            Opcode::JbcRegNum => self.jb_reg_num(false),
+            Opcode::JbcAddrNum => self.jb_addr_num(false),
+            Opcode::JbsRegReg => self.jb_reg_reg(true),
+            Opcode::JbsAddrReg => self.jb_addr_reg(true),
+            Opcode::JbsRegNum => self.jb_reg_num(true),
+            Opcode::JbsAddrNum => self.jb_addr_num(true),
+            Opcode::AddcRegNumByte => (false, self.mathc_reg_num_byte(u8_carrying_add)),
+            Opcode::AddcRegRegByte => (false, self.mathc_reg_reg_byte(u8_carrying_add)),
+            Opcode::AddcRegAddrByte => (false, self.mathc_reg_addr_byte(u8_carrying_add)),
+            Opcode::AddcRegNumWord => (false, self.mathc_reg_num_word(u16_carrying_add)),
+            Opcode::AddcRegRegWord => (false, self.mathc_reg_reg_word(u16_carrying_add)),
+            Opcode::AddcRegAddrWord => (false, self.mathc_reg_addr_word(u16_carrying_add)),
+            Opcode::AddcAddrNumByte => (false, self.mathc_addr_num_byte(u8_carrying_add)),
+            Opcode::AddcAddrRegByte => (false, self.mathc_addr_reg_byte(u8_carrying_add)),
+            Opcode::AddcAddrAddrByte => (false, self.mathc_addr_addr_byte(u8_carrying_add)),
+            Opcode::AddcAddrNumWord => (false, self.mathc_addr_num_word(u16_carrying_add)),
+            Opcode::AddcAddrRegWord => (false, self.mathc_addr_reg_word(u16_carrying_add)),
+            Opcode::AddcAddrAddrWord => (false, self.mathc_addr_addr_word(u16_carrying_add)),
+            Opcode::SubcRegNumByte => (false, self.mathc_reg_num_byte(u8_carrying_sub)),
+            Opcode::SubcRegRegByte => (false, self.mathc_reg_reg_byte(u8_carrying_sub)),
+            Opcode::SubcRegAddrByte => (false, self.mathc_reg_addr_byte(u8_carrying_sub)),
+            Opcode::SubcRegNumWord => (false, self.mathc_reg_num_word(u16_carrying_sub)),
+            Opcode::SubcRegRegWord => (false, self.mathc_reg_reg_word(u16_carrying_sub)),
+            Opcode::SubcRegAddrWord => (false, self.mathc_reg_addr_word(u16_carrying_sub)),
+            Opcode::SubcAddrNumByte => (false, self.mathc_addr_num_byte(u8_carrying_sub)),
+            Opcode::SubcAddrRegByte => (false, self.mathc_addr_reg_byte(u8_carrying_sub)),
+            Opcode::SubcAddrAddrByte => (false, self.mathc_addr_addr_byte(u8_carrying_sub)),
+            Opcode::SubcAddrNumWord => (false, self.mathc_addr_num_word(u16_carrying_sub)),
+            Opcode::SubcAddrRegWord => (false, self.mathc_addr_reg_word(u16_carrying_sub)),
+            Opcode::SubcAddrAddrWord => (false, self.mathc_addr_addr_word(u16_carrying_sub)),
+            Opcode::MulsRegNumByte => (false, self.maths_reg_num_byte(u8_muls)),
+            Opcode::MulsRegRegByte => (false, self.maths_reg_reg_byte(u8_muls)),
+            Opcode::MulsRegAddrByte => (false, self.maths_reg_addr_byte(u8_muls)),
+            Opcode::MulsRegNumWord => (false, self.maths_reg_num_word(u16_muls)),
+            Opcode::MulsRegRegWord => (false, self.maths_reg_reg_word(u16_muls)),
+            Opcode::MulsRegAddrWord => (false, self.maths_reg_addr_word(u16_muls)),
+            Opcode::MulsAddrNumByte => (false, self.maths_addr_num_byte(u8_muls)),
+            Opcode::MulsAddrRegByte => (false, self.maths_addr_reg_byte(u8_muls)),
+            Opcode::MulsAddrAddrByte => (false, self.maths_addr_addr_byte(u8_muls)),
+            Opcode::MulsAddrNumWord => (false, self.maths_addr_num_word(u16_muls)),
+            Opcode::MulsAddrRegWord => (false, self.maths_addr_reg_word(u16_muls)),
+            Opcode::MulsAddrAddrWord => (false, self.maths_addr_addr_word(u16_muls)),
+            Opcode::DivsRegNumByte => (false, self.maths_reg_num_byte(u8_divs)),
+            Opcode::DivsRegRegByte => (false, self.maths_reg_reg_byte(u8_divs)),
+            Opcode::DivsRegAddrByte => (false, self.maths_reg_addr_byte(u8_divs)),
+            Opcode::DivsRegNumWord => (false, self.maths_reg_num_word(u16_divs)),
+            Opcode::DivsRegRegWord => (false, self.maths_reg_reg_word(u16_divs)),
+            Opcode::DivsRegAddrWord => (false, self.maths_reg_addr_word(u16_divs)),
+            Opcode::DivsAddrNumByte => (false, self.maths_addr_num_byte(u8_divs)),
+            Opcode::DivsAddrRegByte => (false, self.maths_addr_reg_byte(u8_divs)),
+            Opcode::DivsAddrAddrByte => (false, self.maths_addr_addr_byte(u8_divs)),
+            Opcode::DivsAddrNumWord => (false, self.maths_addr_num_word(u16_divs)),
+            Opcode::DivsAddrRegWord => (false, self.maths_addr_reg_word(u16_divs)),
+            Opcode::DivsAddrAddrWord => (false, self.maths_addr_addr_word(u16_divs)),
+            Opcode::AslAddrByte => (false, self.asl_addr_byte()),
+            Opcode::AsrAddrByte => (false, self.asr_addr_byte()),
+            Opcode::LsrAddrByte => (false, self.lsr_addr_byte()),
+            Opcode::RolAddrByte => (false, self.rol_addr_byte()),
+            Opcode::RorAddrByte => (false, self.ror_addr_byte()),
+            Opcode::RclAddrByte => (false, self.rcl_addr_byte()),
+            Opcode::RcrAddrByte => (false, self.rcr_addr_byte()),
+            Opcode::AslAddrWord => (false, self.asl_addr_word()),
+            Opcode::AsrAddrWord => (false, self.asr_addr_word()),
+            Opcode::LsrAddrWord => (false, self.lsr_addr_word()),
+            Opcode::RolAddrWord => (false, self.rol_addr_word()),
+            Opcode::RorAddrWord => (false, self.ror_addr_word()),
+            Opcode::RclAddrWord => (false, self.rcl_addr_word()),
+            Opcode::RcrAddrWord => (false, self.rcr_addr_word()),
+            Opcode::AslRegNumByte => (false, self.asl_reg_num_byte()),
+            Opcode::AsrRegNumByte => (false, self.asr_reg_num_byte()),
+            Opcode::LsrRegNumByte => (false, self.lsr_reg_num_byte()),
+            Opcode::RolRegNumByte => (false, self.rol_reg_num_byte()),
+            Opcode::RorRegNumByte => (false, self.ror_reg_num_byte()),
+            Opcode::RclRegNumByte => (false, self.rcl_reg_num_byte()),
+            Opcode::RcrRegNumByte => (false, self.rcr_reg_num_byte()),
+            Opcode::AslRegNumWord => (false, self.asl_reg_num_word()),
+            Opcode::AsrRegNumWord => (false, self.asr_reg_num_word()),
+            Opcode::LsrRegNumWord => (false, self.lsr_reg_num_word()),
+            Opcode::RolRegNumWord => (false, self.rol_reg_num_word()),
+            Opcode::RorRegNumWord => (false, self.ror_reg_num_word()),
+            Opcode::RclRegNumWord => (false, self.rcl_reg_num_word()),
+            Opcode::RcrRegNumWord => (false, self.rcr_reg_num_word()),
+            Opcode::AslRegRegByte => (false, self.asl_reg_reg_byte()),
+            Opcode::AsrRegRegByte => (false, self.asr_reg_reg_byte()),
+            Opcode::LsrRegRegByte => (false, self.lsr_reg_reg_byte()),
+            Opcode::RolRegRegByte => (false, self.rol_reg_reg_byte()),
+            Opcode::RorRegRegByte => (false, self.ror_reg_reg_byte()),
+            Opcode::RclRegRegByte => (false, self.rcl_reg_reg_byte()),
+            Opcode::RcrRegRegByte => (false, self.rcr_reg_reg_byte()),
+            Opcode::AslRegRegWord => (false, self.asl_reg_reg_word()),
+            Opcode::AsrRegRegWord => (false, self.asr_reg_reg_word()),
+            Opcode::LsrRegRegWord => (false, self.lsr_reg_reg_word()),
+            Opcode::RolRegRegWord => (false, self.rol_reg_reg_word()),
+            Opcode::RorRegRegWord => (false, self.ror_reg_reg_word()),
+            Opcode::RclRegRegWord => (false, self.rcl_reg_reg_word()),
+            Opcode::RcrRegRegWord => (false, self.rcr_reg_reg_word()),
+            Opcode::CmpRegAddrByte
+            | Opcode::CmpRegAddrWord
+            | Opcode::CmpsRegAddrByte
+            | Opcode::CmpsRegAddrWord
+            | Opcode::DivRegRegByte
+            | Opcode::DivRegRegWord
+            | Opcode::DivRegNumByte
+            | Opcode::DivRegNumWord
+            | Opcode::DivRegAddrByte
+            | Opcode::DivRegAddrWord
+            | Opcode::DivAddrRegByte
+            | Opcode::DivAddrRegWord
+            | Opcode::DivAddrNumByte
+            | Opcode::DivAddrNumWord
+            | Opcode::DivAddrAddrByte
+            | Opcode::DivAddrAddrWord
+            | Opcode::CmpRegRegByte
+            | Opcode::CmpRegRegWord
+            | Opcode::CmpsRegRegByte
+            | Opcode::CmpsRegRegWord
+            | Opcode::BmulAddrAddr
+            | Opcode::BmulAddrNum
+            | Opcode::BmulAddrReg
+            | Opcode::BmulRegAddr
+            | Opcode::BmulRegNum
+            | Opcode::BmulRegReg
+            | Opcode::Sleep => {
+                return Err(VmError::UnsupportedOp {
+                    op: instruction.op(),
+                    pc: self.pc,
+                });
             }
         })
     }
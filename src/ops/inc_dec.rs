@@ -71,29 +71,62 @@ mod test {
     use super::*;
     use crate::ops::test::check_cycles;
     use maikor_platform::op_params::*;
+    use maikor_platform::ops;
     use maikor_platform::registers::id;
 
     #[test]
     fn test_costs() {
-        check_cycles(&[id::AL as u8], 2, VM::inc_reg_byte);
-        check_cycles(&[id::AX as u8], 4, VM::inc_reg_word);
-        check_cycles(&[id::AX as u8 | INDIRECT], 6, VM::inc_reg_byte);
-        check_cycles(&[id::AX as u8 | IND_OFFSET_NUM], 6, VM::inc_reg_byte);
-        check_cycles(&[id::AX as u8 | INDIRECT], 8, VM::inc_reg_word);
-        check_cycles(&[id::AX as u8 | IND_PRE_DEC], 10, VM::inc_reg_word);
-        check_cycles(&[id::AL as u8], 2, VM::dec_reg_byte);
-        check_cycles(&[id::AX as u8], 4, VM::dec_reg_word);
-        check_cycles(&[id::AX as u8 | INDIRECT], 6, VM::dec_reg_byte);
-        check_cycles(&[id::AX as u8 | INDIRECT], 8, VM::dec_reg_word);
-        check_cycles(&[id::AX as u8 | IND_OFFSET_REG], 9, VM::dec_reg_word);
+        check_cycles(&[ops::INC_REG_BYTE, id::AL as u8], 2, VM::inc_reg_byte);
+        check_cycles(&[ops::INC_REG_WORD, id::AX as u8], 4, VM::inc_reg_word);
         check_cycles(
-            &[id::AX as u8 | IND_OFFSET_EXT_REG, id::BX as u8],
+            &[ops::INC_REG_BYTE, id::AX as u8 | INDIRECT],
+            6,
+            VM::inc_reg_byte,
+        );
+        check_cycles(
+            &[ops::INC_REG_BYTE, id::AX as u8 | IND_OFFSET_NUM],
+            6,
+            VM::inc_reg_byte,
+        );
+        check_cycles(
+            &[ops::INC_REG_WORD, id::AX as u8 | INDIRECT],
+            8,
+            VM::inc_reg_word,
+        );
+        check_cycles(
+            &[ops::INC_REG_WORD, id::AX as u8 | IND_PRE_DEC],
+            10,
+            VM::inc_reg_word,
+        );
+        check_cycles(&[ops::DEC_REG_BYTE, id::AL as u8], 2, VM::dec_reg_byte);
+        check_cycles(&[ops::DEC_REG_WORD, id::AX as u8], 4, VM::dec_reg_word);
+        check_cycles(
+            &[ops::DEC_REG_BYTE, id::AX as u8 | INDIRECT],
+            6,
+            VM::dec_reg_byte,
+        );
+        check_cycles(
+            &[ops::DEC_REG_WORD, id::AX as u8 | INDIRECT],
+            8,
+            VM::dec_reg_word,
+        );
+        check_cycles(
+            &[ops::DEC_REG_WORD, id::AX as u8 | IND_OFFSET_REG],
+            9,
+            VM::dec_reg_word,
+        );
+        check_cycles(
+            &[
+                ops::DEC_REG_WORD,
+                id::AX as u8 | IND_OFFSET_EXT_REG,
+                id::BX as u8,
+            ],
             10,
             VM::dec_reg_word,
         );
-        check_cycles(&[0], 2, VM::inc_addr_byte);
-        check_cycles(&[0], 4, VM::inc_addr_word);
-        check_cycles(&[0], 2, VM::dec_addr_byte);
-        check_cycles(&[0], 4, VM::dec_addr_word);
+        check_cycles(&[ops::INC_ADDR_BYTE, 0], 2, VM::inc_addr_byte);
+        check_cycles(&[ops::INC_ADDR_WORD, 0], 4, VM::inc_addr_word);
+        check_cycles(&[ops::DEC_ADDR_BYTE, 0], 2, VM::dec_addr_byte);
+        check_cycles(&[ops::DEC_ADDR_WORD, 0], 4, VM::dec_addr_word);
     }
 }
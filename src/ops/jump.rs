@@ -86,22 +86,23 @@ impl VM {
 mod test {
     use super::*;
     use crate::ops::test::check_jmp_cycles;
+    use maikor_platform::ops;
 
     #[test]
     fn test_costs() {
-        check_jmp_cycles(&[0, 0], 1, VM::jmp_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::je_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::jne_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::jg_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::jl_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::jge_addr);
-        check_jmp_cycles(&[0, 0], 1, VM::jle_addr);
-        check_jmp_cycles(&[0], 2, VM::jmp_reg);
-        check_jmp_cycles(&[0], 2, VM::je_reg);
-        check_jmp_cycles(&[0], 2, VM::jne_reg);
-        check_jmp_cycles(&[0], 2, VM::jg_reg);
-        check_jmp_cycles(&[0], 2, VM::jl_reg);
-        check_jmp_cycles(&[0], 2, VM::jge_reg);
-        check_jmp_cycles(&[0], 2, VM::jle_reg);
+        check_jmp_cycles(&[ops::JMP_ADDR, 0, 0], 1, VM::jmp_addr);
+        check_jmp_cycles(&[ops::JE_ADDR, 0, 0], 1, VM::je_addr);
+        check_jmp_cycles(&[ops::JNE_ADDR, 0, 0], 1, VM::jne_addr);
+        check_jmp_cycles(&[ops::JG_ADDR, 0, 0], 1, VM::jg_addr);
+        check_jmp_cycles(&[ops::JL_ADDR, 0, 0], 1, VM::jl_addr);
+        check_jmp_cycles(&[ops::JGE_ADDR, 0, 0], 1, VM::jge_addr);
+        check_jmp_cycles(&[ops::JLE_ADDR, 0, 0], 1, VM::jle_addr);
+        check_jmp_cycles(&[ops::JMP_REG, 0], 2, VM::jmp_reg);
+        check_jmp_cycles(&[ops::JE_REG, 0], 2, VM::je_reg);
+        check_jmp_cycles(&[ops::JNE_REG, 0], 2, VM::jne_reg);
+        check_jmp_cycles(&[ops::JG_REG, 0], 2, VM::jg_reg);
+        check_jmp_cycles(&[ops::JL_REG, 0], 2, VM::jl_reg);
+        check_jmp_cycles(&[ops::JGE_REG, 0], 2, VM::jge_reg);
+        check_jmp_cycles(&[ops::JLE_REG, 0], 2, VM::jle_reg);
     }
 }
@@ -182,7 +182,8 @@ pub fn u16_carrying_sub(lhs: u16, rhs: u16, carry: bool) -> (u16, bool) {
 #[cfg(test)]
 mod test {
     use super::*;
-    use maikor_platform::mem::address::RESERVED;
+    use crate::ops::test::vm_with_args;
+    use maikor_platform::ops;
     use maikor_platform::registers::id;
 
     #[test]
@@ -194,51 +195,81 @@ mod test {
     }
 
     pub fn check_math_methods_b(math_method: fn(u8, u8, bool) -> (u8, bool)) {
-        check_byte_math_cycles(&[id::AL as u8, 10], 2, math_method, VM::mathc_reg_num_byte);
         check_byte_math_cycles(
-            &[id::AL as u8, id::AH as u8],
+            &[ops::ADDC_REG_NUM_BYTE, id::AL as u8, 10],
+            2,
+            math_method,
+            VM::mathc_reg_num_byte,
+        );
+        check_byte_math_cycles(
+            &[ops::ADDC_REG_REG_BYTE, id::AL as u8, id::AH as u8],
             3,
             math_method,
             VM::mathc_reg_reg_byte,
         );
         check_byte_math_cycles(
-            &[id::CH as u8, 0, 100],
+            &[ops::ADDC_REG_ADDR_BYTE, id::CH as u8, 0, 100],
             3,
             math_method,
             VM::mathc_reg_addr_byte,
         );
-        check_byte_math_cycles(&[1, 1, 60], 2, math_method, VM::mathc_addr_num_byte);
         check_byte_math_cycles(
-            &[9, 9, id::AL as u8],
+            &[ops::ADDC_ADDR_NUM_BYTE, 1, 1, 60],
+            2,
+            math_method,
+            VM::mathc_addr_num_byte,
+        );
+        check_byte_math_cycles(
+            &[ops::ADDC_ADDR_REG_BYTE, 9, 9, id::AL as u8],
             3,
             math_method,
             VM::mathc_addr_reg_byte,
         );
-        check_byte_math_cycles(&[0, 0, 1, 1], 3, math_method, VM::mathc_addr_addr_byte);
+        check_byte_math_cycles(
+            &[ops::ADDC_ADDR_ADDR_BYTE, 0, 0, 1, 1],
+            3,
+            math_method,
+            VM::mathc_addr_addr_byte,
+        );
     }
 
     pub fn check_math_methods_w(math_method: fn(u16, u16, bool) -> (u16, bool)) {
-        check_word_math_cycles(&[id::AX as u8, 10], 4, math_method, VM::mathc_reg_num_word);
         check_word_math_cycles(
-            &[id::AL as u8, id::AH as u8],
+            &[ops::ADDC_REG_NUM_WORD, id::AX as u8, 10],
+            4,
+            math_method,
+            VM::mathc_reg_num_word,
+        );
+        check_word_math_cycles(
+            &[ops::ADDC_REG_REG_WORD, id::AL as u8, id::AH as u8],
             6,
             math_method,
             VM::mathc_reg_reg_word,
         );
         check_word_math_cycles(
-            &[id::CX as u8, 0, 100],
+            &[ops::ADDC_REG_ADDR_WORD, id::CX as u8, 0, 100],
             6,
             math_method,
             VM::mathc_reg_addr_word,
         );
-        check_word_math_cycles(&[1, 1, 60], 4, math_method, VM::mathc_addr_num_word);
         check_word_math_cycles(
-            &[9, 9, id::AX as u8],
+            &[ops::ADDC_ADDR_NUM_WORD, 1, 1, 60],
+            4,
+            math_method,
+            VM::mathc_addr_num_word,
+        );
+        check_word_math_cycles(
+            &[ops::ADDC_ADDR_REG_WORD, 9, 9, id::AX as u8],
             6,
             math_method,
             VM::mathc_addr_reg_word,
         );
-        check_word_math_cycles(&[0, 0, 1, 1], 6, math_method, VM::mathc_addr_addr_word);
+        check_word_math_cycles(
+            &[ops::ADDC_ADDR_ADDR_WORD, 0, 0, 1, 1],
+            6,
+            math_method,
+            VM::mathc_addr_addr_word,
+        );
     }
 
     pub fn check_byte_math_cycles(
@@ -247,11 +278,7 @@ mod test {
         math_method: fn(u8, u8, bool) -> (u8, bool),
         op_method: fn(&mut VM, fn(u8, u8, bool) -> (u8, bool)) -> usize,
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        let mut vm = vm_with_args(bytes);
         assert_eq!(op_method(&mut vm, math_method), expected_cycles)
     }
 
@@ -261,11 +288,7 @@ mod test {
         math_method: fn(u16, u16, bool) -> (u16, bool),
         op_method: fn(&mut VM, fn(u16, u16, bool) -> (u16, bool)) -> usize,
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        let mut vm = vm_with_args(bytes);
         assert_eq!(op_method(&mut vm, math_method), expected_cycles)
     }
 }
@@ -2,21 +2,52 @@ use crate::VM;
 
 impl VM {
     pub fn copy_mem(&mut self, dst: u16, src: u16, count: usize) {
+        let before = self.watch_before_transfer(dst, src, count);
+        self.log_mem(dst as usize, count, false);
         unsafe {
             let dst_ptr = self.get_memory_mut(dst as usize, count).as_mut_ptr();
             let src_ptr = self.get_memory_mut(src as usize, count).as_mut_ptr();
             std::ptr::copy(src_ptr, dst_ptr, count);
         }
+        if let Some(before) = before {
+            self.watch_transfer(dst, src, before, false);
+        }
     }
 
     #[allow(clippy::manual_swap)] //manual is 2x faster
     pub fn swap_mem(&mut self, dst: u16, src: u16, count: usize) {
-        let dst = dst as usize;
-        let src = src as usize;
+        let before = self.watch_before_transfer(dst, src, count);
+        let dst_idx = dst as usize;
+        let src_idx = src as usize;
+        self.log_mem(dst_idx, count, false);
+        self.log_mem(src_idx, count, false);
         for i in 0..(count) {
-            let tmp = self.memory[dst + i];
-            self.memory[dst + i] = self.memory[src + i];
-            self.memory[src + i] = tmp;
+            let tmp = self.memory[dst_idx + i];
+            self.memory[dst_idx + i] = self.memory[src_idx + i];
+            self.memory[src_idx + i] = tmp;
+        }
+        if let Some(before) = before {
+            self.watch_transfer(dst, src, before, true);
+        }
+    }
+
+    /// Copy of dst and src, only if watchpoints are set
+    #[inline(always)]
+    fn watch_before_transfer(
+        &self,
+        dst: u16,
+        src: u16,
+        count: usize,
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        if self.is_watching() {
+            let dst = dst as usize;
+            let src = src as usize;
+            Some((
+                self.memory[dst..dst + count].to_vec(),
+                self.memory[src..src + count].to_vec(),
+            ))
+        } else {
+            None
         }
     }
 
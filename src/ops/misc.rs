@@ -54,28 +54,78 @@ mod test {
     use super::*;
     use crate::ops::test::check_cycles;
     use maikor_platform::op_params::{INDIRECT, IND_OFFSET_EXT_REG};
+    use maikor_platform::ops;
     use maikor_platform::registers::id;
 
     #[test]
     fn test_costs() {
-        check_cycles(&[id::AL as u8, id::AH as u8], 1, VM::swap_byte);
-        check_cycles(&[id::AX as u8 | INDIRECT, id::AH as u8], 8, VM::swap_byte);
-        check_cycles(&[id::AL as u8, id::BX as u8 | INDIRECT], 8, VM::swap_byte);
         check_cycles(
-            &[id::AX as u8 | INDIRECT, id::BX as u8 | INDIRECT],
+            &[ops::SWAP_REG_REG_BYTE, id::AL as u8, id::AH as u8],
+            1,
+            VM::swap_byte,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_BYTE,
+                id::AX as u8 | INDIRECT,
+                id::AH as u8,
+            ],
+            8,
+            VM::swap_byte,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_BYTE,
+                id::AL as u8,
+                id::BX as u8 | INDIRECT,
+            ],
+            8,
+            VM::swap_byte,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_BYTE,
+                id::AX as u8 | INDIRECT,
+                id::BX as u8 | INDIRECT,
+            ],
             12,
             VM::swap_byte,
         );
-        check_cycles(&[id::AX as u8, id::BX as u8], 1, VM::swap_word);
-        check_cycles(&[id::AX as u8 | INDIRECT, id::BX as u8], 12, VM::swap_word);
-        check_cycles(&[id::AX as u8, id::BX as u8 | INDIRECT], 12, VM::swap_word);
         check_cycles(
-            &[id::AX as u8 | INDIRECT, id::BX as u8 | INDIRECT],
+            &[ops::SWAP_REG_REG_WORD, id::AX as u8, id::BX as u8],
+            1,
+            VM::swap_word,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_WORD,
+                id::AX as u8 | INDIRECT,
+                id::BX as u8,
+            ],
+            12,
+            VM::swap_word,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_WORD,
+                id::AX as u8,
+                id::BX as u8 | INDIRECT,
+            ],
+            12,
+            VM::swap_word,
+        );
+        check_cycles(
+            &[
+                ops::SWAP_REG_REG_WORD,
+                id::AX as u8 | INDIRECT,
+                id::BX as u8 | INDIRECT,
+            ],
             16,
             VM::swap_word,
         );
         check_cycles(
             &[
+                ops::SWAP_REG_REG_WORD,
                 id::AX as u8 | IND_OFFSET_EXT_REG,
                 id::BX as u8 | IND_OFFSET_EXT_REG,
                 id::CX as u8,
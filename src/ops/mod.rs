@@ -1,3 +1,4 @@
+use crate::decoder::{Instruction, Operand};
 use crate::register::Register;
 use crate::VM;
 
@@ -19,41 +20,58 @@ mod stack;
 
 // Argument reading
 impl VM {
-    fn next_byte(&mut self) -> u8 {
-        let byte = self.memory[self.arg_ptr as usize];
-        self.arg_ptr += 1;
-        byte
+    /// Store operands of `instruction` to be read by the op
+    fn load_args(&mut self, instruction: &Instruction) {
+        self.args[..instruction.operands().len()].copy_from_slice(instruction.operands());
+        self.arg_idx = 0;
+    }
+
+    fn next_arg(&mut self) -> Operand {
+        let arg = self.args[self.arg_idx];
+        self.arg_idx += 1;
+        arg
     }
 
     #[inline(always)]
     fn read_arg_byte(&mut self) -> u8 {
-        self.next_byte()
+        match self.next_arg() {
+            Operand::Byte(value) => value,
+            arg => unreachable!("expected byte operand, found {arg:?}"),
+        }
     }
 
     fn read_arg_word(&mut self) -> u16 {
-        let mut value = self.next_byte() as u16;
-        value <<= 8;
-        value += self.next_byte() as u16;
-        value
+        match self.next_arg() {
+            Operand::Word(value) | Operand::Addr(value) => value,
+            arg => unreachable!("expected word operand, found {arg:?}"),
+        }
     }
 
     #[inline(always)]
     fn read_arg_register(&mut self) -> Register {
-        Register::from(self.next_byte())
+        match self.next_arg() {
+            Operand::Register(reg) => Register::from_operand(reg),
+            arg => unreachable!("expected register operand, found {arg:?}"),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::decoder::{decode, MAX_LEN};
     use crate::VM;
-    use maikor_platform::mem::address::RESERVED;
 
-    pub fn check_cycles(bytes: &[u8], expected_cycles: usize, method: fn(&mut VM) -> usize) {
+    /// VM with operands of op in `bytes` loaded, missing operand bytes are treated as 0
+    pub fn vm_with_args(bytes: &[u8]) -> VM {
+        let mut padded = [0; MAX_LEN];
+        padded[..bytes.len()].copy_from_slice(bytes);
         let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        vm.load_args(&decode(&padded).unwrap());
+        vm
+    }
+
+    pub fn check_cycles(bytes: &[u8], expected_cycles: usize, method: fn(&mut VM) -> usize) {
+        let mut vm = vm_with_args(bytes);
         assert_eq!(method(&mut vm), expected_cycles)
     }
 
@@ -62,11 +80,7 @@ mod test {
         expected_cycles: usize,
         method: fn(&mut VM) -> (bool, usize),
     ) {
-        let mut vm = VM::new_test();
-        vm.arg_ptr = RESERVED;
-        for (i, byte) in bytes.iter().enumerate() {
-            vm.memory[RESERVED as usize + i] = *byte;
-        }
+        let mut vm = vm_with_args(bytes);
         assert_eq!(method(&mut vm).1, expected_cycles)
     }
 }
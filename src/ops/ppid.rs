@@ -1,21 +1,22 @@
+use crate::error::VmError;
 use crate::register::Register;
 use crate::VM;
 
 impl VM {
     /// Run Pre Inc/Dec on register
-    /// Also check and read bytes for index addressing
+    /// Also calculate offset for index addressing
     /// Returns address offset and cycle cost
     #[must_use]
     pub fn pre_process(&mut self, reg: &Register, op_size: u8) -> (i16, usize) {
         let process_cost = self.process_arg(reg, op_size, false);
         if reg.is_offset_num {
-            (self.read_arg_word() as i16, process_cost)
+            (reg.offset_num, process_cost)
         } else if reg.is_offset_reg {
-            let offset_reg = self.read_arg_register();
+            let offset_reg = Register::from(reg.offset_reg);
             let (num, cost) = self.read_byte_reg_value(&offset_reg);
             (num as i16, cost + process_cost)
         } else if reg.is_offset_ext_reg {
-            let offset_reg = self.read_arg_register();
+            let offset_reg = Register::from(reg.offset_reg);
             let (num, cost) = self.read_word_reg_value(&offset_reg);
             (num as i16, cost + process_cost)
         } else {
@@ -42,7 +43,10 @@ impl VM {
                         let (value, _) = self.read_word_reg_value(reg);
                         return self.write_word_reg_value(reg, value.wrapping_add(op_size as u16));
                     }
-                    _ => self.fail(format!("Invalid register size: {}", reg.addr)),
+                    _ => self.fail(VmError::InvalidRegister {
+                        value: reg.id(),
+                        pc: self.pc,
+                    }),
                 }
             } else {
                 match reg.size {
@@ -54,7 +58,10 @@ impl VM {
                         let (value, _) = self.read_word_reg_value(reg);
                         return self.write_word_reg_value(reg, value.wrapping_sub(op_size as u16));
                     }
-                    _ => self.fail(format!("Invalid register size: {}", reg.addr)),
+                    _ => self.fail(VmError::InvalidRegister {
+                        value: reg.id(),
+                        pc: self.pc,
+                    }),
                 }
             }
         }
@@ -1,3 +1,4 @@
+use crate::error::VmError;
 use crate::{address, VM};
 
 impl VM {
@@ -5,7 +6,19 @@ impl VM {
     /// true if execution can continue
     fn check_for_overflow(&mut self, room_needed: u16) -> bool {
         if self.get_sp() >= (65535 - room_needed) {
-            self.fail(String::from("Stack overflow"));
+            self.fail(VmError::StackOverflow { pc: self.pc });
+            false
+        } else {
+            true
+        }
+    }
+
+    /// calls fail() and returns false if popping `room_needed` bytes would move SP below the
+    /// start of the stack
+    /// true if execution can continue
+    fn check_for_underflow(&mut self, room_needed: u16) -> bool {
+        if self.get_sp() < address::STACK as u16 + room_needed {
+            self.fail(VmError::StackUnderflow { pc: self.pc });
             false
         } else {
             true
@@ -14,12 +27,14 @@ impl VM {
 
     fn update_sp(&mut self, diff: i16) {
         let sp = (self.get_sp() as i16 + diff).to_be_bytes();
+        self.log_mem(address::SP, 2, false);
         self.memory[address::SP] = sp[0];
         self.memory[address::SP + 1] = sp[1];
     }
 
     fn set_fp(&mut self, value: u16) {
         let bytes = value.to_be_bytes();
+        self.log_mem(address::FP, 2, false);
         self.memory[address::FP] = bytes[0];
         self.memory[address::FP + 1] = bytes[1];
     }
@@ -28,35 +43,46 @@ impl VM {
     fn write_to_stack(&mut self, value: u16) -> usize {
         let sp = self.get_sp() as usize;
         let bytes = value.to_be_bytes();
+        self.log_mem(sp, 2, false);
         self.memory[sp] = bytes[0];
         self.memory[sp + 1] = bytes[1];
         self.update_sp(2);
         2
     }
 
+    #[must_use]
+    fn write_byte_to_stack(&mut self, value: u8) -> usize {
+        let sp = self.get_sp() as usize;
+        self.log_mem(sp, 1, false);
+        self.memory[sp] = value;
+        self.update_sp(1);
+        1
+    }
+
     fn read_from_stack(&mut self) -> u16 {
         self.update_sp(-2);
         let sp = self.get_sp() as usize;
         u16::from_be_bytes([self.memory[sp], self.memory[sp + 1]])
     }
 
+    /// pushes FP and `target_pc` then sets FP to the new SP, so FP always points just after
+    /// the current frame (see [VM::call_stack()])
     /// calls fail() and returns false if the stack limited has been reached
     /// true if execution can continue
     fn setup_stack(&mut self, target_pc: u16) -> (bool, usize) {
         if self.check_for_overflow(4) {
-            (
-                false,
-                self.write_to_stack(self.get_fp()) + self.write_to_stack(target_pc),
-            )
+            let cost = self.write_to_stack(self.get_fp()) + self.write_to_stack(target_pc);
+            self.set_fp(self.get_sp());
+            (true, cost)
         } else {
-            (true, 0)
+            (false, 0)
         }
     }
 }
 
 impl VM {
     pub fn call_addr(&mut self) -> usize {
-        let (result, cost) = self.setup_stack(self.pc + 3);
+        let (result, cost) = self.setup_stack(self.arg_ptr);
         if result {
             self.pc = self.read_arg_word();
         }
@@ -67,7 +93,7 @@ impl VM {
         let reg = self.read_arg_register();
         let (offset, offset_calc) = self.pre_process(&reg, 2);
         let (addr, calc) = self.read_word_reg(&reg, offset);
-        let (result, cost) = self.setup_stack(self.pc + 2);
+        let (result, cost) = self.setup_stack(self.arg_ptr);
         if result {
             self.pc = addr;
         }
@@ -75,9 +101,17 @@ impl VM {
     }
 
     pub fn ret(&mut self) -> usize {
-        self.pc = self.read_from_stack();
-        let value = self.read_from_stack();
-        self.set_fp(value);
+        if !self.check_for_underflow(4) {
+            return 0;
+        }
+        let ret_pc = self.read_from_stack();
+        let fp = self.read_from_stack();
+        if self.strict_stack && fp != 0 && !(address::STACK as u16..=self.get_sp()).contains(&fp) {
+            self.fail(VmError::InvalidFrame { fp, pc: self.pc });
+            return 0;
+        }
+        self.pc = ret_pc;
+        self.set_fp(fp);
         3
     }
 
@@ -85,9 +119,8 @@ impl VM {
         let reg = self.read_arg_register();
         let (offset, offset_cost) = self.pre_process(&reg, 1);
         let (value, read_cost) = self.read_byte_reg(&reg, offset);
-        self.memory[address::SP] = value;
-        self.update_sp(1);
-        offset_cost + read_cost + self.post_process(&reg, 1) + 1
+        let write_cost = self.write_byte_to_stack(value);
+        write_cost + offset_cost + read_cost + self.post_process(&reg, 1)
     }
 
     pub fn push_reg_word(&mut self) -> usize {
@@ -99,9 +132,8 @@ impl VM {
     }
 
     pub fn push_num_byte(&mut self) -> usize {
-        self.memory[address::SP] = self.read_arg_byte();
-        self.update_sp(1);
-        1
+        let value = self.read_arg_byte();
+        self.write_byte_to_stack(value)
     }
 
     pub fn push_num_word(&mut self) -> usize {
@@ -111,6 +143,9 @@ impl VM {
 
     pub fn pop_reg_byte(&mut self) -> usize {
         let reg = self.read_arg_register();
+        if !self.check_for_underflow(1) {
+            return 0;
+        }
         let (offset, offset_cost) = self.pre_process(&reg, 1);
         self.update_sp(-1);
         let (addr, read_cost) = self.read_byte_mem(self.get_sp());
@@ -120,8 +155,11 @@ impl VM {
 
     pub fn pop_reg_word(&mut self) -> usize {
         let reg = self.read_arg_register();
+        if !self.check_for_underflow(2) {
+            return 0;
+        }
         let (offset, offset_cost) = self.pre_process(&reg, 2);
-        self.update_sp(-1);
+        self.update_sp(-2);
         let (addr, read_cost) = self.read_word_mem(self.get_sp());
         let write_cost = self.write_word_reg(&reg, offset, addr);
         self.post_process(&reg, 2) + offset_cost + read_cost + write_cost
@@ -130,20 +168,151 @@ impl VM {
 
 #[cfg(test)]
 mod test {
-    use crate::ops::test::check_cycles;
-    use crate::VM;
+    use crate::error::VmError;
+    use crate::ops::test::{check_cycles, vm_with_args};
+    use crate::{address, VM};
+    use maikor_platform::op_params::IND_OFFSET_NUM;
+    use maikor_platform::ops;
+    use maikor_platform::registers::id;
 
     #[test]
     fn test_cycles() {
-        check_cycles(&[0, 0], 4, VM::call_addr);
-        check_cycles(&[0, 0], 6, VM::call_reg);
-        check_cycles(&[0, 0], 1, VM::push_num_byte);
-        check_cycles(&[0, 0], 2, VM::push_num_word);
-        check_cycles(&[0, 0], 2, VM::push_reg_byte);
-        check_cycles(&[0, 0], 4, VM::push_reg_word);
-        check_cycles(&[0, 0], 2, VM::pop_reg_byte);
-        check_cycles(&[0, 0], 4, VM::pop_reg_word);
-        check_cycles(&[0, 0], 3, VM::ret);
+        check_cycles(&[ops::CALL_ADDR, 0, 0], 4, VM::call_addr);
+        check_cycles(&[ops::CALL_REG, 0, 0], 6, VM::call_reg);
+        check_cycles(&[ops::PUSH_NUM_BYTE, 0, 0], 1, VM::push_num_byte);
+        check_cycles(&[ops::PUSH_NUM_WORD, 0, 0], 2, VM::push_num_word);
+        check_cycles(&[ops::PUSH_REG_BYTE, 0, 0], 2, VM::push_reg_byte);
+        check_cycles(&[ops::PUSH_REG_WORD, 0, 0], 4, VM::push_reg_word);
+        check_pop_cycles(&[ops::POP_REG_BYTE, 0, 0], 2, VM::pop_reg_byte);
+        check_pop_cycles(&[ops::POP_REG_WORD, 0, 0], 4, VM::pop_reg_word);
+        check_pop_cycles(&[ops::RET, 0, 0], 3, VM::ret);
+    }
+
+    /// same as check_cycles but with something on the stack to pop
+    fn check_pop_cycles(bytes: &[u8], expected_cycles: usize, method: fn(&mut VM) -> usize) {
+        let mut vm = vm_with_args(bytes);
+        vm.update_sp(4);
+        assert_eq!(method(&mut vm), expected_cycles)
+    }
+
+    #[test]
+    fn underflow() {
+        for op in [ops::RET, ops::POP_REG_BYTE, ops::POP_REG_WORD] {
+            let mut vm = VM::new_test();
+            vm.debug_set_mem_range(0, &[ops::NOP, op, id::AL]);
+            vm.step();
+            vm.step();
+            assert!(vm.halted);
+            assert_eq!(vm.error, Some(VmError::StackUnderflow { pc: 1 }));
+            assert_eq!(vm.get_sp(), address::STACK as u16);
+        }
+
+        let mut vm = VM::new_test();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::PUSH_NUM_WORD, 0, 8,
+            ops::RET,
+            0, 0, 0, 0,
+            ops::RET,
+        ]);
+        vm.step();
+        vm.step();
+        assert_eq!(vm.error, Some(VmError::StackUnderflow { pc: 3 }));
+        assert_eq!(vm.pc, 3);
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut vm = VM::new_test();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CPY_REG_NUM_BYTE, id::AL as u8, 0x12,
+            ops::CPY_REG_NUM_WORD, id::CX as u8, 0x34, 0x56,
+            ops::PUSH_REG_BYTE, id::AL as u8,
+            ops::PUSH_REG_WORD, id::CX as u8,
+            ops::PUSH_NUM_BYTE, 0x78,
+            ops::POP_REG_BYTE, id::DL as u8,
+            ops::POP_REG_WORD, id::BX as u8,
+            ops::POP_REG_BYTE, id::AH as u8,
+            ops::HALT,
+        ]);
+        let sp = vm.get_sp();
+        while !vm.halted {
+            vm.step();
+        }
+        assert_eq!(vm.error, None);
+        assert_eq!(vm.registers[id::DL as usize], 0x78);
+        assert_eq!(vm.registers[id::BH as usize], 0x34);
+        assert_eq!(vm.registers[id::BL as usize], 0x56);
+        assert_eq!(vm.registers[id::AH as usize], 0x12);
+        assert_eq!(vm.get_sp(), sp);
+    }
+
+    #[test]
+    fn call_returns_after_operands() {
+        let mut vm = VM::new_test();
+        vm.registers[0] = 0x50;
+        vm.registers[1] = 0x00;
+        vm.debug_set_mem_range(0x5010, &[0, 0x20]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CALL_REG, id::AX as u8 | IND_OFFSET_NUM, 0, 16,
+            ops::CALL_ADDR, 0, 0x20,
+            ops::HALT,
+        ]);
+        vm.debug_set_mem(0x20, ops::RET);
+        vm.step();
+        assert_eq!(vm.pc, 0x20);
+        vm.step();
+        assert_eq!(vm.pc, 4);
+        vm.step();
+        vm.step();
+        assert_eq!(vm.pc, 7);
+        vm.step();
+        assert!(vm.halted);
+        assert_eq!(vm.error, None);
+    }
+
+    #[test]
+    fn strict_stack() {
+        #[rustfmt::skip]
+        let bad_frame = [
+            ops::PUSH_NUM_WORD, 0x12, 0x34,
+            ops::PUSH_NUM_WORD, 0, 10,
+            ops::RET,
+            0, 0, 0,
+            ops::HALT,
+        ];
+        let mut vm = VM::new_test();
+        vm.debug_set_mem_range(0, &bad_frame);
+        vm.step();
+        vm.step();
+        vm.step();
+        assert!(!vm.halted);
+        assert_eq!(vm.pc, 10);
+        assert_eq!(vm.get_fp(), 0x1234);
+
+        let mut vm = VM::new_test();
+        vm.strict_stack = true;
+        vm.debug_set_mem_range(0, &bad_frame);
+        vm.step();
+        vm.step();
+        vm.step();
+        assert_eq!(vm.error, Some(VmError::InvalidFrame { fp: 0x1234, pc: 6 }));
+
+        let mut vm = VM::new_test();
+        vm.strict_stack = true;
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CALL_ADDR, 0, 4,
+            ops::HALT,
+            ops::RET,
+        ]);
+        while !vm.halted {
+            vm.step();
+        }
+        assert_eq!(vm.error, None);
+        assert_eq!(vm.pc, 3);
     }
 
     #[test]
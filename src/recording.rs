@@ -0,0 +1,174 @@
+use std::io;
+use std::io::Write;
+
+/// Sample format used when writing a WAV file
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WavFormat {
+    /// 16 bit signed integer
+    Pcm16,
+    /// 32 bit float
+    Float32,
+}
+
+/// Stereo audio output by the VM, see [Sound::start_recording()](crate::sound::Sound::start_recording)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    sample_rate: u32,
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+impl Recording {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            left: vec![],
+            right: vec![],
+        }
+    }
+
+    pub(crate) fn push(&mut self, left: &[f32], right: &[f32]) {
+        self.left.extend_from_slice(left);
+        self.right.extend_from_slice(right);
+    }
+
+    pub(crate) fn push_silence(&mut self, count: usize) {
+        self.left.resize(self.left.len() + count, 0.0);
+        self.right.resize(self.right.len() + count, 0.0);
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn left(&self) -> &[f32] {
+        &self.left
+    }
+
+    pub fn right(&self) -> &[f32] {
+        &self.right
+    }
+
+    /// Number of samples per channel
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// Write recording as a stereo WAV file
+    pub fn write_wav<W: Write>(&self, writer: &mut W, format: WavFormat) -> io::Result<()> {
+        let (format_tag, bytes_per_sample): (u16, u32) = match format {
+            WavFormat::Pcm16 => (1, 2),
+            WavFormat::Float32 => (3, 4),
+        };
+        let block_align = bytes_per_sample * 2;
+        let data_len = self.len() as u32 * block_align;
+        //float files need the extended fmt chunk and a fact chunk
+        let (fmt_len, fact_len) = match format {
+            WavFormat::Pcm16 => (16, 0),
+            WavFormat::Float32 => (18, 12),
+        };
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(4 + 8 + fmt_len + fact_len + 8 + data_len).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&fmt_len.to_le_bytes())?;
+        writer.write_all(&format_tag.to_le_bytes())?;
+        writer.write_all(&2_u16.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate * block_align).to_le_bytes())?;
+        writer.write_all(&(block_align as u16).to_le_bytes())?;
+        writer.write_all(&(bytes_per_sample as u16 * 8).to_le_bytes())?;
+        if format == WavFormat::Float32 {
+            writer.write_all(&0_u16.to_le_bytes())?;
+            writer.write_all(b"fact")?;
+            writer.write_all(&4_u32.to_le_bytes())?;
+            writer.write_all(&(self.len() as u32).to_le_bytes())?;
+        }
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data_len.to_le_bytes())?;
+        for (left, right) in self.left.iter().zip(self.right.iter()) {
+            match format {
+                WavFormat::Pcm16 => {
+                    writer.write_all(&to_i16(*left).to_le_bytes())?;
+                    writer.write_all(&to_i16(*right).to_le_bytes())?;
+                }
+                WavFormat::Float32 => {
+                    writer.write_all(&left.to_le_bytes())?;
+                    writer.write_all(&right.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recording as a stereo WAV file
+    pub fn to_wav(&self, format: WavFormat) -> Vec<u8> {
+        let mut output = vec![];
+        self.write_wav(&mut output, format)
+            .expect("writing to vec can't fail");
+        output
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wav_pcm16() {
+        let mut recording = Recording::new(44100);
+        recording.push(&[0.0, 1.0], &[-1.0, 2.0]);
+        let wav = recording.to_wav(WavFormat::Pcm16);
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes([wav[4], wav[5], wav[6], wav[7]]), 44);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[20], wav[21]]), 1);
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            44100
+        );
+        assert_eq!(u16::from_le_bytes([wav[34], wav[35]]), 16);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]), 8);
+        assert_eq!(wav[44..], [0, 0, 0x01, 0x80, 0xFF, 0x7F, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn wav_float32() {
+        let mut recording = Recording::new(22050);
+        recording.push(&[0.5], &[-0.5]);
+        let wav = recording.to_wav(WavFormat::Float32);
+
+        assert_eq!(wav.len(), 58 + 8);
+        assert_eq!(u16::from_le_bytes([wav[20], wav[21]]), 3);
+        assert_eq!(u16::from_le_bytes([wav[34], wav[35]]), 32);
+        assert_eq!(&wav[38..42], b"fact");
+        assert_eq!(&wav[50..54], b"data");
+        assert_eq!(wav[58..62], 0.5_f32.to_le_bytes());
+        assert_eq!(wav[62..66], (-0.5_f32).to_le_bytes());
+    }
+
+    #[test]
+    fn silence() {
+        let mut recording = Recording::new(4);
+        recording.push(&[0.5], &[0.5]);
+        recording.push_silence(2);
+        assert_eq!(recording.len(), 3);
+        assert_eq!(recording.left(), &[0.5, 0.0, 0.0]);
+        assert_eq!(recording.right(), &[0.5, 0.0, 0.0]);
+    }
+}
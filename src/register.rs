@@ -1,3 +1,4 @@
+use crate::decoder::{AddressingMode, RegisterOperand};
 use maikor_platform::registers::id;
 
 pub mod offset {
@@ -21,6 +22,10 @@ pub struct Register {
     pub is_offset_reg: bool,
     pub is_offset_ext_reg: bool,
     pub is_offset_num: bool,
+    /// Offset if is_offset_num
+    pub offset_num: i16,
+    /// ID of offset register if is_offset_reg or is_offset_ext_reg
+    pub offset_reg: u8,
     pub size: usize,
     pub addr: usize,
 }
@@ -63,6 +68,29 @@ impl Register {
         }
     }
 
+    pub fn from_operand(operand: RegisterOperand) -> Register {
+        use maikor_platform::op_params::*;
+        let (mode, offset_num, offset_reg) = match operand.mode {
+            AddressingMode::Direct => (REGISTER, 0, 0),
+            AddressingMode::Indirect => (INDIRECT, 0, 0),
+            AddressingMode::PreInc => (PRE_INC, 0, 0),
+            AddressingMode::PreDec => (PRE_DEC, 0, 0),
+            AddressingMode::PostInc => (POST_INC, 0, 0),
+            AddressingMode::PostDec => (POST_DEC, 0, 0),
+            AddressingMode::IndirectPreInc => (IND_PRE_INC, 0, 0),
+            AddressingMode::IndirectPreDec => (IND_PRE_DEC, 0, 0),
+            AddressingMode::IndirectPostInc => (IND_POST_INC, 0, 0),
+            AddressingMode::IndirectPostDec => (IND_POST_DEC, 0, 0),
+            AddressingMode::OffsetReg(reg) => (IND_OFFSET_REG, 0, reg),
+            AddressingMode::OffsetExtReg(reg) => (IND_OFFSET_EXT_REG, 0, reg),
+            AddressingMode::OffsetNum(num) => (IND_OFFSET_NUM, num, 0),
+        };
+        let mut register = Register::from(operand.id | mode);
+        register.offset_num = offset_num;
+        register.offset_reg = offset_reg;
+        register
+    }
+
     pub fn id(&self) -> u8 {
         match (self.addr, self.size) {
             (offset::AH, 2) => id::AX,
@@ -90,6 +118,8 @@ impl Register {
             is_offset_reg: false,
             is_offset_ext_reg: false,
             is_offset_num: false,
+            offset_num: 0,
+            offset_reg: 0,
             size,
             addr,
         }
@@ -110,6 +140,8 @@ impl Register {
             is_offset_reg,
             is_offset_ext_reg,
             is_offset_num,
+            offset_num: 0,
+            offset_reg: 0,
             size,
             addr,
         }
@@ -131,6 +163,8 @@ impl Register {
             is_offset_reg: false,
             is_offset_ext_reg: false,
             is_offset_num: false,
+            offset_num: 0,
+            offset_reg: 0,
             size,
             addr,
         }
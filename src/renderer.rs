@@ -0,0 +1,401 @@
+//! Software renderer
+//!
+//! Draws the background layers and sprites in video memory into a buffer of RGBA pixels
+//! (`0xRRGGBBAA`), one `u32` per pixel, [SCREEN_WIDTH] x [SCREEN_HEIGHT]
+//!
+//! - Atlases are 200 pixels wide, each byte is two pixels (high nibble first) and each nibble
+//!   is a color index in a palette
+//! - Palettes are 16 colors of 3 bytes (RGB), 0,0,0 is transparent
+//! - Layers are 44x30 tiles and are drawn offset by their header X and Y, pixels outside the
+//!   layer are transparent
+//! - Sprites are 8x8 pixels, or 16x16 if large (tiles `id`, `id+1`, `id+25` and `id+26`)
+//! - Flips are applied before rotation, rotation is 90° clockwise
+//! - Half alpha pixels are mixed 50/50 with whatever is below them
+//!
+//! Drawing is in order from 0 to 3, for each order the layers with that order are drawn
+//! (layer 0 first) then the sprites with that order (sprite 0 first), so later pixels are on top
+//! Anywhere nothing is drawn is black
+
+use crate::{address, sizes, VM};
+use maikor_platform::constants::{
+    ATLAS_TILE_HEIGHT, ATLAS_TILE_WIDTH, LAYER_COUNT, SCREEN_HEIGHT, SCREEN_PIXELS, SCREEN_WIDTH,
+    SPRITE_COUNT, TILES_PER_ATLAS_ROW, TILES_PER_LAYER, TILES_PER_LAYER_COLUMN,
+    TILES_PER_LAYER_ROW, TILE_HEIGHT, TILE_WIDTH,
+};
+use maikor_platform::models::{Byteable, LayerHeader, LayerTile, Sprite};
+
+/// Number of `u32`s needed for a frame
+pub const BUFFER_SIZE: usize = SCREEN_PIXELS;
+/// Color used where nothing is drawn
+pub const BACKGROUND: u32 = 0x000000FF;
+
+const ORDER_COUNT: usize = 4;
+const ATLAS_WIDTH: usize = TILES_PER_ATLAS_ROW * ATLAS_TILE_WIDTH;
+const LAYER_WIDTH: isize = (TILES_PER_LAYER_ROW * TILE_WIDTH) as isize;
+const LAYER_HEIGHT: isize = (TILES_PER_LAYER_COLUMN * TILE_HEIGHT) as isize;
+const COLOR_SIZE: usize = 3;
+const PALETTE_SIZE: usize = 16 * COLOR_SIZE;
+
+/// Draw the whole screen into `buffer`
+///
+/// # Panics
+/// If `buffer` is smaller than [BUFFER_SIZE]
+pub fn render_frame(vm: &VM, buffer: &mut [u32]) {
+    for (y, line) in buffer[..BUFFER_SIZE]
+        .chunks_exact_mut(SCREEN_WIDTH)
+        .enumerate()
+    {
+        render_line(vm, y, line);
+    }
+}
+
+/// Draw line `y` of the screen into `line`
+///
+/// # Panics
+/// If `line` is smaller than [SCREEN_WIDTH] or `y` is not less than [SCREEN_HEIGHT]
+pub fn render_line(vm: &VM, y: usize, line: &mut [u32]) {
+    assert!(y < SCREEN_HEIGHT, "line {y} is off screen");
+    let line = &mut line[..SCREEN_WIDTH];
+    line.fill(BACKGROUND);
+    let layers: Vec<LayerHeader> = (0..LAYER_COUNT)
+        .map(|i| LayerHeader::from_bytes(&vm.memory[address::LAYER_HEADERS + i * 3..]))
+        .collect();
+    let sprites: Vec<Sprite> = (0..SPRITE_COUNT)
+        .map(|i| Sprite::from_bytes(&vm.memory[address::SPRITE_TABLE + i * Sprite::SIZE..]))
+        .filter(|sprite| sprite.enabled && sprite_contains_line(sprite, y))
+        .collect();
+    for order in 0..ORDER_COUNT {
+        for (i, header) in layers.iter().enumerate() {
+            if header.enabled && header.order == order {
+                draw_layer_line(vm, i, header, y, line);
+            }
+        }
+        for sprite in sprites.iter().filter(|sprite| sprite.order == order) {
+            draw_sprite_line(vm, sprite, y, line);
+        }
+    }
+}
+
+fn sprite_size(sprite: &Sprite) -> usize {
+    if sprite.large {
+        TILE_WIDTH * 2
+    } else {
+        TILE_WIDTH
+    }
+}
+
+fn sprite_contains_line(sprite: &Sprite, y: usize) -> bool {
+    y >= sprite.y && y < sprite.y + sprite_size(sprite)
+}
+
+fn draw_layer_line(vm: &VM, layer: usize, header: &LayerHeader, y: usize, line: &mut [u32]) {
+    let ly = y as isize + header.y;
+    if !(0..LAYER_HEIGHT).contains(&ly) {
+        return;
+    }
+    let ly = ly as usize;
+    let content = address::LAYERS + layer * TILES_PER_LAYER * LayerTile::SIZE;
+    for (x, pixel) in line.iter_mut().enumerate() {
+        let lx = x as isize + header.x;
+        if !(0..LAYER_WIDTH).contains(&lx) {
+            continue;
+        }
+        let lx = lx as usize;
+        let idx = (ly / TILE_HEIGHT) * TILES_PER_LAYER_ROW + lx / TILE_WIDTH;
+        let tile = LayerTile::from_bytes(&vm.memory[content + idx * LayerTile::SIZE..]);
+        let (sx, sy) = transform(
+            lx % TILE_WIDTH,
+            ly % TILE_HEIGHT,
+            TILE_WIDTH,
+            tile.flip_h,
+            tile.flip_v,
+            tile.rotated,
+        );
+        if let Some(color) = atlas_color(vm, header.atlas, tile.id, sx, sy, tile.palette) {
+            *pixel = blend(*pixel, color, tile.half_alpha);
+        }
+    }
+}
+
+fn draw_sprite_line(vm: &VM, sprite: &Sprite, y: usize, line: &mut [u32]) {
+    let size = sprite_size(sprite);
+    let py = y - sprite.y;
+    for px in 0..size {
+        let x = sprite.x + px;
+        if x >= SCREEN_WIDTH {
+            break;
+        }
+        let (sx, sy) = transform(px, py, size, sprite.flip_h, sprite.flip_v, sprite.rotated);
+        let tile = sprite.id + (sy / TILE_HEIGHT) * TILES_PER_ATLAS_ROW + sx / TILE_WIDTH;
+        let color = atlas_color(
+            vm,
+            sprite.atlas,
+            tile,
+            sx % TILE_WIDTH,
+            sy % TILE_HEIGHT,
+            sprite.palette,
+        );
+        if let Some(color) = color {
+            line[x] = blend(line[x], color, sprite.half_alpha);
+        }
+    }
+}
+
+/// Convert position in the drawn image to position in the source image
+fn transform(
+    x: usize,
+    y: usize,
+    size: usize,
+    flip_h: bool,
+    flip_v: bool,
+    rotated: bool,
+) -> (usize, usize) {
+    let x = if flip_h { size - 1 - x } else { x };
+    let y = if flip_v { size - 1 - y } else { y };
+    if rotated {
+        (y, size - 1 - x)
+    } else {
+        (x, y)
+    }
+}
+
+/// Returns the RGBA color of pixel `x`,`y` of `tile` in `atlas`
+/// or None if it's transparent or the tile is outside the atlas
+pub(crate) fn atlas_color(
+    vm: &VM,
+    atlas: usize,
+    tile: usize,
+    x: usize,
+    y: usize,
+    palette: usize,
+) -> Option<u32> {
+    let index = atlas_index(vm, atlas, tile, x, y)?;
+    palette_color(vm, palette, index as usize)
+}
+
+/// Returns the palette color index of pixel `x`,`y` of `tile` in `atlas`
+/// or None if the tile is outside the atlas
+pub(crate) fn atlas_index(vm: &VM, atlas: usize, tile: usize, x: usize, y: usize) -> Option<u8> {
+    let tile_x = tile % TILES_PER_ATLAS_ROW;
+    let tile_y = tile / TILES_PER_ATLAS_ROW;
+    let offset = (tile_y * ATLAS_TILE_HEIGHT + y) * ATLAS_WIDTH + tile_x * ATLAS_TILE_WIDTH + x / 2;
+    if offset >= sizes::ATLAS {
+        return None;
+    }
+    let byte = vm.memory[atlas_address(atlas) + offset];
+    Some(if x & 1 == 0 { byte >> 4 } else { byte & 0x0F })
+}
+
+/// Returns the RGBA color of `index` in `palette`
+/// or None if it's transparent
+pub(crate) fn palette_color(vm: &VM, palette: usize, index: usize) -> Option<u32> {
+    let addr = address::PALETTES + palette * PALETTE_SIZE + index * COLOR_SIZE;
+    let [r, g, b] = [vm.memory[addr], vm.memory[addr + 1], vm.memory[addr + 2]];
+    if r == 0 && g == 0 && b == 0 {
+        None
+    } else {
+        Some(u32::from_be_bytes([r, g, b, 0xFF]))
+    }
+}
+
+pub(crate) fn atlas_address(atlas: usize) -> usize {
+    match atlas {
+        0 => address::ATLAS1,
+        1 => address::ATLAS2,
+        2 => address::ATLAS3,
+        _ => address::ATLAS4,
+    }
+}
+
+fn blend(below: u32, above: u32, half_alpha: bool) -> u32 {
+    if !half_alpha {
+        return above;
+    }
+    let below = below.to_be_bytes();
+    let above = above.to_be_bytes();
+    u32::from_be_bytes([
+        ((below[0] as u16 + above[0] as u16) / 2) as u8,
+        ((below[1] as u16 + above[1] as u16) / 2) as u8,
+        ((below[2] as u16 + above[2] as u16) / 2) as u8,
+        0xFF,
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RED: u32 = 0xFF0000FF;
+    const GREEN: u32 = 0x00FF00FF;
+    const BLUE: u32 = 0x0000FFFF;
+
+    /// palette 0 is 1 = red, 2 = green, 3 = blue
+    /// atlas 0 tile 0 has a red top left pixel and the rest is green
+    /// atlas 0 tile 1 is blue
+    fn setup() -> VM {
+        let mut vm = VM::new_test();
+        vm.memory[address::PALETTES + 3..address::PALETTES + 12]
+            .copy_from_slice(&[255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        for y in 0..8 {
+            let row = address::ATLAS1 + y * ATLAS_WIDTH;
+            vm.memory[row..row + 4].fill(0x22);
+            vm.memory[row + 4..row + 8].fill(0x33);
+        }
+        vm.memory[address::ATLAS1] = 0x12;
+        vm
+    }
+
+    fn set_sprite(vm: &mut VM, idx: usize, sprite: Sprite) {
+        let addr = address::SPRITE_TABLE + idx * Sprite::SIZE;
+        vm.memory[addr..addr + Sprite::SIZE].copy_from_slice(&sprite.to_bytes());
+    }
+
+    fn sprite(x: usize, y: usize, id: usize) -> Sprite {
+        Sprite {
+            x,
+            y,
+            id,
+            enabled: true,
+            ..Sprite::default()
+        }
+    }
+
+    fn pixel(buffer: &[u32], x: usize, y: usize) -> u32 {
+        buffer[y * SCREEN_WIDTH + x]
+    }
+
+    #[test]
+    fn empty() {
+        let vm = VM::new_test();
+        let mut buffer = vec![0; BUFFER_SIZE];
+        render_frame(&vm, &mut buffer);
+        assert!(buffer.iter().all(|&pixel| pixel == BACKGROUND));
+    }
+
+    #[test]
+    fn sprites() {
+        let mut vm = setup();
+        set_sprite(&mut vm, 0, sprite(10, 20, 0));
+        set_sprite(
+            &mut vm,
+            1,
+            Sprite {
+                flip_h: true,
+                ..sprite(30, 20, 0)
+            },
+        );
+        set_sprite(
+            &mut vm,
+            2,
+            Sprite {
+                rotated: true,
+                ..sprite(50, 20, 0)
+            },
+        );
+        set_sprite(
+            &mut vm,
+            3,
+            Sprite {
+                enabled: false,
+                ..sprite(70, 20, 1)
+            },
+        );
+        let mut buffer = vec![0; BUFFER_SIZE];
+        render_frame(&vm, &mut buffer);
+
+        assert_eq!(pixel(&buffer, 10, 20), RED);
+        assert_eq!(pixel(&buffer, 11, 20), GREEN);
+        assert_eq!(pixel(&buffer, 17, 27), GREEN);
+        assert_eq!(pixel(&buffer, 18, 20), BACKGROUND);
+        assert_eq!(pixel(&buffer, 10, 28), BACKGROUND);
+
+        assert_eq!(pixel(&buffer, 37, 20), RED);
+        assert_eq!(pixel(&buffer, 30, 20), GREEN);
+
+        assert_eq!(pixel(&buffer, 57, 20), RED);
+        assert_eq!(pixel(&buffer, 50, 20), GREEN);
+
+        assert_eq!(pixel(&buffer, 70, 20), BACKGROUND);
+    }
+
+    #[test]
+    fn large_sprite() {
+        let mut vm = setup();
+        set_sprite(
+            &mut vm,
+            0,
+            Sprite {
+                large: true,
+                ..sprite(0, 0, 0)
+            },
+        );
+        let mut buffer = vec![0; BUFFER_SIZE];
+        render_frame(&vm, &mut buffer);
+        assert_eq!(pixel(&buffer, 0, 0), RED);
+        assert_eq!(pixel(&buffer, 8, 0), BLUE);
+        //tiles 25 and 26 are empty
+        assert_eq!(pixel(&buffer, 0, 8), BACKGROUND);
+        assert_eq!(pixel(&buffer, 16, 0), BACKGROUND);
+    }
+
+    #[test]
+    fn layers_and_order() {
+        let mut vm = setup();
+        //layer 0 is all tile 1 (blue) scrolled 4 pixels left, order 1
+        vm.memory[address::LAYER_HEADERS..address::LAYER_HEADERS + 3].copy_from_slice(
+            &LayerHeader {
+                x: 4,
+                y: 0,
+                enabled: true,
+                atlas: 0,
+                order: 1,
+            }
+            .to_bytes(),
+        );
+        for tile in 0..TILES_PER_LAYER {
+            vm.memory[address::LAYERS + tile * 2] = 1;
+        }
+        //layer 1 has tile 0 at the top left, order 0
+        vm.memory[address::LAYER_HEADERS + 3..address::LAYER_HEADERS + 6].copy_from_slice(
+            &LayerHeader {
+                x: -8,
+                y: -8,
+                enabled: true,
+                atlas: 0,
+                order: 0,
+            }
+            .to_bytes(),
+        );
+        set_sprite(&mut vm, 0, sprite(0, 0, 0));
+        set_sprite(
+            &mut vm,
+            1,
+            Sprite {
+                order: 1,
+                half_alpha: true,
+                ..sprite(100, 0, 0)
+            },
+        );
+
+        let mut buffer = vec![0; BUFFER_SIZE];
+        render_frame(&vm, &mut buffer);
+        //layer 0 covers layer 1 and sprite 0
+        assert_eq!(pixel(&buffer, 0, 0), BLUE);
+        assert_eq!(pixel(&buffer, 8, 8), BLUE);
+        assert_eq!(pixel(&buffer, 100, 0), 0x7F007FFF);
+        assert_eq!(pixel(&buffer, 101, 0), 0x007F7FFF);
+
+        //layer 0 scrolled past the right edge of the screen
+        vm.memory[address::LAYER_HEADERS] = 127;
+        render_frame(&vm, &mut buffer);
+        assert_eq!(pixel(&buffer, 200, 0), BLUE);
+        assert_eq!(pixel(&buffer, 230, 0), BACKGROUND);
+
+        //without layer 0
+        vm.memory[address::LAYER_HEADERS + 2] = 0;
+        render_frame(&vm, &mut buffer);
+        assert_eq!(pixel(&buffer, 0, 0), RED);
+        assert_eq!(pixel(&buffer, 8, 8), RED);
+        assert_eq!(pixel(&buffer, 9, 8), GREEN);
+    }
+}
@@ -0,0 +1,717 @@
+//! Save state file format
+//!
+//! All numbers are big endian
+//!
+//! | Size | Content                                   |
+//! |------|-------------------------------------------|
+//! | 4    | Magic (`MKSS`)                            |
+//! | 1    | Format version                            |
+//! | 2+n  | Game ID (length then UTF-8)               |
+//! | 4    | Game hash (CRC32 of ID, build and code)   |
+//! | ...  | Chunks                                    |
+//! | 4    | CRC32 of everything before this           |
+//!
+//! Each chunk is
+//!
+//! | Size | Content                                   |
+//! |------|-------------------------------------------|
+//! | 1    | Chunk ID                                  |
+//! | 1    | Compression (0 = none, 1 = RLE)           |
+//! | 4    | Uncompressed length                       |
+//! | 4    | Stored length                             |
+//! | n    | Data                                      |
+//!
+//! Unknown chunks are skipped, all known chunks are required
+
+use crate::error::VmError;
+use crate::input::InputState;
+use crate::snapshot::Snapshot;
+use crate::sound::SoundSnapshot;
+use crate::timer::{Timers, TIMER_COUNT};
+use crate::{sizes, VM};
+use maikor_platform::constants::SAVE_COUNT;
+use maikor_platform::registers;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+const MAGIC: [u8; 4] = *b"MKSS";
+/// Must be changed whenever the layout of any chunk changes in a released version
+///
+/// The format is new in 0.1.16 so all changes made before that is released share version 1
+pub const FORMAT_VERSION: u8 = 1;
+
+mod chunk {
+    pub const CPU: u8 = 1;
+    pub const MEMORY: u8 = 2;
+    pub const RAM_BANKS: u8 = 3;
+    pub const SAVE_BANKS: u8 = 4;
+    pub const SOUND: u8 = 5;
+    pub const TIMERS: u8 = 6;
+    pub const INPUT: u8 = 7;
+}
+
+mod compression {
+    pub const NONE: u8 = 0;
+    pub const RLE: u8 = 1;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SaveStateError {
+    /// Data is not a save state, or is truncated/malformed
+    InvalidFormat(&'static str),
+    /// Save state was made by a VM using a different format version
+    UnsupportedVersion(u8),
+    /// Data has been corrupted
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// Save state was made for a different game (or a different build of the same game)
+    DifferentGame {
+        expected_id: String,
+        expected_hash: u32,
+        found_id: String,
+        found_hash: u32,
+    },
+    /// Number of RAM or save banks doesn't match the loaded game
+    BankCountMismatch {
+        kind: &'static str,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for SaveStateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveStateError::InvalidFormat(reason) => write!(f, "Invalid save state: {reason}"),
+            SaveStateError::UnsupportedVersion(ver) => write!(
+                f,
+                "Unsupported save state version, was {ver} and must be {FORMAT_VERSION}"
+            ),
+            SaveStateError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Save state is corrupt, checksum was {actual:08X} and should be {expected:08X}"
+            ),
+            SaveStateError::DifferentGame {
+                expected_id,
+                expected_hash,
+                found_id,
+                found_hash,
+            } => write!(
+                f,
+                "Save state is for {found_id} ({found_hash:08X}) but {expected_id} ({expected_hash:08X}) is loaded"
+            ),
+            SaveStateError::BankCountMismatch {
+                kind,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Save state has {found} {kind} banks but game has {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for SaveStateError {}
+
+impl VM {
+    /// Write the current state in the save state format
+    /// If `compress` is true then memory and banks are RLE compressed
+    pub fn save_state(&self, compress: bool) -> Vec<u8> {
+        let snapshot = self.snapshot();
+        let mut writer = ByteWriter::default();
+        writer.write_bytes(&MAGIC);
+        writer.write_u8(FORMAT_VERSION);
+        writer.write_len_string(&self.game_id);
+        writer.write_u32(self.game_hash);
+
+        let mut cpu = ByteWriter::default();
+        cpu.write_bytes(&snapshot.registers);
+        cpu.write_u16(snapshot.pc);
+        cpu.write_u16(snapshot.arg_ptr);
+        cpu.write_bool(snapshot.halted);
+        cpu.write_u64(snapshot.op_executed as u64);
+        cpu.write_u64(snapshot.cycles_executed as u64);
+        cpu.write_u32(snapshot.line_cycles as u32);
+        match &snapshot.error {
+            None => cpu.write_bool(false),
+            Some(error) => {
+                cpu.write_bool(true);
+                error.write(&mut cpu);
+            }
+        }
+        writer.write_chunk(chunk::CPU, &cpu.bytes, false);
+
+        writer.write_chunk(chunk::MEMORY, &snapshot.memory, compress);
+
+        let mut ram = ByteWriter::default();
+        ram.write_u8(snapshot.ram_banks.len() as u8);
+        for bank in &snapshot.ram_banks {
+            ram.write_bytes(bank);
+        }
+        writer.write_chunk(chunk::RAM_BANKS, &ram.bytes, compress);
+
+        let mut save = ByteWriter::default();
+        save.write_u8(snapshot.save_banks.len() as u8);
+        for bank in &snapshot.save_banks {
+            save.write_bytes(bank);
+        }
+        for flag in snapshot.save_dirty_flag {
+            save.write_bool(flag);
+        }
+        writer.write_chunk(chunk::SAVE_BANKS, &save.bytes, compress);
+
+        let mut sound = ByteWriter::default();
+        snapshot.sound.write(&mut sound);
+        writer.write_chunk(chunk::SOUND, &sound.bytes, false);
+
+        let mut timers = ByteWriter::default();
+        timers.write_bytes(&snapshot.timers.reload);
+        for elapsed in snapshot.timers.elapsed {
+            timers.write_u32(elapsed);
+        }
+        writer.write_chunk(chunk::TIMERS, &timers.bytes, false);
+
+        let mut input = ByteWriter::default();
+        input.write_bytes(&snapshot.input.to_bytes());
+        input.write_bytes(&snapshot.input_latch.to_bytes());
+        writer.write_chunk(chunk::INPUT, &input.bytes, false);
+
+        let checksum = crc32(&writer.bytes);
+        writer.write_u32(checksum);
+        writer.bytes
+    }
+
+    /// Replace the current state with one from [VM::save_state()]
+    /// The state is fully validated before any changes are made, if this fails the VM is unchanged
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), SaveStateError> {
+        if bytes.len() < MAGIC.len() + 1 + 4 || bytes[..MAGIC.len()] != MAGIC {
+            return Err(SaveStateError::InvalidFormat("not a save state"));
+        }
+        if bytes[MAGIC.len()] != FORMAT_VERSION {
+            return Err(SaveStateError::UnsupportedVersion(bytes[MAGIC.len()]));
+        }
+        let (content, checksum) = bytes.split_at(bytes.len() - 4);
+        let expected = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let actual = crc32(content);
+        if expected != actual {
+            return Err(SaveStateError::ChecksumMismatch { expected, actual });
+        }
+
+        let mut reader = ByteReader::new(&content[MAGIC.len() + 1..]);
+        let found_id = reader.read_len_string()?;
+        let found_hash = reader.read_u32()?;
+        if found_id != self.game_id || found_hash != self.game_hash {
+            return Err(SaveStateError::DifferentGame {
+                expected_id: self.game_id.clone(),
+                expected_hash: self.game_hash,
+                found_id,
+                found_hash,
+            });
+        }
+
+        let mut cpu = None;
+        let mut memory = None;
+        let mut ram_banks = None;
+        let mut save_banks = None;
+        let mut sound = None;
+        let mut timers = None;
+        let mut input = None;
+        while !reader.is_empty() {
+            let (id, data) = reader.read_chunk()?;
+            let mut chunk_reader = ByteReader::new(&data);
+            match id {
+                chunk::CPU => cpu = Some(read_cpu(&mut chunk_reader)?),
+                chunk::MEMORY => {
+                    let mut mem = [0; sizes::TOTAL];
+                    mem.copy_from_slice(chunk_reader.read_bytes(sizes::TOTAL)?);
+                    memory = Some(mem);
+                }
+                chunk::RAM_BANKS => {
+                    let count = chunk_reader.read_u8()? as usize;
+                    let mut banks = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        let mut bank = [0; sizes::RAM_BANK];
+                        bank.copy_from_slice(chunk_reader.read_bytes(sizes::RAM_BANK)?);
+                        banks.push(bank);
+                    }
+                    ram_banks = Some(banks);
+                }
+                chunk::SAVE_BANKS => {
+                    let count = chunk_reader.read_u8()? as usize;
+                    let mut banks = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        let mut bank = [0; sizes::SAVE_BANK];
+                        bank.copy_from_slice(chunk_reader.read_bytes(sizes::SAVE_BANK)?);
+                        banks.push(bank);
+                    }
+                    let mut flags = [false; SAVE_COUNT];
+                    for flag in flags.iter_mut() {
+                        *flag = chunk_reader.read_bool()?;
+                    }
+                    save_banks = Some((banks, flags));
+                }
+                chunk::SOUND => sound = Some(SoundSnapshot::read(&mut chunk_reader)?),
+                chunk::TIMERS => {
+                    let mut state = Timers::default();
+                    state
+                        .reload
+                        .copy_from_slice(chunk_reader.read_bytes(TIMER_COUNT)?);
+                    for elapsed in state.elapsed.iter_mut() {
+                        *elapsed = chunk_reader.read_u32()?;
+                    }
+                    timers = Some(state);
+                }
+                chunk::INPUT => {
+                    let mut states = [InputState::default(); 2];
+                    for state in states.iter_mut() {
+                        state.direction = chunk_reader.read_u8()?;
+                        state.action = chunk_reader.read_u8()?;
+                    }
+                    input = Some(states);
+                }
+                _ => continue,
+            }
+            if !chunk_reader.is_empty() {
+                return Err(SaveStateError::InvalidFormat("chunk has trailing data"));
+            }
+        }
+
+        let missing = SaveStateError::InvalidFormat("missing chunk");
+        let cpu = cpu.ok_or_else(|| missing.clone())?;
+        let memory = memory.ok_or_else(|| missing.clone())?;
+        let ram_banks = ram_banks.ok_or_else(|| missing.clone())?;
+        let (save_banks, save_dirty_flag) = save_banks.ok_or_else(|| missing.clone())?;
+        let sound = sound.ok_or_else(|| missing.clone())?;
+        let timers = timers.ok_or_else(|| missing.clone())?;
+        let [input, input_latch] = input.ok_or(missing)?;
+
+        if ram_banks.len() != self.ram_banks.len() {
+            return Err(SaveStateError::BankCountMismatch {
+                kind: "RAM",
+                expected: self.ram_banks.len(),
+                found: ram_banks.len(),
+            });
+        }
+        if save_banks.len() != self.save_banks.len() {
+            return Err(SaveStateError::BankCountMismatch {
+                kind: "save",
+                expected: self.save_banks.len(),
+                found: save_banks.len(),
+            });
+        }
+
+        self.restore(&Snapshot {
+            registers: cpu.registers,
+            pc: cpu.pc,
+            memory,
+            ram_banks,
+            save_banks,
+            save_dirty_flag,
+            error: cpu.error,
+            halted: cpu.halted,
+            op_executed: cpu.op_executed,
+            cycles_executed: cpu.cycles_executed,
+            arg_ptr: cpu.arg_ptr,
+            line_cycles: cpu.line_cycles,
+            input,
+            input_latch,
+            timers,
+            sound,
+        });
+        Ok(())
+    }
+}
+
+struct CpuState {
+    registers: [u8; registers::SIZE],
+    pc: u16,
+    arg_ptr: u16,
+    halted: bool,
+    op_executed: usize,
+    cycles_executed: usize,
+    line_cycles: usize,
+    error: Option<VmError>,
+}
+
+fn read_cpu(reader: &mut ByteReader) -> Result<CpuState, SaveStateError> {
+    let mut registers = [0; registers::SIZE];
+    registers.copy_from_slice(reader.read_bytes(registers::SIZE)?);
+    let pc = reader.read_u16()?;
+    let arg_ptr = reader.read_u16()?;
+    let halted = reader.read_bool()?;
+    let op_executed = reader.read_u64()? as usize;
+    let cycles_executed = reader.read_u64()? as usize;
+    let line_cycles = reader.read_u32()? as usize;
+    let error = if reader.read_bool()? {
+        Some(VmError::read(reader)?)
+    } else {
+        None
+    };
+    Ok(CpuState {
+        registers,
+        pc,
+        arg_ptr,
+        halted,
+        op_executed,
+        cycles_executed,
+        line_cycles,
+        error,
+    })
+}
+
+/// Hash used to check a save state belongs to the loaded game
+pub(crate) fn game_hash(id: &str, build: u32, code: &[&[u8]]) -> u32 {
+    let mut bytes = id.as_bytes().to_vec();
+    bytes.extend_from_slice(&build.to_be_bytes());
+    for block in code {
+        bytes.extend_from_slice(block);
+    }
+    crc32(&bytes)
+}
+
+#[derive(Default)]
+pub(crate) struct ByteWriter {
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub(crate) fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub(crate) fn write_bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub(crate) fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn write_i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn write_bytes(&mut self, value: &[u8]) {
+        self.bytes.extend_from_slice(value);
+    }
+
+    fn write_len_string(&mut self, value: &str) {
+        let bytes = &value.as_bytes()[..value.len().min(u16::MAX as usize)];
+        self.write_u16(bytes.len() as u16);
+        self.write_bytes(bytes);
+    }
+
+    fn write_chunk(&mut self, id: u8, data: &[u8], compress: bool) {
+        self.write_u8(id);
+        if compress {
+            let encoded = rle_encode(data);
+            self.write_u8(compression::RLE);
+            self.write_u32(data.len() as u32);
+            self.write_u32(encoded.len() as u32);
+            self.write_bytes(&encoded);
+        } else {
+            self.write_u8(compression::NONE);
+            self.write_u32(data.len() as u32);
+            self.write_u32(data.len() as u32);
+            self.write_bytes(data);
+        }
+    }
+}
+
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], SaveStateError> {
+        if self.pos + len > self.bytes.len() {
+            return Err(SaveStateError::InvalidFormat("unexpected end of data"));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_bool(&mut self) -> Result<bool, SaveStateError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SaveStateError::InvalidFormat("invalid bool")),
+        }
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Result<u16, SaveStateError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, SaveStateError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, SaveStateError> {
+        let mut value = [0; 8];
+        value.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_be_bytes(value))
+    }
+
+    pub(crate) fn read_i32(&mut self) -> Result<i32, SaveStateError> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    fn read_len_string(&mut self) -> Result<String, SaveStateError> {
+        let len = self.read_u16()? as usize;
+        let bytes = self.read_bytes(len)?;
+        Ok(String::from_utf8_lossy(bytes).to_string())
+    }
+
+    fn read_chunk(&mut self) -> Result<(u8, Vec<u8>), SaveStateError> {
+        let id = self.read_u8()?;
+        let compression = self.read_u8()?;
+        let len = self.read_u32()? as usize;
+        let stored_len = self.read_u32()? as usize;
+        let data = self.read_bytes(stored_len)?;
+        let data = match compression {
+            compression::NONE => data.to_vec(),
+            compression::RLE => rle_decode(data)?,
+            _ => return Err(SaveStateError::InvalidFormat("unknown compression")),
+        };
+        if data.len() != len {
+            return Err(SaveStateError::InvalidFormat("chunk length mismatch"));
+        }
+        Ok((id, data))
+    }
+}
+
+/// PackBits style RLE
+/// Control byte `n`:
+///   0..=127   - copy the next `n + 1` bytes
+///   128..=255 - repeat the next byte `n - 126` times
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() / 4);
+    let mut i = 0;
+    while i < data.len() {
+        let mut run = 1;
+        while i + run < data.len() && run < 129 && data[i + run] == data[i] {
+            run += 1;
+        }
+        if run >= 2 {
+            output.push((run + 126) as u8);
+            output.push(data[i]);
+            i += run;
+        } else {
+            let start = i;
+            while i < data.len()
+                && i - start < 128
+                && !(i + 1 < data.len() && data[i] == data[i + 1])
+            {
+                i += 1;
+            }
+            output.push((i - start - 1) as u8);
+            output.extend_from_slice(&data[start..i]);
+        }
+    }
+    output
+}
+
+fn rle_decode(data: &[u8]) -> Result<Vec<u8>, SaveStateError> {
+    let mut output = Vec::with_capacity(data.len() * 4);
+    let mut i = 0;
+    while i < data.len() {
+        let control = data[i] as usize;
+        i += 1;
+        if control < 128 {
+            let len = control + 1;
+            if i + len > data.len() {
+                return Err(SaveStateError::InvalidFormat("invalid compressed data"));
+            }
+            output.extend_from_slice(&data[i..i + len]);
+            i += len;
+        } else {
+            if i >= data.len() {
+                return Err(SaveStateError::InvalidFormat("invalid compressed data"));
+            }
+            output.resize(output.len() + control - 126, data[i]);
+            i += 1;
+        }
+    }
+    Ok(output)
+}
+
+/// CRC-32 (IEEE)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFF_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::address;
+    use crate::error::BankKind;
+    use maikor_platform::ops::{ADD_REG_NUM_BYTE, INC_REG_BYTE, JMP_ADDR};
+    use maikor_platform::registers::id;
+
+    fn setup_vm() -> VM {
+        let mut vm = VM::new_test();
+        vm.ram_banks = vec![[0; sizes::RAM_BANK]; 2];
+        vm.save_banks = vec![[0; sizes::SAVE_BANK]; 3];
+        vm.game_id = String::from("app.maikor.test");
+        vm.game_hash = 1234;
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            INC_REG_BYTE, id::AL as u8,
+            ADD_REG_NUM_BYTE, id::BL as u8, 3,
+            JMP_ADDR, 0, 0,
+        ]);
+        vm
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let inputs: [&[u8]; 6] = [
+            &[],
+            &[1],
+            &[1, 1],
+            &[1, 2, 3, 3, 3, 3, 4, 5, 5, 6],
+            &[0; 1000],
+            &(0..=255).chain(0..=255).collect::<Vec<u8>>(),
+        ];
+        for input in inputs {
+            assert_eq!(rle_decode(&rle_encode(input)).unwrap(), input);
+        }
+        assert!(rle_encode(&[0; 1000]).len() < 20);
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn round_trip() {
+        for compress in [false, true] {
+            let mut vm = setup_vm();
+            vm.debug_set_mem(address::SAVE_BANK as u16, 9);
+            for _ in 0..10 {
+                vm.step();
+            }
+            let state = vm.save_state(compress);
+            let registers = vm.registers;
+            let pc = vm.pc;
+            for _ in 0..10 {
+                vm.step();
+            }
+            vm.load_state(&state).unwrap();
+            assert_eq!(vm.registers, registers);
+            assert_eq!(vm.pc, pc);
+            assert_eq!(vm.op_executed, 10);
+            assert_eq!(vm.save_banks[0][0], 9);
+            assert!(vm.save_dirty_flag[0]);
+            assert_eq!(vm.save_state(compress), state);
+        }
+        assert!(setup_vm().save_state(true).len() < setup_vm().save_state(false).len());
+    }
+
+    #[test]
+    fn long_game_id() {
+        let mut vm = setup_vm();
+        vm.game_id = "a".repeat(300);
+        let state = vm.save_state(false);
+        assert_eq!(vm.load_state(&state), Ok(()));
+    }
+
+    #[test]
+    fn error_round_trip() {
+        let errors = [
+            VmError::UnsupportedOp { op: 0x6E, pc: 10 },
+            VmError::InvalidBank {
+                kind: BankKind::Save,
+                requested: 4,
+                available: 3,
+            },
+            VmError::GameValidation(String::from("no code")),
+        ];
+        for error in errors {
+            let mut vm = setup_vm();
+            vm.error = Some(error.clone());
+            vm.halted = true;
+            let state = vm.save_state(false);
+            vm.error = None;
+            vm.load_state(&state).unwrap();
+            assert_eq!(vm.error, Some(error));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_states() {
+        let mut vm = setup_vm();
+        let state = vm.save_state(true);
+
+        assert_eq!(
+            vm.load_state(&[1, 2, 3]),
+            Err(SaveStateError::InvalidFormat("not a save state"))
+        );
+
+        let mut wrong_version = state.clone();
+        wrong_version[4] = FORMAT_VERSION + 1;
+        assert_eq!(
+            vm.load_state(&wrong_version),
+            Err(SaveStateError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+
+        let mut corrupt = state.clone();
+        corrupt[40] ^= 0xFF;
+        assert!(matches!(
+            vm.load_state(&corrupt),
+            Err(SaveStateError::ChecksumMismatch { .. })
+        ));
+
+        let mut other_game = setup_vm();
+        other_game.game_hash = 4321;
+        assert!(matches!(
+            other_game.load_state(&state),
+            Err(SaveStateError::DifferentGame { .. })
+        ));
+
+        let mut other_banks = setup_vm();
+        other_banks.ram_banks.pop();
+        assert_eq!(
+            other_banks.load_state(&state),
+            Err(SaveStateError::BankCountMismatch {
+                kind: "RAM",
+                expected: 1,
+                found: 2
+            })
+        );
+    }
+}
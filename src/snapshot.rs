@@ -0,0 +1,148 @@
+use crate::error::VmError;
+use crate::input::InputState;
+use crate::sound::SoundSnapshot;
+use crate::timer::Timers;
+use crate::{sizes, VM};
+use maikor_platform::constants::SAVE_COUNT;
+use maikor_platform::registers;
+
+/// Copy of the VM state at a point in time, see [VM::snapshot()]
+///
+/// Game data (code banks, atlas banks, etc) is not included so a snapshot
+/// must only be restored into a VM that has the same game loaded
+#[derive(Clone)]
+pub struct Snapshot {
+    pub(crate) registers: [u8; registers::SIZE],
+    pub(crate) pc: u16,
+    pub(crate) memory: [u8; sizes::TOTAL],
+    pub(crate) ram_banks: Vec<[u8; sizes::RAM_BANK]>,
+    pub(crate) save_banks: Vec<[u8; sizes::SAVE_BANK]>,
+    pub(crate) save_dirty_flag: [bool; SAVE_COUNT],
+    pub(crate) error: Option<VmError>,
+    pub(crate) halted: bool,
+    pub(crate) op_executed: usize,
+    pub(crate) cycles_executed: usize,
+    pub(crate) arg_ptr: u16,
+    pub(crate) line_cycles: usize,
+    pub(crate) input: InputState,
+    pub(crate) input_latch: InputState,
+    pub(crate) timers: Timers,
+    pub(crate) sound: SoundSnapshot,
+}
+
+impl VM {
+    /// Copy everything needed to resume execution from this point
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            registers: self.registers,
+            pc: self.pc,
+            memory: self.memory,
+            ram_banks: self.ram_banks.clone(),
+            save_banks: self.save_banks.clone(),
+            save_dirty_flag: self.save_dirty_flag,
+            error: self.error.clone(),
+            halted: self.halted,
+            op_executed: self.op_executed,
+            cycles_executed: self.cycles_executed,
+            arg_ptr: self.arg_ptr,
+            line_cycles: self.line_cycles,
+            input: self.input,
+            input_latch: self.input_latch,
+            timers: self.timers,
+            sound: self.sound.snapshot(),
+        }
+    }
+
+    /// Replace the VM state with `snapshot`
+    /// Memory is copied directly and so bank switching, interrupts, etc are not triggered
+    /// History (see [VM::enable_history()]) is cleared
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.clear_history();
+        self.restore_state(snapshot);
+    }
+
+    /// Same as [VM::restore()] but history is kept
+    pub(crate) fn restore_state(&mut self, snapshot: &Snapshot) {
+        self.registers = snapshot.registers;
+        self.pc = snapshot.pc;
+        self.memory = snapshot.memory;
+        self.ram_banks = snapshot.ram_banks.clone();
+        self.save_banks = snapshot.save_banks.clone();
+        self.save_dirty_flag = snapshot.save_dirty_flag;
+        self.error = snapshot.error.clone();
+        self.halted = snapshot.halted;
+        self.op_executed = snapshot.op_executed;
+        self.cycles_executed = snapshot.cycles_executed;
+        self.arg_ptr = snapshot.arg_ptr;
+        self.line_cycles = snapshot.line_cycles;
+        self.input = snapshot.input;
+        self.input_latch = snapshot.input_latch;
+        self.timers = snapshot.timers;
+        self.sound.restore(&snapshot.sound);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{address, sizes, VM};
+    use maikor_platform::ops::{ADD_REG_NUM_BYTE, CPY_ADDR_REG_BYTE, INC_REG_BYTE, JMP_ADDR};
+    use maikor_platform::registers::id;
+
+    #[test]
+    fn restore_resumes_execution() {
+        let mut vm = VM::new_test();
+        vm.ram_banks = vec![[0; sizes::RAM_BANK], [0; sizes::RAM_BANK]];
+        vm.debug_set_mem(address::RAM_BANK_1_ID as u16, 0);
+        let ram = (address::RAM_BANK_1 as u16).to_be_bytes();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            INC_REG_BYTE, id::AL as u8,
+            ADD_REG_NUM_BYTE, id::BL as u8, 3,
+            CPY_ADDR_REG_BYTE, ram[0], ram[1], id::BL as u8,
+            JMP_ADDR, 0, 0,
+        ]);
+        for _ in 0..6 {
+            vm.step();
+        }
+        let snapshot = vm.snapshot();
+        let expected_registers = vm.registers;
+        let expected_pc = vm.pc;
+
+        for _ in 0..9 {
+            vm.step();
+        }
+        assert_ne!(vm.registers, expected_registers);
+        let expected_after = (vm.registers, vm.pc, vm.ram_banks[0][0], vm.cycles_executed);
+
+        vm.restore(&snapshot);
+        assert_eq!(vm.registers, expected_registers);
+        assert_eq!(vm.pc, expected_pc);
+        assert_eq!(vm.op_executed, 6);
+        assert_eq!(vm.ram_banks[0][0], 3);
+        assert_eq!(vm.memory[address::RAM_BANK_1], 3);
+
+        for _ in 0..9 {
+            vm.step();
+        }
+        assert_eq!(
+            (vm.registers, vm.pc, vm.ram_banks[0][0], vm.cycles_executed),
+            expected_after
+        );
+    }
+
+    #[test]
+    fn restore_sound() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem((address::SOUND + 22) as u16, 0x80);
+        vm.debug_set_mem((address::SOUND + 7) as u16, 0xF0);
+        vm.debug_set_mem((address::SOUND + 9) as u16, 0x80);
+        let snapshot = vm.snapshot();
+        assert_eq!(vm.sound.read((address::SOUND + 22) as u16), 0x82);
+
+        vm.debug_set_mem((address::SOUND + 22) as u16, 0);
+        assert_ne!(vm.sound.read((address::SOUND + 22) as u16), 0x82);
+
+        vm.restore(&snapshot);
+        assert_eq!(vm.sound.read((address::SOUND + 22) as u16), 0x82);
+    }
+}
@@ -1,8 +1,11 @@
 // this whole file was taken from https://github.com/mvdnes/rboy/blob/master/src/sound.rs
 
+use crate::recording::Recording;
+use crate::save_state::{ByteReader, ByteWriter, SaveStateError};
 use crate::AudioPlayer;
 use blip_buf::BlipBuf;
 use maikor_platform::mem;
+use std::collections::VecDeque;
 
 const WAVE_PATTERN: [[i32; 8]; 4] = [
     [-1, -1, -1, -1, 1, -1, -1, -1],
@@ -12,6 +15,8 @@ const WAVE_PATTERN: [[i32; 8]; 4] = [
 ];
 const CLOCKS_PER_SECOND: u32 = 1 << 22;
 const OUTPUT_SAMPLE_COUNT: usize = 2000; // this should be less than blip_buf::MAX_FRAME
+/// Volume used for channel taps, same as the mix at max master volume
+const TAP_VOL: f32 = (1.0 / 15.0) * 0.25;
 
 const ADDR: u16 = mem::address::SOUND;
 const S1_S: u16 = ADDR;
@@ -39,11 +44,12 @@ const C_B: u16 = ADDR + 21;
 const C_P: u16 = ADDR + 22;
 
 const WAVE_TABLE_START: u16 = mem::address::WAVE_TABLE;
-const WAVE_TABLE_END: u16 = mem::address::WAVE_TABLE + mem::sizes::WAVE_TABLE;
+const WAVE_TABLE_END: u16 = mem::address::WAVE_TABLE + mem::sizes::WAVE_TABLE - 1;
 
 const SOUND: usize = mem::address::SOUND as usize;
 const WAVE_TABLE: usize = mem::address::WAVE_TABLE as usize;
 
+#[derive(Clone)]
 struct VolumeEnvelope {
     period: u8,
     goes_up: bool,
@@ -95,6 +101,7 @@ impl VolumeEnvelope {
     }
 }
 
+#[derive(Clone)]
 struct SquareChannel {
     enabled: bool,
     duty: u8,
@@ -113,11 +120,10 @@ struct SquareChannel {
     sweep_shift: u8,
     sweep_frequency_increase: bool,
     volume_envelope: VolumeEnvelope,
-    blip: BlipBuf,
 }
 
 impl SquareChannel {
-    fn new(blip: BlipBuf, with_sweep: bool) -> SquareChannel {
+    fn new(with_sweep: bool) -> SquareChannel {
         SquareChannel {
             enabled: false,
             duty: 1,
@@ -136,7 +142,6 @@ impl SquareChannel {
             sweep_shift: 0,
             sweep_frequency_increase: false,
             volume_envelope: VolumeEnvelope::new(),
-            blip,
         }
     }
 
@@ -190,10 +195,10 @@ impl SquareChannel {
     }
 
     // This assumes no volume or sweep adjustments need to be done in the meantime
-    fn run(&mut self, start_time: u32, end_time: u32) {
+    fn run(&mut self, blip: &mut BlipBuf, start_time: u32, end_time: u32) {
         if !self.enabled || self.period == 0 || self.volume_envelope.volume == 0 {
             if self.last_amp != 0 {
-                self.blip.add_delta(start_time, -self.last_amp);
+                blip.add_delta(start_time, -self.last_amp);
                 self.last_amp = 0;
                 self.delay = 0;
             }
@@ -205,7 +210,7 @@ impl SquareChannel {
             while time < end_time {
                 let amp = vol * pattern[self.phase as usize];
                 if amp != self.last_amp {
-                    self.blip.add_delta(time, amp - self.last_amp);
+                    blip.add_delta(time, amp - self.last_amp);
                     self.last_amp = amp;
                 }
                 time += self.period;
@@ -260,6 +265,7 @@ impl SquareChannel {
     }
 }
 
+#[derive(Clone)]
 struct WaveChannel {
     enabled: bool,
     enabled_flag: bool,
@@ -273,11 +279,10 @@ struct WaveChannel {
     volume_shift: u8,
     waveram: [u8; 32],
     current_wave: u8,
-    blip: BlipBuf,
 }
 
 impl WaveChannel {
-    fn new(blip: BlipBuf) -> WaveChannel {
+    fn new() -> WaveChannel {
         WaveChannel {
             enabled: false,
             enabled_flag: false,
@@ -291,7 +296,6 @@ impl WaveChannel {
             volume_shift: 0,
             waveram: [0; 32],
             current_wave: 0,
-            blip,
         }
     }
 
@@ -338,10 +342,10 @@ impl WaveChannel {
         self.enabled
     }
 
-    fn run(&mut self, start_time: u32, end_time: u32) {
+    fn run(&mut self, blip: &mut BlipBuf, start_time: u32, end_time: u32) {
         if !self.enabled || self.period == 0 {
             if self.last_amp != 0 {
-                self.blip.add_delta(start_time, -self.last_amp);
+                blip.add_delta(start_time, -self.last_amp);
                 self.last_amp = 0;
                 self.delay = 0;
             }
@@ -367,7 +371,7 @@ impl WaveChannel {
                 let amp = ((sample << 2) >> volshift) as i32;
 
                 if amp != self.last_amp {
-                    self.blip.add_delta(time, amp - self.last_amp);
+                    blip.add_delta(time, amp - self.last_amp);
                     self.last_amp = amp;
                 }
 
@@ -390,6 +394,7 @@ impl WaveChannel {
     }
 }
 
+#[derive(Clone)]
 struct NoiseChannel {
     enabled: bool,
     length: u8,
@@ -401,11 +406,10 @@ struct NoiseChannel {
     state: u16,
     delay: u32,
     last_amp: i32,
-    blip: BlipBuf,
 }
 
 impl NoiseChannel {
-    fn new(blip: BlipBuf) -> NoiseChannel {
+    fn new() -> NoiseChannel {
         NoiseChannel {
             enabled: false,
             length: 0,
@@ -417,7 +421,6 @@ impl NoiseChannel {
             state: 1,
             delay: 0,
             last_amp: 0,
-            blip,
         }
     }
 
@@ -451,10 +454,10 @@ impl NoiseChannel {
         self.enabled
     }
 
-    fn run(&mut self, start_time: u32, end_time: u32) {
+    fn run(&mut self, blip: &mut BlipBuf, start_time: u32, end_time: u32) {
         if !self.enabled || self.volume_envelope.volume == 0 {
             if self.last_amp != 0 {
-                self.blip.add_delta(start_time, -self.last_amp);
+                blip.add_delta(start_time, -self.last_amp);
                 self.last_amp = 0;
                 self.delay = 0;
             }
@@ -472,7 +475,7 @@ impl NoiseChannel {
                 };
 
                 if self.last_amp != amp {
-                    self.blip.add_delta(time, amp - self.last_amp);
+                    blip.add_delta(time, amp - self.last_amp);
                     self.last_amp = amp;
                 }
 
@@ -502,13 +505,76 @@ pub struct Sound {
     next_time: u32,
     time_divider: u8,
     output_period: u32,
+    //VM cycles per second, used to convert VM cycles to sound clocks
+    vm_cycles_per_second: u32,
+    //VM cycles (multiplied by CLOCKS_PER_SECOND) not yet converted to sound clocks
+    cycle_remainder: u64,
     channel1: SquareChannel,
     channel2: SquareChannel,
     channel3: WaveChannel,
     channel4: NoiseChannel,
     volume_left: u8,
     volume_right: u8,
+    //output buffer for each channel, in channel order
+    blips: [BlipBuf; 4],
     player: Box<dyn AudioPlayer>,
+    //if set all output is mixed and stored, regardless of the player
+    recording: Option<Recording>,
+    output_mode: OutputMode,
+    //mixed samples waiting to be read, only used in pull mode
+    queue_left: VecDeque<f32>,
+    queue_right: VecDeque<f32>,
+    //sound clocks (multiplied by sample rate) not yet converted to silent samples
+    silence_remainder: u64,
+    //in channel order
+    muted: [bool; 4],
+    soloed: [bool; 4],
+    //unmixed samples for each channel, in channel order, only stored if set
+    taps: Option<ChannelTaps>,
+    //if true samples are generated but not played, recorded, queued or tapped
+    discard_output: bool,
+}
+
+/// Sound channel, see [Sound::set_channel_muted()] and [Sound::set_channel_solo()]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Channel {
+    /// Square wave with frequency sweep
+    Square1,
+    /// Square wave
+    Square2,
+    /// Wave table
+    Wave,
+    Noise,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [
+        Channel::Square1,
+        Channel::Square2,
+        Channel::Wave,
+        Channel::Noise,
+    ];
+
+    fn idx(self) -> usize {
+        self as usize
+    }
+}
+
+struct ChannelTaps {
+    max_samples: usize,
+    samples: [VecDeque<f32>; 4],
+}
+
+/// How mixed audio is delivered to the host
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OutputMode {
+    /// Samples are sent to the [AudioPlayer] when it reports it has underflowed,
+    /// otherwise they are discarded
+    Push,
+    /// Samples are stored until read with [Sound::drain_samples()], the [AudioPlayer]
+    /// is not used
+    /// If more than `max_samples` (per channel) are waiting then the oldest are discarded
+    Pull { max_samples: usize },
 }
 
 impl Sound {
@@ -517,17 +583,14 @@ impl Sound {
         self.time = 0;
         self.prev_time = 0;
         self.next_time = 0;
+        self.cycle_remainder = 0;
+        self.silence_remainder = 0;
         self.on = false;
     }
 }
 
 impl Sound {
     pub fn new(player: Box<dyn AudioPlayer>) -> Sound {
-        let blipbuf1 = create_blipbuf(player.samples_rate());
-        let blipbuf2 = create_blipbuf(player.samples_rate());
-        let blipbuf3 = create_blipbuf(player.samples_rate());
-        let blipbuf4 = create_blipbuf(player.samples_rate());
-
         let output_period =
             (OUTPUT_SAMPLE_COUNT as u64 * CLOCKS_PER_SECOND as u64) / player.samples_rate() as u64;
 
@@ -539,12 +602,29 @@ impl Sound {
             next_time: CLOCKS_PER_SECOND / 256,
             time_divider: 0,
             output_period: output_period as u32,
-            channel1: SquareChannel::new(blipbuf1, true),
-            channel2: SquareChannel::new(blipbuf2, false),
-            channel3: WaveChannel::new(blipbuf3),
-            channel4: NoiseChannel::new(blipbuf4),
+            vm_cycles_per_second: CLOCKS_PER_SECOND,
+            cycle_remainder: 0,
+            channel1: SquareChannel::new(true),
+            channel2: SquareChannel::new(false),
+            channel3: WaveChannel::new(),
+            channel4: NoiseChannel::new(),
             volume_left: 7,
             volume_right: 7,
+            blips: [
+                create_blipbuf(player.samples_rate()),
+                create_blipbuf(player.samples_rate()),
+                create_blipbuf(player.samples_rate()),
+                create_blipbuf(player.samples_rate()),
+            ],
+            recording: None,
+            output_mode: OutputMode::Push,
+            queue_left: VecDeque::new(),
+            queue_right: VecDeque::new(),
+            silence_remainder: 0,
+            muted: [false; 4],
+            soloed: [false; 4],
+            taps: None,
+            discard_output: false,
             player,
         }
     }
@@ -604,8 +684,135 @@ impl Sound {
         false
     }
 
+    /// Start storing all audio output, replacing any existing recording
+    /// While recording the output is mixed every output period, rather than only when
+    /// the player has underflowed, so the recording is the same on every platform
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new(self.player.samples_rate()));
+    }
+
+    /// Stop recording and return audio output since [Sound::start_recording()]
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+
+    /// Set how mixed audio is delivered, any samples waiting to be read are discarded
+    pub fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+        self.queue_left.clear();
+        self.queue_right.clear();
+    }
+
+    /// Number of samples (per channel) waiting to be read by [Sound::drain_samples()]
+    pub fn samples_available(&self) -> usize {
+        self.queue_left.len()
+    }
+
+    /// Move waiting samples into `left` and `right`, oldest first
+    /// Only used with [OutputMode::Pull]
+    /// Returns number of samples written to each slice
+    pub fn drain_samples(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        let count = self.queue_left.len().min(left.len()).min(right.len());
+        for (dst, src) in left.iter_mut().zip(self.queue_left.drain(..count)) {
+            *dst = src;
+        }
+        for (dst, src) in right.iter_mut().zip(self.queue_right.drain(..count)) {
+            *dst = src;
+        }
+        count
+    }
+
+    /// Mute or unmute `channel` in the mixed output
+    pub fn set_channel_muted(&mut self, channel: Channel, muted: bool) {
+        self.muted[channel.idx()] = muted;
+    }
+
+    pub fn is_channel_muted(&self, channel: Channel) -> bool {
+        self.muted[channel.idx()]
+    }
+
+    /// Solo or unsolo `channel`, while any channel is soloed only soloed channels
+    /// are included in the mixed output
+    pub fn set_channel_solo(&mut self, channel: Channel, solo: bool) {
+        self.soloed[channel.idx()] = solo;
+    }
+
+    pub fn is_channel_solo(&self, channel: Channel) -> bool {
+        self.soloed[channel.idx()]
+    }
+
+    /// Returns true if `channel` is included in the mixed output, based on mute and solo
+    /// (but not panning, volume or if the channel is playing)
+    pub fn is_channel_audible(&self, channel: Channel) -> bool {
+        let idx = channel.idx();
+        !self.muted[idx] && (self.soloed[idx] || !self.soloed.contains(&true))
+    }
+
+    /// Start (with `Some`) or stop (with `None`) storing unmixed samples for each channel,
+    /// samples are stored regardless of mute, solo, panning and master volume
+    /// If more than `max_samples` are waiting for a channel then the oldest are discarded
+    pub fn set_channel_taps(&mut self, max_samples: Option<usize>) {
+        self.taps = max_samples.map(|max_samples| ChannelTaps {
+            max_samples,
+            samples: Default::default(),
+        });
+    }
+
+    /// Number of unmixed samples waiting to be read by [Sound::drain_channel_tap()]
+    pub fn channel_tap_available(&self, channel: Channel) -> usize {
+        self.taps
+            .as_ref()
+            .map(|taps| taps.samples[channel.idx()].len())
+            .unwrap_or_default()
+    }
+
+    /// Move waiting unmixed samples for `channel` into `output`, oldest first
+    /// Returns number of samples written
+    pub fn drain_channel_tap(&mut self, channel: Channel, output: &mut [f32]) -> usize {
+        match &mut self.taps {
+            Some(taps) => {
+                let samples = &mut taps.samples[channel.idx()];
+                let count = samples.len().min(output.len());
+                for (dst, src) in output.iter_mut().zip(samples.drain(..count)) {
+                    *dst = src;
+                }
+                count
+            }
+            None => 0,
+        }
+    }
+
+    /// Used while ops are replayed by [VM::step_back()](crate::VM::step_back) so the host
+    /// doesn't get the same audio twice
+    pub(crate) fn set_discard_output(&mut self, discard: bool) {
+        self.discard_output = discard;
+    }
+
+    /// Set how many VM cycles are executed per second
+    /// This is used by [VM::step()](crate::VM::step) to advance the sound clock, by default
+    /// one VM cycle is one sound clock
+    pub fn set_cycles_per_second(&mut self, vm_cycles_per_second: u32) {
+        assert!(vm_cycles_per_second > 0, "cycles per second must be > 0");
+        self.vm_cycles_per_second = vm_cycles_per_second;
+        self.cycle_remainder = 0;
+    }
+
+    /// Advance sound clock by `cycles` VM cycles
+    pub(crate) fn do_vm_cycles(&mut self, cycles: usize) {
+        let total = cycles as u64 * CLOCKS_PER_SECOND as u64 + self.cycle_remainder;
+        let per_second = self.vm_cycles_per_second as u64;
+        self.cycle_remainder = total % per_second;
+        let clocks = total / per_second;
+        if clocks > 0 {
+            self.do_cycle(clocks as u32);
+        }
+    }
+
+    /// Advance sound clock by `cycles` sound clocks
+    /// This is called automatically by [VM::step()](crate::VM::step)
     pub fn do_cycle(&mut self, cycles: u32) {
         if !self.on {
+            self.output_silence(cycles);
             return;
         }
 
@@ -619,16 +826,18 @@ impl Sound {
     fn do_output(&mut self) {
         self.run();
         debug_assert!(self.time == self.prev_time);
-        self.channel1.blip.end_frame(self.time);
-        self.channel2.blip.end_frame(self.time);
-        self.channel3.blip.end_frame(self.time);
-        self.channel4.blip.end_frame(self.time);
+        for blip in &mut self.blips {
+            blip.end_frame(self.time);
+        }
         self.next_time -= self.time;
         self.time = 0;
         self.prev_time = 0;
 
-        if self.player.underflowed() {
-            self.mix_buffers();
+        let pull = matches!(self.output_mode, OutputMode::Pull { .. });
+        let play = !pull && self.player.underflowed();
+        let output = play || pull || self.recording.is_some() || self.taps.is_some();
+        if output && !self.discard_output {
+            self.mix_buffers(play);
         } else {
             // Prevent the BlipBuf's from filling up and triggering an assertion
             self.clear_buffers();
@@ -637,10 +846,14 @@ impl Sound {
 
     fn run(&mut self) {
         while self.next_time <= self.time {
-            self.channel1.run(self.prev_time, self.next_time);
-            self.channel2.run(self.prev_time, self.next_time);
-            self.channel3.run(self.prev_time, self.next_time);
-            self.channel4.run(self.prev_time, self.next_time);
+            self.channel1
+                .run(&mut self.blips[0], self.prev_time, self.next_time);
+            self.channel2
+                .run(&mut self.blips[1], self.prev_time, self.next_time);
+            self.channel3
+                .run(&mut self.blips[2], self.prev_time, self.next_time);
+            self.channel4
+                .run(&mut self.blips[3], self.prev_time, self.next_time);
 
             self.channel1.step_length();
             self.channel2.step_length();
@@ -661,20 +874,61 @@ impl Sound {
         }
 
         if self.prev_time != self.time {
-            self.channel1.run(self.prev_time, self.time);
-            self.channel2.run(self.prev_time, self.time);
-            self.channel3.run(self.prev_time, self.time);
-            self.channel4.run(self.prev_time, self.time);
+            self.channel1
+                .run(&mut self.blips[0], self.prev_time, self.time);
+            self.channel2
+                .run(&mut self.blips[1], self.prev_time, self.time);
+            self.channel3
+                .run(&mut self.blips[2], self.prev_time, self.time);
+            self.channel4
+                .run(&mut self.blips[3], self.prev_time, self.time);
 
             self.prev_time = self.time;
         }
     }
 
-    fn mix_buffers(&mut self) {
-        let sample_count = self.channel1.blip.samples_avail() as usize;
-        debug_assert!(sample_count == self.channel2.blip.samples_avail() as usize);
-        debug_assert!(sample_count == self.channel3.blip.samples_avail() as usize);
-        debug_assert!(sample_count == self.channel4.blip.samples_avail() as usize);
+    /// While sound is off no samples are generated, so silence is output instead to
+    /// keep recordings, pulled audio and taps in sync with the VM
+    fn output_silence(&mut self, clocks: u32) {
+        let pull = matches!(self.output_mode, OutputMode::Pull { .. });
+        if self.discard_output || (!pull && self.recording.is_none() && self.taps.is_none()) {
+            return;
+        }
+        let total = clocks as u64 * self.player.samples_rate() as u64 + self.silence_remainder;
+        self.silence_remainder = total % CLOCKS_PER_SECOND as u64;
+        let count = (total / CLOCKS_PER_SECOND as u64) as usize;
+        if let Some(recording) = &mut self.recording {
+            recording.push_silence(count);
+        }
+        if pull {
+            self.queue_samples(&vec![0.0; count], &vec![0.0; count]);
+        }
+        if let Some(taps) = &mut self.taps {
+            for idx in 0..4 {
+                taps.push(idx, &vec![0.0; count]);
+            }
+        }
+    }
+
+    fn queue_samples(&mut self, left: &[f32], right: &[f32]) {
+        if let OutputMode::Pull { max_samples } = self.output_mode {
+            self.queue_left.extend(left);
+            self.queue_right.extend(right);
+            if self.queue_left.len() > max_samples {
+                let excess = self.queue_left.len() - max_samples;
+                self.queue_left.drain(..excess);
+                self.queue_right.drain(..excess);
+            }
+        }
+    }
+
+    /// Mix channels and send to player (if `play` is true), recording (if active),
+    /// the sample queue (if in pull mode) and store unmixed samples (if taps are set)
+    fn mix_buffers(&mut self, play: bool) {
+        let sample_count = self.blips[0].samples_avail() as usize;
+        debug_assert!(sample_count == self.blips[1].samples_avail() as usize);
+        debug_assert!(sample_count == self.blips[2].samples_avail() as usize);
+        debug_assert!(sample_count == self.blips[3].samples_avail() as usize);
 
         let mut outputted = 0;
 
@@ -686,63 +940,500 @@ impl Sound {
             let buf_right = &mut [0f32; OUTPUT_SAMPLE_COUNT + 10];
             let buf = &mut [0i16; OUTPUT_SAMPLE_COUNT + 10];
 
-            let count1 = self.channel1.blip.read_samples(buf, false);
-            for (i, v) in buf[..count1].iter().enumerate() {
-                if self.register_data[0x15] & 0x01 == 0x01 {
-                    buf_left[i] += *v as f32 * left_vol;
-                }
-                if self.register_data[0x15] & 0x10 == 0x10 {
-                    buf_right[i] += *v as f32 * right_vol;
+            let mut count = 0;
+            for (idx, channel) in Channel::ALL.iter().enumerate() {
+                let channel_count = self.blips[idx].read_samples(buf, false);
+                debug_assert!(idx == 0 || count == channel_count);
+                count = channel_count;
+                // channel3 is the WaveChannel, that outputs samples with a 4x
+                // increase in amplitude in order to avoid a loss of precision.
+                let scale = if *channel == Channel::Wave { 4.0 } else { 1.0 };
+                if let Some(taps) = &mut self.taps {
+                    let tap = &mut [0f32; OUTPUT_SAMPLE_COUNT + 10];
+                    for (i, v) in buf[..count].iter().enumerate() {
+                        tap[i] = (*v as f32 / scale) * TAP_VOL;
+                    }
+                    taps.push(idx, &tap[..count]);
                 }
-            }
-
-            let count2 = self.channel2.blip.read_samples(buf, false);
-            for (i, v) in buf[..count2].iter().enumerate() {
-                if self.register_data[0x15] & 0x02 == 0x02 {
-                    buf_left[i] += *v as f32 * left_vol;
+                if !self.is_channel_audible(*channel) {
+                    continue;
                 }
-                if self.register_data[0x15] & 0x20 == 0x20 {
-                    buf_right[i] += *v as f32 * right_vol;
+                let left_bit = 0x01 << idx;
+                let right_bit = 0x10 << idx;
+                for (i, v) in buf[..count].iter().enumerate() {
+                    if self.register_data[0x15] & left_bit == left_bit {
+                        buf_left[i] += (*v as f32 / scale) * left_vol;
+                    }
+                    if self.register_data[0x15] & right_bit == right_bit {
+                        buf_right[i] += (*v as f32 / scale) * right_vol;
+                    }
                 }
             }
 
-            // channel3 is the WaveChannel, that outputs samples with a 4x
-            // increase in amplitude in order to avoid a loss of precision.
-            let count3 = self.channel3.blip.read_samples(buf, false);
-            for (i, v) in buf[..count3].iter().enumerate() {
-                if self.register_data[0x15] & 0x04 == 0x04 {
-                    buf_left[i] += ((*v as f32) / 4.0) * left_vol;
-                }
-                if self.register_data[0x15] & 0x40 == 0x40 {
-                    buf_right[i] += ((*v as f32) / 4.0) * right_vol;
-                }
+            if play {
+                self.player.play(&buf_left[..count], &buf_right[..count]);
+            }
+            if let Some(recording) = &mut self.recording {
+                recording.push(&buf_left[..count], &buf_right[..count]);
             }
+            self.queue_samples(&buf_left[..count], &buf_right[..count]);
 
-            let count4 = self.channel4.blip.read_samples(buf, false);
-            for (i, v) in buf[..count4].iter().enumerate() {
-                if self.register_data[0x15] & 0x08 == 0x08 {
-                    buf_left[i] += *v as f32 * left_vol;
-                }
-                if self.register_data[0x15] & 0x80 == 0x80 {
-                    buf_right[i] += *v as f32 * right_vol;
-                }
+            outputted += count;
+        }
+    }
+
+    fn clear_buffers(&mut self) {
+        for blip in &mut self.blips {
+            blip.clear();
+        }
+    }
+}
+
+impl ChannelTaps {
+    fn push(&mut self, idx: usize, samples: &[f32]) {
+        let queue = &mut self.samples[idx];
+        queue.extend(samples);
+        if queue.len() > self.max_samples {
+            let excess = queue.len() - self.max_samples;
+            queue.drain(..excess);
+        }
+    }
+}
+
+/// Copy of the sound state, see [Sound::snapshot()]
+#[derive(Clone)]
+pub struct SoundSnapshot {
+    on: bool,
+    register_data: [u8; 0x17],
+    time: u32,
+    prev_time: u32,
+    next_time: u32,
+    time_divider: u8,
+    cycle_remainder: u64,
+    channel1: SquareChannel,
+    channel2: SquareChannel,
+    channel3: WaveChannel,
+    channel4: NoiseChannel,
+    volume_left: u8,
+    volume_right: u8,
+}
+
+impl Sound {
+    /// Copy all channel, envelope and timing state
+    /// Samples waiting in the output buffers are not included
+    pub fn snapshot(&self) -> SoundSnapshot {
+        SoundSnapshot {
+            on: self.on,
+            register_data: self.register_data,
+            time: self.time,
+            prev_time: self.prev_time,
+            next_time: self.next_time,
+            time_divider: self.time_divider,
+            cycle_remainder: self.cycle_remainder,
+            channel1: self.channel1.clone(),
+            channel2: self.channel2.clone(),
+            channel3: self.channel3.clone(),
+            channel4: self.channel4.clone(),
+            volume_left: self.volume_left,
+            volume_right: self.volume_right,
+        }
+    }
+
+    /// Replace all channel, envelope and timing state
+    /// Any samples waiting in the output buffers are discarded
+    pub fn restore(&mut self, snapshot: &SoundSnapshot) {
+        self.on = snapshot.on;
+        self.register_data = snapshot.register_data;
+        self.time = snapshot.time;
+        self.prev_time = snapshot.prev_time;
+        self.next_time = snapshot.next_time;
+        self.time_divider = snapshot.time_divider;
+        self.cycle_remainder = snapshot.cycle_remainder;
+        self.channel1 = snapshot.channel1.clone();
+        self.channel2 = snapshot.channel2.clone();
+        self.channel3 = snapshot.channel3.clone();
+        self.channel4 = snapshot.channel4.clone();
+        self.volume_left = snapshot.volume_left;
+        self.volume_right = snapshot.volume_right;
+        self.clear_buffers();
+        //the buffers only store changes in amplitude, so the current level of each
+        //channel has to be re-added otherwise the output would be offset until the channel
+        //next returns to 0
+        let amps = [
+            self.channel1.last_amp,
+            self.channel2.last_amp,
+            self.channel3.last_amp,
+            self.channel4.last_amp,
+        ];
+        for (blip, amp) in self.blips.iter_mut().zip(amps) {
+            if amp != 0 {
+                blip.add_delta(0, amp);
             }
+        }
+    }
+}
 
-            debug_assert!(count1 == count2);
-            debug_assert!(count1 == count3);
-            debug_assert!(count1 == count4);
+impl SoundSnapshot {
+    pub(crate) fn write(&self, writer: &mut ByteWriter) {
+        writer.write_bool(self.on);
+        writer.write_bytes(&self.register_data);
+        writer.write_u32(self.time);
+        writer.write_u32(self.prev_time);
+        writer.write_u32(self.next_time);
+        writer.write_u8(self.time_divider);
+        writer.write_u64(self.cycle_remainder);
+        self.channel1.write(writer);
+        self.channel2.write(writer);
+        self.channel3.write(writer);
+        self.channel4.write(writer);
+        writer.write_u8(self.volume_left);
+        writer.write_u8(self.volume_right);
+    }
+
+    pub(crate) fn read(reader: &mut ByteReader) -> Result<SoundSnapshot, SaveStateError> {
+        let on = reader.read_bool()?;
+        let mut register_data = [0; 0x17];
+        register_data.copy_from_slice(reader.read_bytes(0x17)?);
+        Ok(SoundSnapshot {
+            on,
+            register_data,
+            time: reader.read_u32()?,
+            prev_time: reader.read_u32()?,
+            next_time: reader.read_u32()?,
+            time_divider: reader.read_u8()?,
+            cycle_remainder: reader.read_u64()?,
+            channel1: SquareChannel::read(reader)?,
+            channel2: SquareChannel::read(reader)?,
+            channel3: WaveChannel::read(reader)?,
+            channel4: NoiseChannel::read(reader)?,
+            volume_left: reader.read_u8()?,
+            volume_right: reader.read_u8()?,
+        })
+    }
+}
 
-            self.player.play(&buf_left[..count1], &buf_right[..count1]);
+impl VolumeEnvelope {
+    fn write(&self, writer: &mut ByteWriter) {
+        writer.write_u8(self.period);
+        writer.write_bool(self.goes_up);
+        writer.write_u8(self.delay);
+        writer.write_u8(self.initial_volume);
+        writer.write_u8(self.volume);
+    }
 
-            outputted += count1;
+    fn read(reader: &mut ByteReader) -> Result<VolumeEnvelope, SaveStateError> {
+        Ok(VolumeEnvelope {
+            period: reader.read_u8()?,
+            goes_up: reader.read_bool()?,
+            delay: reader.read_u8()?,
+            initial_volume: reader.read_u8()?,
+            volume: reader.read_u8()?,
+        })
+    }
+}
+
+impl SquareChannel {
+    fn write(&self, writer: &mut ByteWriter) {
+        writer.write_bool(self.enabled);
+        writer.write_u8(self.duty);
+        writer.write_u8(self.phase);
+        writer.write_u8(self.length);
+        writer.write_u8(self.new_length);
+        writer.write_bool(self.length_enabled);
+        writer.write_u16(self.frequency);
+        writer.write_u32(self.period);
+        writer.write_i32(self.last_amp);
+        writer.write_u32(self.delay);
+        writer.write_bool(self.has_sweep);
+        writer.write_u16(self.sweep_frequency);
+        writer.write_u8(self.sweep_delay);
+        writer.write_u8(self.sweep_period);
+        writer.write_u8(self.sweep_shift);
+        writer.write_bool(self.sweep_frequency_increase);
+        self.volume_envelope.write(writer);
+    }
+
+    fn read(reader: &mut ByteReader) -> Result<SquareChannel, SaveStateError> {
+        let channel = SquareChannel {
+            enabled: reader.read_bool()?,
+            duty: reader.read_u8()?,
+            phase: reader.read_u8()?,
+            length: reader.read_u8()?,
+            new_length: reader.read_u8()?,
+            length_enabled: reader.read_bool()?,
+            frequency: reader.read_u16()?,
+            period: reader.read_u32()?,
+            last_amp: reader.read_i32()?,
+            delay: reader.read_u32()?,
+            has_sweep: reader.read_bool()?,
+            sweep_frequency: reader.read_u16()?,
+            sweep_delay: reader.read_u8()?,
+            sweep_period: reader.read_u8()?,
+            sweep_shift: reader.read_u8()?,
+            sweep_frequency_increase: reader.read_bool()?,
+            volume_envelope: VolumeEnvelope::read(reader)?,
+        };
+        if channel.duty as usize >= WAVE_PATTERN.len() || channel.phase >= 8 {
+            return Err(SaveStateError::InvalidFormat("invalid square channel"));
         }
+        Ok(channel)
     }
+}
 
-    fn clear_buffers(&mut self) {
-        self.channel1.blip.clear();
-        self.channel2.blip.clear();
-        self.channel3.blip.clear();
-        self.channel4.blip.clear();
+impl WaveChannel {
+    fn write(&self, writer: &mut ByteWriter) {
+        writer.write_bool(self.enabled);
+        writer.write_bool(self.enabled_flag);
+        writer.write_u16(self.length);
+        writer.write_u16(self.new_length);
+        writer.write_bool(self.length_enabled);
+        writer.write_u16(self.frequency);
+        writer.write_u32(self.period);
+        writer.write_i32(self.last_amp);
+        writer.write_u32(self.delay);
+        writer.write_u8(self.volume_shift);
+        writer.write_bytes(&self.waveram);
+        writer.write_u8(self.current_wave);
+    }
+
+    fn read(reader: &mut ByteReader) -> Result<WaveChannel, SaveStateError> {
+        let mut channel = WaveChannel {
+            enabled: reader.read_bool()?,
+            enabled_flag: reader.read_bool()?,
+            length: reader.read_u16()?,
+            new_length: reader.read_u16()?,
+            length_enabled: reader.read_bool()?,
+            frequency: reader.read_u16()?,
+            period: reader.read_u32()?,
+            last_amp: reader.read_i32()?,
+            delay: reader.read_u32()?,
+            volume_shift: reader.read_u8()?,
+            waveram: [0; 32],
+            current_wave: 0,
+        };
+        channel.waveram.copy_from_slice(reader.read_bytes(32)?);
+        channel.current_wave = reader.read_u8()?;
+        if channel.volume_shift > 3 || channel.current_wave >= 32 {
+            return Err(SaveStateError::InvalidFormat("invalid wave channel"));
+        }
+        Ok(channel)
+    }
+}
+
+impl NoiseChannel {
+    fn write(&self, writer: &mut ByteWriter) {
+        writer.write_bool(self.enabled);
+        writer.write_u8(self.length);
+        writer.write_u8(self.new_length);
+        writer.write_bool(self.length_enabled);
+        self.volume_envelope.write(writer);
+        writer.write_u32(self.period);
+        writer.write_u8(self.shift_width);
+        writer.write_u16(self.state);
+        writer.write_u32(self.delay);
+        writer.write_i32(self.last_amp);
+    }
+
+    fn read(reader: &mut ByteReader) -> Result<NoiseChannel, SaveStateError> {
+        let channel = NoiseChannel {
+            enabled: reader.read_bool()?,
+            length: reader.read_u8()?,
+            new_length: reader.read_u8()?,
+            length_enabled: reader.read_bool()?,
+            volume_envelope: VolumeEnvelope::read(reader)?,
+            period: reader.read_u32()?,
+            shift_width: reader.read_u8()?,
+            state: reader.read_u16()?,
+            delay: reader.read_u32()?,
+            last_amp: reader.read_i32()?,
+        };
+        if channel.shift_width > 15 {
+            return Err(SaveStateError::InvalidFormat("invalid noise channel"));
+        }
+        Ok(channel)
+    }
+}
+
+/// Read only view of the sound system, see [Sound::state()]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SoundState {
+    /// If the sound system is powered on
+    pub on: bool,
+    /// Master volume, 0..=7
+    pub volume_left: u8,
+    pub volume_right: u8,
+    pub square1: SquareState,
+    pub square2: SquareState,
+    pub wave: WaveState,
+    pub noise: NoiseState,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EnvelopeState {
+    /// Current volume, 0..=15
+    pub volume: u8,
+    /// Volume when the channel is triggered
+    pub initial_volume: u8,
+    /// If the volume goes up (otherwise down) each step
+    pub increasing: bool,
+    /// Steps between volume changes, 0 means the volume doesn't change
+    pub period: u8,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SweepState {
+    /// Steps between frequency changes, 0 means the frequency doesn't change
+    pub period: u8,
+    pub shift: u8,
+    /// If the frequency goes up (otherwise down) each step
+    pub increasing: bool,
+    /// Current (shadow) frequency
+    pub frequency: u16,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SquareState {
+    /// If the channel is playing
+    pub enabled: bool,
+    pub frequency: u16,
+    /// Sound clocks per wave step
+    pub period: u32,
+    /// Index into the duty patterns, 0 = 12.5%, 1 = 25%, 2 = 50%, 3 = 75%
+    pub duty: u8,
+    /// Remaining length counter
+    pub length: u8,
+    /// If the channel stops when the length counter reaches 0
+    pub length_enabled: bool,
+    pub envelope: EnvelopeState,
+    /// Only set for [Channel::Square1]
+    pub sweep: Option<SweepState>,
+    pub output_left: bool,
+    pub output_right: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WaveState {
+    /// If the channel is playing
+    pub enabled: bool,
+    /// If the channel can play (W_P bit 7)
+    pub dac_enabled: bool,
+    pub frequency: u16,
+    /// Sound clocks per wave step
+    pub period: u32,
+    /// Remaining length counter
+    pub length: u16,
+    /// If the channel stops when the length counter reaches 0
+    pub length_enabled: bool,
+    /// Wave samples are shifted right by this, 4 is silent
+    pub volume_shift: u8,
+    /// Wave table as 32 4 bit samples
+    pub wave_table: [u8; 32],
+    /// Index of the current sample in `wave_table`
+    pub position: u8,
+    pub output_left: bool,
+    pub output_right: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoiseState {
+    /// If the channel is playing
+    pub enabled: bool,
+    /// Sound clocks per LFSR step
+    pub period: u32,
+    /// Width of the LFSR in bits, either 7 or 15
+    pub lfsr_width: u8,
+    pub lfsr: u16,
+    /// Remaining length counter
+    pub length: u8,
+    /// If the channel stops when the length counter reaches 0
+    pub length_enabled: bool,
+    pub envelope: EnvelopeState,
+    pub output_left: bool,
+    pub output_right: bool,
+}
+
+impl Sound {
+    /// Current state of the sound system and each channel
+    /// Values are as of the last sound memory access or output period
+    pub fn state(&self) -> SoundState {
+        let panning = self.register_data[0x15];
+        let output = |idx: usize| (panning & (0x01 << idx) != 0, panning & (0x10 << idx) != 0);
+        SoundState {
+            on: self.on,
+            volume_left: self.volume_left,
+            volume_right: self.volume_right,
+            square1: self.channel1.state(output(0)),
+            square2: self.channel2.state(output(1)),
+            wave: self.channel3.state(output(2)),
+            noise: self.channel4.state(output(3)),
+        }
+    }
+}
+
+impl VolumeEnvelope {
+    fn state(&self) -> EnvelopeState {
+        EnvelopeState {
+            volume: self.volume,
+            initial_volume: self.initial_volume,
+            increasing: self.goes_up,
+            period: self.period,
+        }
+    }
+}
+
+impl SquareChannel {
+    fn state(&self, (output_left, output_right): (bool, bool)) -> SquareState {
+        SquareState {
+            enabled: self.enabled,
+            frequency: self.frequency,
+            period: self.period,
+            duty: self.duty,
+            length: self.length,
+            length_enabled: self.length_enabled,
+            envelope: self.volume_envelope.state(),
+            sweep: self.has_sweep.then_some(SweepState {
+                period: self.sweep_period,
+                shift: self.sweep_shift,
+                increasing: self.sweep_frequency_increase,
+                frequency: self.sweep_frequency,
+            }),
+            output_left,
+            output_right,
+        }
+    }
+}
+
+impl WaveChannel {
+    fn state(&self, (output_left, output_right): (bool, bool)) -> WaveState {
+        WaveState {
+            enabled: self.enabled,
+            dac_enabled: self.enabled_flag,
+            frequency: self.frequency,
+            period: self.period,
+            length: self.length,
+            length_enabled: self.length_enabled,
+            volume_shift: self.volume_shift,
+            wave_table: self.waveram,
+            position: self.current_wave,
+            output_left,
+            output_right,
+        }
+    }
+}
+
+impl NoiseChannel {
+    fn state(&self, (output_left, output_right): (bool, bool)) -> NoiseState {
+        NoiseState {
+            enabled: self.enabled,
+            period: self.period,
+            lfsr_width: self.shift_width + 1,
+            lfsr: self.state,
+            length: self.length,
+            length_enabled: self.length_enabled,
+            envelope: self.volume_envelope.state(),
+            output_left,
+            output_right,
+        }
     }
 }
 
@@ -751,3 +1442,217 @@ fn create_blipbuf(samples_rate: u32) -> BlipBuf {
     blipbuf.set_rates(CLOCKS_PER_SECOND as f64, samples_rate as f64);
     blipbuf
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VM;
+    use maikor_platform::ops::NOP;
+
+    #[test]
+    fn vm_cycles_to_clocks() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem(C_P, 0x80);
+        vm.sound.set_cycles_per_second(CLOCKS_PER_SECOND / 4);
+        vm.sound.do_vm_cycles(3);
+        assert_eq!(vm.sound.time, 12);
+
+        vm.sound.set_cycles_per_second(CLOCKS_PER_SECOND / 2 * 3);
+        vm.sound.do_vm_cycles(1);
+        assert_eq!(vm.sound.time, 12);
+        vm.sound.do_vm_cycles(1);
+        assert_eq!(vm.sound.time, 13);
+        vm.sound.do_vm_cycles(1);
+        assert_eq!(vm.sound.time, 14);
+        assert_eq!(vm.sound.cycle_remainder, 0);
+    }
+
+    #[test]
+    fn recording() {
+        let mut vm = VM::new_test();
+        vm.sound.start_recording();
+        vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        play_tone(&mut vm);
+        for _ in 0..10 {
+            vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        }
+        let recording = vm.sound.stop_recording().unwrap();
+
+        assert!(vm.sound.stop_recording().is_none());
+        assert_eq!(recording.sample_rate(), 44100);
+        //first 10ms is silent as sound was off
+        assert!(recording.len() >= 441 + OUTPUT_SAMPLE_COUNT * 2);
+        assert!(recording.left()[..441].iter().all(|sample| *sample == 0.0));
+        assert!(recording.left()[441..].iter().any(|sample| *sample != 0.0));
+        assert_eq!(recording.left(), recording.right());
+    }
+
+    fn play_tone(vm: &mut VM) {
+        vm.debug_set_mem(C_P, 0x80);
+        vm.debug_set_mem(C_V, 0x77);
+        vm.debug_set_mem(C_B, 0xFF);
+        vm.debug_set_mem(S2_D, 0x80);
+        vm.debug_set_mem(S2_V, 0xF0);
+        vm.debug_set_mem(S2_F, 0x00);
+        vm.debug_set_mem(S2_T, 0x84);
+    }
+
+    #[test]
+    fn pull_mode() {
+        let run = || {
+            let mut vm = VM::new_test();
+            vm.sound
+                .set_output_mode(OutputMode::Pull { max_samples: 44100 });
+            vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+            //CLOCKS_PER_SECOND isn't a multiple of 100 so slightly less than 441
+            assert_eq!(vm.sound.samples_available(), 440);
+            play_tone(&mut vm);
+            for _ in 0..10 {
+                vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+            }
+            let mut left = vec![0.0; 10000];
+            let mut right = vec![0.0; 10000];
+            let count = vm.sound.drain_samples(&mut left, &mut right);
+            assert_eq!(vm.sound.samples_available(), 0);
+            left.truncate(count);
+            right.truncate(count);
+            (left, right)
+        };
+
+        let (left, right) = run();
+        assert!(left.len() >= 441 + OUTPUT_SAMPLE_COUNT * 2);
+        assert!(left[..440].iter().all(|sample| *sample == 0.0));
+        assert!(left[440..].iter().any(|sample| *sample != 0.0));
+        assert_eq!(left, right);
+        assert_eq!(run(), (left, right));
+    }
+
+    #[test]
+    fn pull_mode_limit() {
+        let mut vm = VM::new_test();
+        vm.sound
+            .set_output_mode(OutputMode::Pull { max_samples: 100 });
+        vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        assert_eq!(vm.sound.samples_available(), 100);
+
+        let mut left = [1.0; 60];
+        let mut right = [1.0; 50];
+        assert_eq!(vm.sound.drain_samples(&mut left, &mut right), 50);
+        assert_eq!(vm.sound.samples_available(), 50);
+        assert_eq!(left[..50], [0.0; 50]);
+        assert_eq!(left[50..], [1.0; 10]);
+    }
+
+    fn pull_tone(setup: impl Fn(&mut Sound)) -> Vec<f32> {
+        let mut vm = VM::new_test();
+        vm.sound
+            .set_output_mode(OutputMode::Pull { max_samples: 44100 });
+        setup(&mut vm.sound);
+        play_tone(&mut vm);
+        for _ in 0..10 {
+            vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        }
+        let mut left = vec![0.0; 10000];
+        let mut right = vec![0.0; 10000];
+        let count = vm.sound.drain_samples(&mut left, &mut right);
+        left.truncate(count);
+        left
+    }
+
+    #[test]
+    fn mute_and_solo() {
+        let normal = pull_tone(|_| {});
+        assert!(normal.iter().any(|sample| *sample != 0.0));
+
+        let muted = pull_tone(|sound| sound.set_channel_muted(Channel::Square2, true));
+        assert_eq!(muted.len(), normal.len());
+        assert!(muted.iter().all(|sample| *sample == 0.0));
+
+        let other_solo = pull_tone(|sound| sound.set_channel_solo(Channel::Square1, true));
+        assert!(other_solo.iter().all(|sample| *sample == 0.0));
+
+        let solo = pull_tone(|sound| {
+            sound.set_channel_solo(Channel::Square1, true);
+            sound.set_channel_solo(Channel::Square2, true);
+            sound.set_channel_muted(Channel::Noise, true);
+        });
+        assert_eq!(solo, normal);
+
+        let mut vm = VM::new_test();
+        vm.sound.set_channel_solo(Channel::Wave, true);
+        vm.sound.set_channel_muted(Channel::Wave, true);
+        assert!(vm.sound.is_channel_solo(Channel::Wave));
+        assert!(vm.sound.is_channel_muted(Channel::Wave));
+        assert!(!vm.sound.is_channel_audible(Channel::Wave));
+        assert!(!vm.sound.is_channel_audible(Channel::Noise));
+    }
+
+    #[test]
+    fn channel_taps() {
+        let mut vm = VM::new_test();
+        assert_eq!(
+            vm.sound.drain_channel_tap(Channel::Square1, &mut [0.0; 10]),
+            0
+        );
+        vm.sound.set_channel_taps(Some(44100));
+        vm.sound.set_channel_muted(Channel::Square2, true);
+        vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        play_tone(&mut vm);
+        for _ in 0..10 {
+            vm.sound.do_cycle(CLOCKS_PER_SECOND / 100);
+        }
+
+        let available = vm.sound.channel_tap_available(Channel::Square2);
+        assert!(available >= 440 + OUTPUT_SAMPLE_COUNT * 2);
+        for channel in Channel::ALL {
+            assert_eq!(vm.sound.channel_tap_available(channel), available);
+            let mut samples = vec![0.0; available];
+            assert_eq!(vm.sound.drain_channel_tap(channel, &mut samples), available);
+            assert!(samples[..440].iter().all(|sample| *sample == 0.0));
+            let silent = samples.iter().all(|sample| *sample == 0.0);
+            assert_eq!(silent, channel != Channel::Square2);
+            assert_eq!(vm.sound.channel_tap_available(channel), 0);
+        }
+
+        vm.sound.set_channel_taps(None);
+        assert_eq!(vm.sound.channel_tap_available(Channel::Square2), 0);
+    }
+
+    #[test]
+    fn inspect_state() {
+        let mut vm = VM::new_test();
+        let state = vm.sound.state();
+        assert!(!state.on);
+        assert!(!state.square2.enabled);
+        assert!(state.square1.sweep.is_some());
+        assert!(state.square2.sweep.is_none());
+
+        play_tone(&mut vm);
+        vm.debug_set_mem(S1_S, 0x23);
+        vm.debug_set_mem(N_C, 0x08);
+        vm.debug_set_mem(WAVE_TABLE_START, 0x1F);
+        let state = vm.sound.state();
+        assert!(state.on);
+        assert_eq!((state.volume_left, state.volume_right), (7, 7));
+        assert!(state.square2.enabled);
+        assert_eq!(state.square2.duty, 2);
+        assert_eq!(state.square2.frequency, 0x400);
+        assert_eq!(state.square2.envelope.volume, 15);
+        assert!(!state.square2.envelope.increasing);
+        assert!(state.square2.output_left && state.square2.output_right);
+        let sweep = state.square1.sweep.unwrap();
+        assert_eq!((sweep.period, sweep.shift, sweep.increasing), (2, 3, false));
+        assert_eq!(state.noise.lfsr_width, 7);
+        assert_eq!(state.wave.wave_table[..2], [1, 0xF]);
+        assert!(!state.wave.enabled);
+    }
+
+    #[test]
+    fn step_advances_sound() {
+        let mut vm = VM::new_test();
+        vm.debug_set_mem(C_P, 0x80);
+        vm.memory[0] = NOP;
+        let cycles = vm.step();
+        assert_eq!(vm.sound.time, cycles as u32);
+    }
+}
@@ -0,0 +1,164 @@
+//! Timers
+//!
+//! There are 4 timers, each with an 8 bit counter at `TIMER_VALUE1..TIMER_VALUE4`
+//!
+//! `TIMER_CONTROL` is
+//!
+//! | Byte | Bits | Content                                                    |
+//! |------|------|------------------------------------------------------------|
+//! | 0    | 0..4 | Enabled, bit 0 is timer 1, etc                             |
+//! | 0    | 4..8 | Repeat, bit 4 is timer 1, etc                              |
+//! | 1    | 0..8 | Rate, 2 bits per timer (bits 0,1 are timer 1), see [RATES] |
+//!
+//! While a timer is enabled its counter is decreased by one every tick (see [RATES]), when it
+//! reaches 0 `IRQ_TIMER` is triggered and if repeat is set the counter is set back to the last
+//! value written to it by the game, otherwise the timer is disabled
+//!
+//! Writing a counter also restarts the current tick for that timer
+
+use crate::{address, VM};
+use maikor_platform::mem::interrupt_flags;
+
+/// Number of timers
+pub const TIMER_COUNT: usize = 4;
+/// VM cycles per tick for each rate
+pub const RATES: [u32; 4] = [64, 1024, 16384, 262144];
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub(crate) struct Timers {
+    /// last value written to each counter by the game
+    pub(crate) reload: [u8; TIMER_COUNT],
+    /// cycles into the current tick for each timer
+    pub(crate) elapsed: [u32; TIMER_COUNT],
+}
+
+impl VM {
+    /// Called when the game writes to a timer counter
+    pub(crate) fn timer_value_written(&mut self, timer: usize, value: u8) {
+        self.timers.reload[timer] = value;
+        self.timers.elapsed[timer] = 0;
+    }
+
+    /// Advance enabled timers by `cycles`, triggers `IRQ_TIMER` if any expired
+    pub(crate) fn advance_timers(&mut self, cycles: usize) {
+        let control = self.memory[address::TIMER_CONTROL];
+        if control & 0x0F == 0 {
+            return;
+        }
+        let rates = self.memory[address::TIMER_CONTROL + 1];
+        let mut expired = false;
+        for timer in 0..TIMER_COUNT {
+            if control & (1 << timer) == 0 {
+                continue;
+            }
+            let rate = RATES[((rates >> (timer * 2)) & 0b11) as usize];
+            let elapsed = self.timers.elapsed[timer] as usize + cycles;
+            let ticks = elapsed / rate as usize;
+            self.timers.elapsed[timer] = (elapsed % rate as usize) as u32;
+            if ticks == 0 {
+                continue;
+            }
+            let addr = address::TIMER_VALUE1 + timer;
+            let value = self.memory[addr] as usize;
+            self.log_mem(addr, 1, false);
+            if ticks < value {
+                self.memory[addr] = (value - ticks) as u8;
+                continue;
+            }
+            expired = true;
+            if control & (0x10 << timer) != 0 {
+                self.memory[addr] = self.timers.reload[timer];
+            } else {
+                self.memory[addr] = 0;
+                self.timers.elapsed[timer] = 0;
+                self.log_mem(address::TIMER_CONTROL, 1, false);
+                self.memory[address::TIMER_CONTROL] &= !(1 << timer);
+            }
+        }
+        if expired {
+            self.trigger_interrupt(interrupt_flags::IRQ_TIMER);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::mem::address::interrupt;
+    use maikor_platform::ops;
+
+    const COUNTER: u16 = 0x5000;
+
+    fn setup(control: u8, rates: u8, value: u8) -> VM {
+        let mut vm = VM::new_test();
+        let counter = COUNTER.to_be_bytes();
+        let timer_control = (address::TIMER_CONTROL as u16).to_be_bytes();
+        let timer_value = (address::TIMER_VALUE1 as u16).to_be_bytes();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::CPY_ADDR_NUM_BYTE, timer_value[0], timer_value[1], value,
+            ops::CPY_ADDR_NUM_BYTE, timer_control[0], timer_control[1] + 1, rates,
+            ops::CPY_ADDR_NUM_BYTE, timer_control[0], timer_control[1], control,
+            ops::JMP_ADDR, 0, 12,
+        ]);
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(interrupt::IRQ_TIMER, &[
+            ops::INC_ADDR_BYTE, counter[0], counter[1],
+            ops::RETI,
+        ]);
+        vm.debug_set_mem(address::IRQ_CONTROL as u16, interrupt_flags::IRQ_TIMER);
+        vm
+    }
+
+    fn run(vm: &mut VM, cycles: usize) {
+        let end = vm.cycles_executed + cycles;
+        while vm.cycles_executed < end && !vm.halted {
+            vm.step();
+        }
+    }
+
+    #[test]
+    fn one_shot() {
+        let mut vm = setup(0b0000_0001, 0, 10);
+        run(&mut vm, 64 * 9);
+        assert_eq!(vm.memory[COUNTER as usize], 0);
+        assert_ne!(vm.memory[address::TIMER_VALUE1], 10);
+        run(&mut vm, 64 * 2);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+        assert_eq!(vm.memory[address::TIMER_VALUE1], 0);
+        assert_eq!(vm.memory[address::TIMER_CONTROL], 0);
+        run(&mut vm, 64 * 20);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+    }
+
+    #[test]
+    fn repeat() {
+        let mut vm = setup(0b0001_0001, 0b01, 2);
+        run(&mut vm, 1024 * 10 + 100);
+        assert_eq!(vm.memory[COUNTER as usize], 5);
+        assert_eq!(vm.memory[address::TIMER_CONTROL], 0b0001_0001);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut vm = setup(0b0001_0000, 0, 1);
+        run(&mut vm, 1000);
+        assert_eq!(vm.memory[COUNTER as usize], 0);
+        assert_eq!(vm.memory[address::TIMER_VALUE1], 1);
+    }
+
+    #[test]
+    fn step_back() {
+        let mut vm = setup(0b0000_0001, 0, 3);
+        vm.enable_history(1000);
+        run(&mut vm, 64 * 4);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+        while vm.step_back() {}
+        assert_eq!(vm.pc, 0);
+        assert_eq!(vm.timers, Timers::default());
+        assert_eq!(vm.memory[address::TIMER_VALUE1], 0);
+        assert_eq!(vm.memory[address::IRQ_REG_ADDR + 1], 0);
+        run(&mut vm, 64 * 4);
+        assert_eq!(vm.memory[COUNTER as usize], 1);
+    }
+}
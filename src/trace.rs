@@ -0,0 +1,389 @@
+//! Record every op executed by the VM, see [VM::start_trace()]
+
+use crate::decoder::{decode, DecodeError, Instruction, Opcode, MAX_LEN};
+use crate::register::offset;
+use crate::{address, VM};
+use maikor_platform::registers;
+use maikor_platform::registers::flags;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::Write;
+
+/// Letters used for each flag in text output, in bit order
+const FLAG_NAMES: [(u8, char); 7] = [
+    (flags::CARRY, 'C'),
+    (flags::ZERO, 'Z'),
+    (flags::SIGNED, 'S'),
+    (flags::OVERFLOW, 'O'),
+    (flags::LESS_THAN, 'L'),
+    (flags::GREATER_THAN, 'G'),
+    (flags::INTERRUPTS, 'I'),
+];
+
+/// Where trace entries are stored
+pub enum TraceOutput {
+    /// Keep the last `max_entries` in memory, see [VM::trace()]
+    Buffer { max_entries: usize },
+    /// Write each entry as a line of text, see [TraceEntry]'s `Display`
+    Text(Box<dyn Write>),
+    /// Write each entry in a compact binary form, see [TraceEntry::write_binary()]
+    Binary(Box<dyn Write>),
+}
+
+/// Single executed op
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceEntry {
+    /// Address of op
+    pub pc: u16,
+    /// IDs of code banks loaded in slot 1 and 2 when op was executed
+    pub code_banks: [u8; 2],
+    pub instruction: Instruction,
+    pub registers_before: [u8; registers::SIZE],
+    pub registers_after: [u8; registers::SIZE],
+    pub cycles: usize,
+    /// op and operand bytes
+    bytes: [u8; MAX_LEN],
+}
+
+impl TraceEntry {
+    pub fn opcode(&self) -> Opcode {
+        self.instruction.opcode
+    }
+
+    /// Op and operands as they were in memory
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.instruction.len]
+    }
+
+    /// Bits of FLG changed by op
+    pub fn flags_changed(&self) -> u8 {
+        self.registers_before[offset::FLAGS] ^ self.registers_after[offset::FLAGS]
+    }
+
+    /// Entry format is:
+    /// PC (2 bytes, BE), code bank ids (2 bytes), op and operands (variable), registers before (9 bytes),
+    /// registers after (9 bytes) and cycles (4 bytes, BE)
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.pc.to_be_bytes())?;
+        writer.write_all(&self.code_banks)?;
+        writer.write_all(self.bytes())?;
+        writer.write_all(&self.registers_before)?;
+        writer.write_all(&self.registers_after)?;
+        writer.write_all(&(self.cycles as u32).to_be_bytes())
+    }
+
+    /// Read all entries from bytes written by [TraceEntry::write_binary()]
+    pub fn read_binary(bytes: &[u8]) -> Result<Vec<TraceEntry>, DecodeError> {
+        let mut entries = vec![];
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let header = bytes.get(idx..idx + 4).ok_or(DecodeError::Truncated)?;
+            let pc = u16::from_be_bytes([header[0], header[1]]);
+            let code_banks = [header[2], header[3]];
+            idx += 4;
+            let instruction = decode(&bytes[idx..])?;
+            let mut op_bytes = [0; MAX_LEN];
+            op_bytes[..instruction.len].copy_from_slice(&bytes[idx..idx + instruction.len]);
+            idx += instruction.len;
+            let rest = bytes
+                .get(idx..idx + registers::SIZE * 2 + 4)
+                .ok_or(DecodeError::Truncated)?;
+            let mut registers_before = [0; registers::SIZE];
+            let mut registers_after = [0; registers::SIZE];
+            registers_before.copy_from_slice(&rest[..registers::SIZE]);
+            registers_after.copy_from_slice(&rest[registers::SIZE..registers::SIZE * 2]);
+            let cycles = u32::from_be_bytes([rest[18], rest[19], rest[20], rest[21]]);
+            idx += rest.len();
+            entries.push(TraceEntry {
+                pc,
+                code_banks,
+                instruction,
+                registers_before,
+                registers_after,
+                cycles: cycles as usize,
+                bytes: op_bytes,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Formatted as `PC [BANK1:BANK2] OP | REGISTERS BEFORE -> REGISTERS AFTER | FLAG CHANGES | CYCLES`
+/// i.e. `0010 [00:01] INC.B AL | 00 00 00 00 00 00 00 00 41 -> 00 01 00 00 00 00 00 00 41 |  | 2`
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let changed = self.flags_changed();
+        let flags = FLAG_NAMES
+            .iter()
+            .filter(|(flag, _)| changed & flag == *flag)
+            .map(|(flag, name)| {
+                let sign = if self.registers_after[offset::FLAGS] & flag == *flag {
+                    '+'
+                } else {
+                    '-'
+                };
+                format!("{sign}{name}")
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        write!(
+            f,
+            "{:04X} [{:02X}:{:02X}] {} | {} -> {} | {} | {}",
+            self.pc,
+            self.code_banks[0],
+            self.code_banks[1],
+            self.instruction,
+            hex(&self.registers_before),
+            hex(&self.registers_after),
+            flags,
+            self.cycles
+        )
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|num| format!("{:02X}", num))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub(crate) struct Tracer {
+    output: TraceOutput,
+    entries: VecDeque<TraceEntry>,
+    /// first error when writing to output, no more entries are written after this
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    fn record(&mut self, entry: TraceEntry) {
+        match &mut self.output {
+            TraceOutput::Buffer { max_entries } => {
+                if *max_entries == 0 {
+                    return;
+                }
+                if self.entries.len() >= *max_entries {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(entry);
+            }
+            TraceOutput::Text(writer) => {
+                if self.error.is_none() {
+                    if let Err(err) = writeln!(writer, "{entry}") {
+                        self.error = Some(err);
+                    }
+                }
+            }
+            TraceOutput::Binary(writer) => {
+                if self.error.is_none() {
+                    if let Err(err) = entry.write_binary(writer) {
+                        self.error = Some(err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl VM {
+    /// Record every op executed from now on, replacing any existing trace
+    pub fn start_trace(&mut self, output: TraceOutput) {
+        self.tracer = Some(Tracer {
+            output,
+            entries: VecDeque::new(),
+            error: None,
+        });
+    }
+
+    /// Stop recording ops, streams are flushed
+    /// Returns buffered entries (oldest first) or the first error when writing to a stream
+    pub fn stop_trace(&mut self) -> io::Result<Vec<TraceEntry>> {
+        match self.tracer.take() {
+            None => Ok(vec![]),
+            Some(mut tracer) => {
+                if let Some(err) = tracer.error {
+                    return Err(err);
+                }
+                match &mut tracer.output {
+                    TraceOutput::Buffer { .. } => {}
+                    TraceOutput::Text(writer) | TraceOutput::Binary(writer) => writer.flush()?,
+                }
+                Ok(tracer.entries.into())
+            }
+        }
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.tracer.is_some()
+    }
+
+    /// Buffered entries, oldest first
+    /// This is empty if the trace is being streamed
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        self.tracer
+            .as_ref()
+            .map(|tracer| tracer.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Buffered entries as text, one op per line
+    /// Intended to be attached to crash reports along with [VM::error]
+    pub fn trace_text(&self) -> String {
+        self.tracer
+            .as_ref()
+            .map(|tracer| {
+                tracer
+                    .entries
+                    .iter()
+                    .map(|entry| entry.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Capture state before op is executed, returns None if not tracing
+    pub(crate) fn begin_trace_entry(&self, instruction: &Instruction) -> Option<TraceEntry> {
+        self.tracer.as_ref()?;
+        let mut bytes = [0; MAX_LEN];
+        for (i, byte) in bytes.iter_mut().enumerate().take(instruction.len) {
+            *byte = self.memory[self.pc.wrapping_add(i as u16) as usize];
+        }
+        Some(TraceEntry {
+            pc: self.pc,
+            code_banks: [
+                self.memory[address::CODE_BANK_1_ID],
+                self.memory[address::CODE_BANK_2_ID],
+            ],
+            instruction: *instruction,
+            registers_before: self.registers,
+            registers_after: self.registers,
+            cycles: 0,
+            bytes,
+        })
+    }
+
+    pub(crate) fn end_trace_entry(&mut self, mut entry: TraceEntry, cycles: usize) {
+        entry.registers_after = self.registers;
+        entry.cycles = cycles;
+        if let Some(tracer) = &mut self.tracer {
+            tracer.record(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maikor_platform::ops;
+    use maikor_platform::registers::id;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that can be read after being given to the VM
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn test_vm() -> VM {
+        let mut vm = VM::new_test();
+        #[rustfmt::skip]
+        vm.debug_set_mem_range(0, &[
+            ops::INC_REG_BYTE, id::AL,
+            ops::CPY_REG_NUM_BYTE, id::BL, 1,
+            ops::ADD_REG_NUM_BYTE, id::BL, 255,
+            ops::JMP_ADDR, 0, 0,
+        ]);
+        vm
+    }
+
+    #[test]
+    fn buffer() {
+        let mut vm = test_vm();
+        vm.start_trace(TraceOutput::Buffer { max_entries: 3 });
+        for _ in 0..5 {
+            vm.step();
+        }
+        let entries = vm.trace();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].pc, 5);
+        assert_eq!(entries[0].opcode(), Opcode::AddRegNumByte);
+        assert_eq!(entries[0].registers_before[3], 1);
+        assert_eq!(entries[0].registers_after[3], 0);
+        assert_eq!(entries[0].flags_changed(), flags::CARRY);
+        assert_eq!(entries[1].opcode(), Opcode::JmpAddr);
+        assert_eq!(entries[1].bytes(), &[ops::JMP_ADDR, 0, 0]);
+        assert_eq!(entries[2].pc, 0);
+        assert_eq!(entries[2].registers_after[1], 2);
+
+        assert_eq!(vm.stop_trace().unwrap(), entries);
+        assert!(!vm.is_tracing());
+    }
+
+    #[test]
+    fn text() {
+        let mut vm = test_vm();
+        vm.start_trace(TraceOutput::Buffer { max_entries: 10 });
+        vm.step();
+        vm.step();
+        vm.step();
+        let lines: Vec<String> = vm.trace_text().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "0000 [00:00] INC.B AL | 00 00 00 00 00 00 00 00 41 -> 00 01 00 00 00 00 00 00 41 |  | 2"
+        );
+        assert!(lines[2].starts_with("0005 [00:00] ADD.B BL, 255 | "));
+        assert!(lines[2].ends_with(" | +C | 2"));
+    }
+
+    #[test]
+    fn streams() {
+        let text = SharedBuffer::default();
+        let mut vm = test_vm();
+        vm.start_trace(TraceOutput::Text(Box::new(text.clone())));
+        for _ in 0..4 {
+            vm.step();
+        }
+        assert!(vm.trace().is_empty());
+        assert!(vm.stop_trace().unwrap().is_empty());
+        assert_eq!(
+            String::from_utf8(text.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .count(),
+            4
+        );
+
+        let binary = SharedBuffer::default();
+        let mut vm = test_vm();
+        vm.start_trace(TraceOutput::Binary(Box::new(binary.clone())));
+        for _ in 0..4 {
+            vm.step();
+        }
+        vm.stop_trace().unwrap();
+        let entries = TraceEntry::read_binary(&binary.0.lock().unwrap()).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[1].opcode(), Opcode::CpyRegNumByte);
+        assert_eq!(entries[1].instruction.to_string(), "CPY.B BL, 1");
+        assert_eq!(entries[3].pc, 8);
+        assert_eq!(entries[3].cycles, 1);
+
+        let mut vm2 = test_vm();
+        vm2.start_trace(TraceOutput::Buffer { max_entries: 4 });
+        for _ in 0..4 {
+            vm2.step();
+        }
+        assert_eq!(entries, vm2.trace());
+    }
+}
@@ -6,6 +6,8 @@ use maikor_platform::registers::id;
 
 mod multiple;
 mod single;
+#[cfg(feature = "test-support")]
+mod visual;
 
 mod offset {
     pub const AH: usize = 0;
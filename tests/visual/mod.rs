@@ -0,0 +1,63 @@
+use maikor_platform::mem::address::interrupt;
+use maikor_platform::mem::{address, interrupt_flags};
+use maikor_platform::models::{Byteable, LayerHeader, Sprite};
+use maikor_platform::ops::{INC_ADDR_BYTE, JMP_ADDR, RETI};
+use maikor_vm_core::headless::{assert_golden, run_frames};
+use maikor_vm_core::VM;
+
+fn golden(name: &str) -> String {
+    format!(
+        "{}/tests/visual/golden/{name}.ppm",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[test]
+fn test_moving_sprite() {
+    let mut vm = VM::new_test();
+    #[rustfmt::skip]
+    vm.debug_set_mem_range(address::PALETTES, &[
+        0, 0, 0,
+        255, 255, 255,
+        40, 120, 200,
+        200, 60, 60,
+    ]);
+    //tile 0 is a white border around red, tile 1 is stripes
+    for row in 0..8u16 {
+        let start = address::ATLAS1 + row * 100;
+        if row == 0 || row == 7 {
+            vm.debug_set_mem_range(start, &[0x11; 4]);
+        } else {
+            vm.debug_set_mem_range(start, &[0x13, 0x33, 0x33, 0x31]);
+        }
+        vm.debug_set_mem_range(start + 4, &[0x22, 0x00, 0x22, 0x00]);
+    }
+    for tile in 0..1320u16 {
+        vm.debug_set_mem(address::LAYERS + tile * 2, 1);
+    }
+    vm.debug_set_mem_range(
+        address::LAYER_HEADERS,
+        &LayerHeader::new(3, -5, true, 0, 0).to_bytes(),
+    );
+    vm.debug_set_mem_range(
+        address::SPRITE_TABLE,
+        &Sprite::new(10, 20, 0, false, false, 0, true, 1, false, false, 0, true).to_bytes(),
+    );
+    vm.debug_set_mem_range(
+        address::SPRITE_TABLE + 5,
+        &Sprite::new(100, 100, 0, false, false, 0, false, 1, true, true, 0, true).to_bytes(),
+    );
+    //move sprite 0 right one pixel each frame
+    vm.debug_set_mem_range(0, &[JMP_ADDR, 0, 0]);
+    #[rustfmt::skip]
+    vm.debug_set_mem_range(interrupt::IRQ_SCREEN_DRAW, &[
+        INC_ADDR_BYTE, (address::SPRITE_TABLE >> 8) as u8, address::SPRITE_TABLE as u8,
+        RETI,
+    ]);
+    vm.debug_set_mem(address::IRQ_CONTROL, interrupt_flags::IRQ_SCREEN_DRAW);
+
+    let buffer = run_frames(&mut vm, 30);
+    assert!(vm.error.is_none());
+    assert_eq!(vm.memory[address::SPRITE_TABLE as usize], 40);
+    assert_golden(&buffer, golden("moving_sprite"));
+}